@@ -25,6 +25,12 @@ settings = Settings...
 settings-title = Settings
 quit = Quit
 restore-notes = Restore note...
+search-notes = Search notes...
+search-title = Search notes
+search-placeholder = Text to find in notes
+search-nothing-found = No notes contain this text
+search-show = Show
+search-deleted = Recently deleted
 recently-deleted-title = Recently deleted notes
 recently-deleted-description = To restore any note press button on the right
 styles-list-description = Currently available note styles
@@ -34,12 +40,52 @@ new-style-name = style
 repository = Repository
 git-description = Git commit {$hash} on {$date}
 problem-text = Internal error: see errors in terminal or system log for details
+protocol-mismatch = Notes service speaks protocol version { $service } but the applet expects { $applet }: some commands are unavailable
+protocol-unknown = Notes service does not report its protocol version, it might be outdated
+service-connecting = Connecting notes service…
+service-failed = Notes service is not available: { $error }
+quick-capture-placeholder = Jot down a note and press Enter
+notify-load-failed = Notes are not loaded
+notify-save-failed = Notes are not saved, changes might be lost
+notify-export-failed = Notes are not exported
+notify-style-failed = Style is not changed
+notify-imported = Notes are imported
+notify-exported = Notes are exported
+reminder-due = Reminder at { $time }
+reminder-snooze = Snooze
+reminder-dismiss = Dismiss
+tags-placeholder = Tags separated by spaces or commas, e.g. #work #home
+tags-description = Show only notes having the tag
+hashtags-as-tags = #hashtags in the text are tags too
+tasks-done-down = Done down
+tasks-clear-done = Clear done
+copy-link = Copy link
+title-placeholder = Title, the first line if empty
+title-chars = Characters of the first line to title notes without the title
+find-placeholder = Text to find
+find-match-case = Aa
+find-previous = Previous
+find-next = Next
+find-close = Close
+replace-placeholder = Replace with
+replace-next = Replace
+replace-all = Replace all
+code-language-none = Text
+history-title = History of { $title }
+history-empty = The note has no previous versions yet
+history-note-deleted = The note was deleted
+history-diff = Changes made since this version
+history-rollback = Roll back
+history-description = Previous versions of every note are kept on finishing edits
+history-revisions = Versions to keep, 0 turns history off
+history-days = Days to keep versions, 0 is no limit
 edit-style-title = Edit selected style
 edit-style-comment = You can rename style and adjust background color, font style and font size.
 edit-style-name = Style name
 edit-style-font = Font
 edit-style-font-size = size
 edit-style-font-sample = Note text example
+edit-style-markdown = Render Markdown: headings, lists, emphasis, code and links
 edit-style-hex = HEX
 edit-style-rgb = RGB
 edit-style-bg = Background
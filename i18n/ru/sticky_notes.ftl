@@ -25,6 +25,12 @@ settings = Настройки...
 settings-title = Настройки
 quit = Завершить работу
 restore-notes = Восстановить заметку...
+search-notes = Найти заметку...
+search-title = Поиск заметок
+search-placeholder = Текст для поиска в заметках
+search-nothing-found = Этот текст не найден ни в одной заметке
+search-show = Показать
+search-deleted = Недавно удалена
 recently-deleted-title = Недавно удаленные заметки
 recently-deleted-description = Чтобы восстановить ранее удаленную заметку, найдите ее и нажмите кнопку справа
 styles-list-description = Стили отрисовки заметок
@@ -34,12 +40,52 @@ new-style-name = Стиль
 repository = Репозиторий
 git-description = Git commit {$hash} от {$date}
 problem-text = Внутренняя ошибка: см. сообщения об  ошибках в терминале или в системном журнале
+protocol-mismatch = Служба заметок использует протокол версии { $service }, а апплет ожидает { $applet }: некоторые команды недоступны
+protocol-unknown = Служба заметок не сообщает версию протокола, возможно, она устарела
+service-connecting = Подключение к службе заметок…
+service-failed = Служба заметок недоступна: { $error }
+quick-capture-placeholder = Запишите заметку и нажмите Enter
+notify-load-failed = Заметки не загружены
+notify-save-failed = Заметки не сохранены, изменения могут быть потеряны
+notify-export-failed = Заметки не экспортированы
+notify-style-failed = Стиль не изменён
+notify-imported = Заметки импортированы
+notify-exported = Заметки экспортированы
+reminder-due = Напоминание на { $time }
+reminder-snooze = Отложить
+reminder-dismiss = Закрыть
+tags-placeholder = Метки через пробел или запятую, например #работа #дом
+tags-description = Показать только заметки с меткой
+hashtags-as-tags = #хэштеги в тексте тоже считаются метками
+tasks-done-down = Готовые вниз
+tasks-clear-done = Убрать готовые
+copy-link = Копировать ссылку
+title-placeholder = Заголовок, если пусто - первая строка
+title-chars = Символов первой строки в заголовке заметки без заголовка
+find-placeholder = Текст для поиска
+find-match-case = Aa
+find-previous = Назад
+find-next = Далее
+find-close = Закрыть
+replace-placeholder = Заменить на
+replace-next = Заменить
+replace-all = Заменить все
+code-language-none = Текст
+history-title = История заметки «{ $title }»
+history-empty = У заметки пока нет прежних версий
+history-note-deleted = Заметка удалена
+history-diff = Изменения после этой версии
+history-rollback = Вернуть
+history-description = Прежние версии каждой заметки сохраняются после окончания правки
+history-revisions = Хранить версий, 0 отключает историю
+history-days = Хранить версии дней, 0 без ограничения
 edit-style-title = Настроить выбранный стиль
 edit-style-comment = Можно переименовать стиль, настроить цвет фона, а также стиль и размер шрифта.
 edit-style-name = Название
 edit-style-font = Шрифт
 edit-style-font-size = размер
 edit-style-font-sample = Пример текста заметки
+edit-style-markdown = Отображать Markdown: заголовки, списки, выделение, код и ссылки
 edit-style-hex = HEX
 edit-style-rgb = RGB
 edit-style-bg = Фон заметки
@@ -1,109 +1,43 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use crate::notifications::{self, Notification};
+pub use crate::{
+    config::APP_ID,
+    service::{
+        Command, NotesAppError, NotesStorage, ServiceFlags, ServiceFlagsError,
+        USAGE as SERVICE_USAGE, send_command,
+    },
+};
 #[cfg(feature = "cosmic")]
 pub use applet::AppletModel;
-use std::str::FromStr;
-use thiserror::Error;
-pub use {
-    service::{ServiceFlags, ServiceModel},
-    utils::to_f32,
-};
+pub use {headless::run_headless, service::ServiceModel, utils::to_f32};
 
 mod about_window;
 #[cfg(feature = "cosmic")]
 mod applet;
+mod clipboard;
+mod edit_context;
 mod edit_style;
+mod headless;
+mod history_view;
+mod markdown_view;
+mod notes_api;
+#[cfg(feature = "cosmic")]
+mod notes_list_view;
 mod restore_view;
+mod search_view;
 mod service;
+mod service_core;
 mod settings_view;
 mod sticky_window;
 mod styles_view;
 mod utils;
 
-const APP_ID: &str = "com.github.aae.sticky_notes";
-
-/// Messages emitted by the application and its widgets.
-#[derive(Debug, Clone, PartialEq)]
-pub enum Command {
-    Ignored, // dummy command
-    Connect,
-    Quit,
-    LoadNotes,
-    SaveNotes,
-    ImportNotes,
-    ExportNotes,
-    ShowAllNotes,
-    HideAllNotes,
-    LockAll,
-    RestoreNotes,
-    OpenSettings,
-    OpenAbout,
-}
-
-#[derive(Debug, Error)]
-pub enum NotesAppError {
-    // Failed reading source file
-    #[error("Failed parsing command: {0}")]
-    ParseError(String),
-}
-
-const CONNECT: &str = "CONNECT";
-const QUIT: &str = "QUIT";
-const LOAD: &str = "LOAD";
-const SAVE: &str = "SAVE";
-const IMPORT: &str = "IMPORT";
-const EXPORT: &str = "EXPORT";
-const SHOW: &str = "SHOW";
-const HIDE: &str = "HIDE";
-const LOCK: &str = "LOCK";
-const RESTORE: &str = "RESTORE";
-const SETTINGS: &str = "SETTINGS";
-const ABOUT: &str = "ABOUT";
-const IGNORED: &str = "IGNORED";
-
-impl std::fmt::Display for Command {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Command::Ignored => IGNORED,
-                Command::Connect => CONNECT,
-                Command::Quit => QUIT,
-                Command::LoadNotes => LOAD,
-                Command::SaveNotes => SAVE,
-                Command::ImportNotes => IMPORT,
-                Command::ExportNotes => EXPORT,
-                Command::ShowAllNotes => SHOW,
-                Command::HideAllNotes => HIDE,
-                Command::LockAll => LOCK,
-                Command::RestoreNotes => RESTORE,
-                Command::OpenSettings => SETTINGS,
-                Command::OpenAbout => ABOUT,
-            }
-        )
-    }
-}
-
-impl FromStr for Command {
-    type Err = NotesAppError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            IGNORED => Ok(Command::Ignored),
-            CONNECT => Ok(Self::Connect),
-            QUIT => Ok(Self::Quit),
-            LOAD => Ok(Self::LoadNotes),
-            SAVE => Ok(Self::SaveNotes),
-            IMPORT => Ok(Self::ImportNotes),
-            EXPORT => Ok(Self::ExportNotes),
-            SHOW => Ok(Self::ShowAllNotes),
-            HIDE => Ok(Self::HideAllNotes),
-            LOCK => Ok(Self::LockAll),
-            RESTORE => Ok(Self::RestoreNotes),
-            SETTINGS => Ok(Self::OpenSettings),
-            ABOUT => Ok(Self::OpenAbout),
-            _ => Err(NotesAppError::ParseError(s.to_string())),
+/// Shows desktop notifications, failures are only logged as there is nobody to tell about them
+pub async fn send_notifications(connection: &zbus::Connection, notifications: Vec<Notification>) {
+    for notification in notifications {
+        if let Err(e) = notifications::notify(connection, &notification).await {
+            tracing::warn!("failed notifying {notification:?}: {e}");
         }
     }
 }
@@ -141,11 +75,12 @@ mod popup_menu {
         widget,
     };
 
-    pub fn build_main_popup_view<F, P, M>(
-        core: &cosmic::Core,
+    pub fn build_main_popup_view<'a, F, P, M>(
+        core: &'a cosmic::Core,
         to_message: F,
         is_enabled: P,
-    ) -> Element<'_, M>
+        header: Option<Element<'a, M>>,
+    ) -> Element<'a, M>
     where
         // such requirements to Message are defined in cosmic::Application:
         M: Clone + std::fmt::Debug + Send + 'static,
@@ -169,16 +104,16 @@ mod popup_menu {
         }
 
         let mut import_export = widget::column::with_capacity(2);
-        if is_enabled(Command::ImportNotes) {
+        if is_enabled(Command::ImportNotes(None)) {
             import_export = import_export.push(
                 cosmic_applet::menu_button(widget::text::body(fl!("import")))
-                    .on_press(to_message(Command::ImportNotes)),
+                    .on_press(to_message(Command::ImportNotes(None))),
             );
         }
-        if is_enabled(Command::ExportNotes) {
+        if is_enabled(Command::ExportNotes(None)) {
             import_export = import_export.push(
                 cosmic_applet::menu_button(widget::text::body(fl!("export")))
-                    .on_press(to_message(Command::ExportNotes)),
+                    .on_press(to_message(Command::ExportNotes(None))),
             );
         }
 
@@ -202,7 +137,13 @@ mod popup_menu {
             );
         }
 
-        let mut settings_restore = widget::column::with_capacity(4);
+        let mut settings_restore = widget::column::with_capacity(5);
+        if is_enabled(Command::SearchNotes) {
+            settings_restore = settings_restore.push(
+                cosmic_applet::menu_button(widget::text::body(fl!("search-notes")))
+                    .on_press(to_message(Command::SearchNotes)),
+            );
+        }
         if is_enabled(Command::RestoreNotes) {
             settings_restore = settings_restore.push(
                 cosmic_applet::menu_button(widget::text::body(fl!("restore-notes")))
@@ -229,7 +170,16 @@ mod popup_menu {
         }
 
         let spacing = cosmic::theme::spacing();
+        // the caller's part of the popup like the list of notes is shown on top
+        let mut top = widget::column::with_capacity(2);
+        if let Some(header) = header {
+            top = top.push(header).push(
+                cosmic_applet::padded_control(widget::divider::horizontal::default())
+                    .padding([spacing.space_xxs, spacing.space_s]),
+            );
+        }
         let content = column![
+            top,
             save_load,
             cosmic_applet::padded_control(widget::divider::horizontal::default())
                 .padding([spacing.space_xxs, spacing.space_s]),
@@ -267,6 +217,7 @@ mod dropdown_menu {
             // fl!("show-all"), // don't use without applet
             // fl!("hide-all"), // don't use without applet
             fl!("lock-all"),
+            fl!("search-notes"),
             fl!("restore-notes"),
             fl!("settings"),
             fl!("about"),
@@ -275,11 +226,12 @@ mod dropdown_menu {
     }
 
     // dummy method is required to build StickyWindow when wayland is off
-    pub fn build_main_popup_view<F, P, M>(
-        _core: &cosmic::Core,
+    pub fn build_main_popup_view<'a, F, P, M>(
+        _core: &'a cosmic::Core,
         _to_message: F,
         _is_enabled: P,
-    ) -> Element<'_, M>
+        _header: Option<Element<'a, M>>,
+    ) -> Element<'a, M>
     where
         // such requirements to Message are defined in cosmic::Application:
         M: Clone + std::fmt::Debug + Send + 'static,
@@ -296,13 +248,14 @@ const fn get_popup_item_by_index(index: usize) -> Command {
     match index {
         0 => Command::LoadNotes,
         1 => Command::SaveNotes,
-        2 => Command::ImportNotes,
-        3 => Command::ExportNotes,
+        2 => Command::ImportNotes(None),
+        3 => Command::ExportNotes(None),
         4 => Command::LockAll,
-        5 => Command::RestoreNotes,
-        6 => Command::OpenSettings,
-        7 => Command::OpenAbout,
-        8 => Command::Quit,
+        5 => Command::SearchNotes,
+        6 => Command::RestoreNotes,
+        7 => Command::OpenSettings,
+        8 => Command::OpenAbout,
+        9 => Command::Quit,
         _ => Command::Ignored, // dummy command
     }
 }
@@ -1,21 +1,30 @@
 use crate::{
-    app::{Command, build_main_popup_view},
+    app::{
+        Command, build_main_popup_view,
+        notes_list_view::{build_notes_list, build_quick_capture},
+        send_command,
+    },
     config::Config,
-    icons,
+    dbus::{self, Backoff, Capabilities, NoteInfo, NotesProxy, PROTOCOL_VERSION, StyleInfo},
+    fl, icons,
 };
 use cosmic::prelude::*;
 use cosmic::{
-    applet,
-    cosmic_config::{self, CosmicConfigEntry},
-    dbus_activation::DbusActivationInterfaceProxy,
-    desktop,
+    applet, desktop,
     iced::{
-        self, Limits, Subscription,
+        self, Color, Limits, Subscription,
         window::{self, Id},
     },
     widget,
 };
-use std::{collections::HashMap, time::Duration};
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Duration,
+};
+use uuid::Uuid;
+
+// commands sent while the service is reconnecting wait for it, the oldest are dropped beyond
+const MAX_PENDING_COMMANDS: usize = 8;
 
 /// Messages emitted by the application and its widgets.
 #[derive(Debug, Clone)]
@@ -24,10 +33,52 @@ pub enum Message {
     TogglePopup,
     ClosePopupIfOpen,
     Signal(Command),
-    SignalResult(Command, bool), // (command, success or not)
+    SignalResult(Command, Result<(), Failure>),
     ZbusConnection(zbus::Result<zbus::Connection>),
-    DbusProxy(zbus::Result<DbusActivationInterfaceProxy<'static>>),
-    Timeout(u64), // waiting for u64 milliseconds have completed
+    DbusProxy(zbus::Result<NotesProxy<'static>>),
+    Capabilities(zbus::Result<Capabilities>),
+    NotesListed(zbus::Result<(Vec<NoteInfo>, Vec<StyleInfo>)>),
+    NoteVisible(Uuid, bool), // hides or shows the note keeping the popup open
+    CaptureInput(String),
+    CaptureStyle(usize), // the index of the style to create the note with
+    CaptureSubmit,
+    HealthCheck,
+    HealthResult(zbus::Result<bool>), // is the service running or not
+    Timeout(u64),                     // waiting for u64 milliseconds have completed
+}
+
+/// The reason of failed sending a command to the service
+#[derive(Debug, Clone, PartialEq)]
+pub enum Failure {
+    NotRunning,
+    DbusError(String),
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Failure::NotRunning => write!(f, "notes-service is not running"),
+            Failure::DbusError(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl From<&zbus::Error> for Failure {
+    fn from(e: &zbus::Error) -> Self {
+        if dbus::is_service_missing(e) {
+            Failure::NotRunning
+        } else {
+            Failure::DbusError(e.to_string())
+        }
+    }
+}
+
+/// What the applet knows about the service
+#[derive(Debug, Clone, PartialEq)]
+enum ServiceState {
+    Connecting,     // trying to connect, launching the service if it is not running
+    Running,        // connected, the service is checked periodically
+    Failed(String), // many attempts to connect have failed, still trying but rarely
 }
 
 pub struct AppletModel {
@@ -37,8 +88,24 @@ pub struct AppletModel {
     config: Config,
     main_popup_id: Option<Id>,
     zbus_connection: Option<zbus::Connection>,
-    dbus_proxy: Option<DbusActivationInterfaceProxy<'static>>,
-    dbus_object_path: String,
+    dbus_proxy: Option<NotesProxy<'static>>,
+    service_state: ServiceState,
+    backoff: Backoff,
+    // commands to send to the service as soon as it is connected
+    pending_commands: VecDeque<Command>,
+    // what the connected service supports, all commands are enabled until it is known
+    capabilities: Option<Capabilities>,
+    protocol_warning: Option<String>,
+    // notes listed in the popup and backgrounds of their styles by style IDs
+    notes: Vec<NoteInfo>,
+    backgrounds: HashMap<String, Color>,
+    // the text of the note being captured in the popup and its style
+    capture_input_id: widget::Id,
+    capture_text: String,
+    style_names: Vec<String>,
+    // IDs of styles in the order of their names, names might repeat
+    style_ids: Vec<String>,
+    capture_style: Option<usize>,
     icons: icons::IconSet,
 }
 
@@ -70,17 +137,13 @@ impl cosmic::Application for AppletModel {
         _flags: Self::Flags,
     ) -> (Self, Task<cosmic::Action<Self::Message>>) {
         // Load config
-        let config = cosmic_config::Config::new(Self::APP_ID, Config::VERSION)
-            .map(|context| match Config::get_entry(&context) {
-                Ok(config) => config,
-                Err((errors, config)) => {
-                    for why in errors {
-                        tracing::error!("error loading app config: {why}");
-                    }
-                    config
-                }
-            })
-            .unwrap_or_default();
+        let config = Config::load(Self::APP_ID);
+
+        let backoff = Backoff::new(
+            config.connect_service_pause_ms,
+            config.connect_service_max_pause_ms,
+        );
+        let health_check_period = config.health_check_period_ms;
 
         // Construct the app model with the runtime's core.
         let app = Self {
@@ -88,16 +151,40 @@ impl cosmic::Application for AppletModel {
             config,
             zbus_connection: None,
             dbus_proxy: None,
-            dbus_object_path: format!("/{}", Self::APP_ID.replace('.', "/")),
+            service_state: ServiceState::Connecting,
+            backoff,
+            pending_commands: VecDeque::new(),
+            capabilities: None,
+            protocol_warning: None,
+            notes: Vec::new(),
+            backgrounds: HashMap::new(),
+            capture_input_id: widget::Id::unique(),
+            capture_text: String::new(),
+            style_names: Vec::new(),
+            style_ids: Vec::new(),
+            capture_style: None,
             main_popup_id: None,
             icons: icons::IconSet::new(),
         };
 
-        let zbus_session_cmd = Task::perform(zbus::Connection::session(), |res| {
-            Message::ZbusConnection(res).into()
-        });
+        let mut startup_tasks = vec![Self::connect_session()];
+        // Launch background task to check the service is alive if health_check_period_ms > 0
+        if health_check_period > 0 {
+            tracing::debug!("launch checking notes-service every {health_check_period} msec");
+            startup_tasks.push(Task::stream(cosmic::iced_futures::stream::channel(
+                1,
+                async move |mut tx| {
+                    loop {
+                        tokio::time::sleep(Duration::from_millis(health_check_period)).await;
+                        if let Err(e) = tx.try_send(Message::HealthCheck.into()) {
+                            tracing::warn!("failed sending health check signal: {e}");
+                        }
+                    }
+                },
+            )));
+        }
 
-        (app, zbus_session_cmd)
+        (app, cosmic::task::batch(startup_tasks))
     }
 
     /// Describes the interface based on the current state of the application model.
@@ -105,9 +192,14 @@ impl cosmic::Application for AppletModel {
     /// Application events will be processed through the view. Any messages emitted by
     /// events received by widgets will be passed to the update method.
     fn view(&self) -> Element<'_, Self::Message> {
+        let icon = match self.service_state {
+            ServiceState::Connecting => self.icons.connecting(),
+            ServiceState::Running => self.icons.notes(),
+            ServiceState::Failed(_) => self.icons.failed(),
+        };
         self.core
             .applet
-            .icon_button_from_handle(self.icons.notes())
+            .icon_button_from_handle(icon)
             .on_press_down(Message::TogglePopup)
             .into()
     }
@@ -117,7 +209,12 @@ impl cosmic::Application for AppletModel {
         if let Some(window_id) = self.main_popup_id
             && window_id == id
         {
-            build_main_popup_view(self.core(), Message::Signal, |_| true)
+            build_main_popup_view(
+                self.core(),
+                Message::Signal,
+                |command| self.is_supported(&command),
+                self.build_popup_header(),
+            )
         } else {
             widget::text("").into()
         }
@@ -164,21 +261,32 @@ impl cosmic::Application for AppletModel {
                 if self.main_popup_id.is_some() {
                     return self.close_popup();
                 }
-                return self.open_popup();
+                let list_notes = self.list_notes();
+                return cosmic::task::batch(vec![
+                    self.open_popup()
+                        .chain(widget::text_input::focus(self.capture_input_id.clone())),
+                    list_notes,
+                ]);
             }
 
             Message::ZbusConnection(Err(e)) => {
                 tracing::error!("failed to connect to session dbus: {e}");
+                // the session bus might come up later than the panel
+                let pause = self.next_pause(e.to_string());
+                return Self::wait(pause).chain(Self::connect_session());
             }
 
             Message::ZbusConnection(Ok(conn)) => {
                 tracing::info!("established connection to dbus");
                 self.zbus_connection = Some(conn);
+                self.backoff.reset();
                 return self.try_build_dbus_proxy();
             }
 
             Message::DbusProxy(Err(e)) => {
                 tracing::error!("failed building dbus proxy: {e}");
+                let pause = self.next_pause(e.to_string());
+                return Self::wait(pause).chain(self.try_build_dbus_proxy());
             }
 
             Message::DbusProxy(Ok(proxy)) => {
@@ -190,34 +298,153 @@ impl cosmic::Application for AppletModel {
                 return self.send_command_via_dbus(Command::Connect);
             }
 
+            Message::Capabilities(Ok(capabilities)) => {
+                tracing::info!(
+                    "notes-service has protocol version {}",
+                    capabilities.protocol_version
+                );
+                self.protocol_warning = if capabilities.is_compatible() {
+                    None
+                } else {
+                    tracing::warn!("notes-applet has protocol version {PROTOCOL_VERSION}");
+                    Some(fl!(
+                        "protocol-mismatch",
+                        service = capabilities.protocol_version,
+                        applet = PROTOCOL_VERSION
+                    ))
+                };
+                self.capabilities = Some(capabilities);
+            }
+
+            Message::Capabilities(Err(e)) => {
+                // the service is older than the handshake, it might not understand some commands
+                tracing::warn!("failed getting capabilities of notes-service: {e}");
+                self.capabilities = None;
+                self.protocol_warning = Some(fl!("protocol-unknown"));
+            }
+
+            Message::NotesListed(Ok((notes, styles))) => {
+                self.notes = notes;
+                // keep the chosen style unless it is gone
+                let chosen_style = self
+                    .capture_style
+                    .and_then(|index| self.style_ids.get(index))
+                    .cloned();
+                self.style_names = styles.iter().map(|style| style.name.clone()).collect();
+                self.style_ids = styles.iter().map(|style| style.id.clone()).collect();
+                self.capture_style = chosen_style
+                    .and_then(|style_id| self.style_ids.iter().position(|id| *id == style_id))
+                    .or_else(|| styles.iter().position(|style| style.is_default));
+                self.backgrounds = styles
+                    .into_iter()
+                    .filter_map(|style| {
+                        let background = dbus::color_from_hex(&style.background).ok()?;
+                        Some((style.id, Color::from(background)))
+                    })
+                    .collect();
+            }
+
+            Message::NotesListed(Err(e)) => {
+                tracing::warn!("failed listing notes: {e}");
+                self.notes.clear();
+            }
+
+            Message::NoteVisible(note_id, is_visible) => {
+                let command = if is_visible {
+                    Command::ShowNote(note_id)
+                } else {
+                    Command::HideNote(note_id)
+                };
+                return self.send_or_queue(command).chain(self.list_notes());
+            }
+
+            Message::CaptureInput(text) => {
+                self.capture_text = text;
+            }
+
+            Message::CaptureStyle(index) => {
+                self.capture_style = Some(index);
+            }
+
+            Message::CaptureSubmit => {
+                if self.capture_text.trim().is_empty() {
+                    return Task::none();
+                }
+                let content = std::mem::take(&mut self.capture_text);
+                let style = self
+                    .capture_style
+                    .and_then(|index| self.style_ids.get(index))
+                    .cloned()
+                    .unwrap_or_default();
+                return self
+                    .send_or_queue(Command::NewNote(content, style))
+                    .chain(self.list_notes());
+            }
+
             Message::Signal(command) => {
                 tracing::debug!("requested {command}");
                 // close popup menu, then send signal via DBus
                 return Task::done(Message::ClosePopupIfOpen.into())
-                    .chain(self.send_command_via_dbus(command));
+                    .chain(self.send_or_queue(command));
             }
 
-            Message::SignalResult(command, success) => {
-                if success {
-                    tracing::debug!("successfully sent {command}");
-                } else {
-                    tracing::warn!("failed sending {command}");
-                    if command == Command::Connect {
-                        // waiting for a while to prevent spamming with attempts to connect the service, then repeat connecting again
-                        let pause = self.config.connect_service_pause_ms;
-                        let command_clone = command.clone();
-                        return Task::future(async move {
-                            tracing::warn!("wait for {pause} msec then repeat {command_clone}");
-                            tokio::time::sleep(Duration::from_millis(pause)).await;
-                            Message::Timeout(pause).into()
-                        })
-                        .chain(self.send_command_via_dbus(command));
-                    }
-                }
+            Message::SignalResult(command, result) => {
                 if let Command::Quit = command {
                     tracing::info!("finish working due to QUIT was sent to service");
                     return iced::exit();
                 }
+                match result {
+                    Ok(()) => {
+                        tracing::debug!("successfully sent {command}");
+                        if command == Command::Connect {
+                            tracing::info!("notes-service is running");
+                            self.service_state = ServiceState::Running;
+                            self.backoff.reset();
+                            // commands sent while connecting follow in order
+                            let pending: Vec<Command> = self.pending_commands.drain(..).collect();
+                            return pending
+                                .into_iter()
+                                .fold(self.query_capabilities(), |task, command| {
+                                    task.chain(self.send_command_via_dbus(command))
+                                });
+                        }
+                    }
+                    Err(failure) => {
+                        tracing::warn!("failed sending {command}: {failure}");
+                        if command == Command::Connect {
+                            return self.retry_connect(&failure);
+                        }
+                        if failure == Failure::NotRunning {
+                            // the command is sent again when the service is back
+                            self.queue_command(command);
+                            return self.reconnect();
+                        }
+                    }
+                }
+            }
+
+            Message::HealthCheck => {
+                if self.service_state == ServiceState::Running
+                    && let Some(connection) = self.zbus_connection.clone()
+                {
+                    return Task::perform(
+                        async move { dbus::is_service_running(&connection).await },
+                        |res| Message::HealthResult(res).into(),
+                    );
+                }
+            }
+
+            Message::HealthResult(Ok(true)) => {
+                tracing::trace!("notes-service is alive");
+            }
+
+            Message::HealthResult(Ok(false)) => {
+                tracing::warn!("notes-service has gone");
+                return self.reconnect();
+            }
+
+            Message::HealthResult(Err(e)) => {
+                tracing::error!("failed checking notes-service: {e}");
             }
 
             Message::Timeout(ms) => {
@@ -264,53 +491,177 @@ impl AppletModel {
     fn try_build_dbus_proxy(&self) -> Task<cosmic::Action<Message>> {
         if let Some(zbus_conn) = self.zbus_connection.clone() {
             tracing::info!("try building proxy client");
-            let path = self.dbus_object_path.clone();
-            match DbusActivationInterfaceProxy::builder(&zbus_conn)
-                .destination(<Self as cosmic::Application>::APP_ID)
-                //.ok()
-                .and_then(|b| b.path(path))
-                .and_then(|b| b.destination(<Self as cosmic::Application>::APP_ID))
-            {
-                Ok(proxy_builder) => {
-                    return Task::perform(async move { proxy_builder.build().await }, |res| {
-                        Message::DbusProxy(res).into()
-                    });
-                }
-                Err(e) => tracing::error!("failed building dbus proxy client: {e}"),
+            // the proxy is built even if the service is not running yet, calls will tell it
+            return Task::perform(async move { NotesProxy::new(&zbus_conn).await }, |res| {
+                Message::DbusProxy(res).into()
+            });
+        }
+        tracing::info!("failed building dbus proxy client: connection is not established yet");
+        Task::none()
+    }
+
+    fn popup_warning(&self) -> Option<String> {
+        match &self.service_state {
+            ServiceState::Connecting => Some(fl!("service-connecting")),
+            ServiceState::Running => self.protocol_warning.clone(),
+            ServiceState::Failed(e) => Some(fl!("service-failed", error = e.clone())),
+        }
+    }
+
+    // the warning about the service, the quick capture and the list of notes
+    fn build_popup_header(&self) -> Option<Element<'_, Message>> {
+        let warning = self.popup_warning();
+        let is_running = self.service_state == ServiceState::Running;
+        let can_capture =
+            is_running && self.is_supported(&Command::NewNote(String::new(), String::new()));
+        let has_notes =
+            is_running && !self.notes.is_empty() && self.is_supported(&Command::ShowAllNotes);
+        if warning.is_none() && !can_capture && !has_notes {
+            return None;
+        }
+        let mut header = widget::column::with_capacity(3);
+        if let Some(warning) = warning {
+            header = header.push(applet::padded_control(widget::text::body(warning)));
+        }
+        if can_capture {
+            header = header.push(applet::padded_control(build_quick_capture(
+                self.capture_input_id.clone(),
+                &self.capture_text,
+                &self.style_names,
+                self.capture_style,
+            )));
+        }
+        if has_notes {
+            header = header.push(build_notes_list(
+                &self.notes,
+                &self.backgrounds,
+                &self.icons,
+                self.config.toolbar_icon_size,
+            ));
+        }
+        Some(header.into())
+    }
+
+    fn list_notes(&self) -> Task<cosmic::Action<Message>> {
+        if let Some(proxy) = self.dbus_proxy.clone() {
+            Task::perform(
+                async move { zbus::Result::Ok((proxy.list_notes().await?, proxy.list_styles().await?)) },
+                |res| Message::NotesListed(res).into(),
+            )
+        } else {
+            Task::none()
+        }
+    }
+
+    // Connects the service again after it has gone, if the service is not running yet
+    // the attempts to connect it are already going on
+    fn reconnect(&mut self) -> Task<cosmic::Action<Message>> {
+        if self.service_state != ServiceState::Running {
+            return Task::none();
+        }
+        self.service_state = ServiceState::Connecting;
+        self.capabilities = None;
+        self.protocol_warning = None;
+        self.backoff.reset();
+        self.send_command_via_dbus(Command::Connect)
+    }
+
+    fn connect_session() -> Task<cosmic::Action<Message>> {
+        Task::perform(zbus::Connection::session(), |res| {
+            Message::ZbusConnection(res).into()
+        })
+    }
+
+    // returns the pause before the next attempt to connect, many failed attempts are shown
+    fn next_pause(&mut self, error: String) -> u64 {
+        let pause = self.backoff.next_pause_ms();
+        self.service_state = if self.backoff.is_exhausted() {
+            ServiceState::Failed(error)
+        } else {
+            ServiceState::Connecting
+        };
+        pause
+    }
+
+    fn wait(pause: u64) -> Task<cosmic::Action<Message>> {
+        Task::future(async move {
+            tokio::time::sleep(Duration::from_millis(pause)).await;
+            Message::Timeout(pause).into()
+        })
+    }
+
+    // Launches the service if it is not running, then waits for a while to prevent spamming
+    // with attempts to connect the service and repeats connecting again
+    fn retry_connect(&mut self, failure: &Failure) -> Task<cosmic::Action<Message>> {
+        let pause = self.next_pause(failure.to_string());
+        // other failures mean the service is running but doesn't respond properly,
+        // another instance would not help
+        let service_exec =
+            (*failure == Failure::NotRunning).then(|| self.config.service_bin.clone());
+        Task::future(async move {
+            if let Some(service_exec) = service_exec {
+                tracing::info!("trying to launch notes-service binary: {service_exec}");
+                desktop::spawn_desktop_exec(
+                    service_exec.as_str(),
+                    Vec::<(String, String)>::new(),
+                    Some(<Self as cosmic::Application>::APP_ID),
+                    false,
+                )
+                .await;
             }
+            tracing::warn!("wait for {pause} msec then repeat {}", Command::Connect);
+            tokio::time::sleep(Duration::from_millis(pause)).await;
+            Message::Timeout(pause).into()
+        })
+        .chain(self.send_command_via_dbus(Command::Connect))
+    }
+
+    fn query_capabilities(&self) -> Task<cosmic::Action<Message>> {
+        if let Some(proxy) = self.dbus_proxy.clone() {
+            Task::perform(async move { proxy.get_capabilities().await }, |res| {
+                Message::Capabilities(res).into()
+            })
         } else {
-            tracing::info!("failed building dbus proxy client: connection is not established yet");
+            Task::none()
         }
-        Task::none()
+    }
+
+    fn is_supported(&self, command: &Command) -> bool {
+        self.capabilities
+            .as_ref()
+            .is_none_or(|capabilities| capabilities.supports(command.name()))
+    }
+
+    // commands wait while the service is (re)connecting, QUIT is sent anyway to exit
+    fn send_or_queue(&mut self, command: Command) -> Task<cosmic::Action<Message>> {
+        if self.service_state == ServiceState::Running
+            || matches!(command, Command::Connect | Command::Quit)
+        {
+            self.send_command_via_dbus(command)
+        } else {
+            tracing::debug!("{command} waits for notes-service");
+            self.queue_command(command);
+            Task::none()
+        }
+    }
+
+    fn queue_command(&mut self, command: Command) {
+        if command == Command::Connect || self.pending_commands.contains(&command) {
+            return;
+        }
+        if self.pending_commands.len() == MAX_PENDING_COMMANDS {
+            self.pending_commands.pop_front();
+        }
+        self.pending_commands.push_back(command);
     }
 
     fn send_command_via_dbus(&self, command: Command) -> Task<cosmic::Action<Message>> {
-        if let Some(mut proxy) = self.dbus_proxy.clone() {
-            let command_str = command.to_string();
-            let service_exec = self.config.service_bin.clone();
+        if let Some(proxy) = self.dbus_proxy.clone() {
             Task::future(async move {
-                if let Err(e) = proxy
-                    .activate_action(command_str.as_str(), Vec::new(), HashMap::new())
+                let result = send_command(&proxy, &command)
                     .await
-                {
-                    tracing::error!("failed sending {command_str}: {e}");
-                    if command == Command::Quit {
-                        tracing::info!("skip trying to launch service because stop working");
-                    } else {
-                        //todo: test error before spawning service; valid candidates are: InterfaceNotFound, Failure(e)
-                        tracing::info!("trying to launch notes-service binary: {}", &service_exec);
-                        desktop::spawn_desktop_exec(
-                            service_exec.as_str(),
-                            Vec::<(String, String)>::new(),
-                            Some(<Self as cosmic::Application>::APP_ID),
-                            false,
-                        )
-                        .await;
-                    }
-                    Message::SignalResult(command, false).into()
-                } else {
-                    Message::SignalResult(command, true).into()
-                }
+                    .map_err(|e| Failure::from(&e));
+                Message::SignalResult(command, result).into()
             })
         } else {
             Task::none()
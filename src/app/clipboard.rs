@@ -0,0 +1,36 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use cosmic::iced::clipboard::mime::AllowedMimeTypes;
+use std::borrow::Cow;
+
+const PNG_MIME_TYPE: &str = "image/png";
+
+/// Image copied to the clipboard, read as PNG data to attach it to a note
+#[derive(Debug, Clone)]
+pub struct ClipboardImage(Vec<u8>);
+
+impl ClipboardImage {
+    pub const FILE_NAME: &str = "image.png";
+
+    pub fn get_data(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AllowedMimeTypes for ClipboardImage {
+    fn allowed() -> Cow<'static, [String]> {
+        Cow::Owned(vec![PNG_MIME_TYPE.to_string()])
+    }
+}
+
+impl TryFrom<(Vec<u8>, String)> for ClipboardImage {
+    type Error = String;
+
+    fn try_from((data, mime_type): (Vec<u8>, String)) -> Result<Self, Self::Error> {
+        if mime_type == PNG_MIME_TYPE && !data.is_empty() {
+            Ok(Self(data))
+        } else {
+            Err(format!("unsupported clipboard data of {mime_type}"))
+        }
+    }
+}
@@ -0,0 +1,624 @@
+use crate::notes::{
+    ListContinuation, continue_list, find_all, indent_line, outdent_line, replace_all,
+    toggle_line_task,
+};
+use cosmic::widget::{
+    self,
+    text_editor::{Action, Content, Cursor, Edit, Position},
+};
+use std::sync::Arc;
+
+// the number of edits to undo in the edit session
+const UNDO_LIMIT: usize = 100;
+
+/// The note being edited in the sticky window: the text with its undo history,
+/// the title and the find/replace bar
+pub struct EditContext {
+    /// currently edited content
+    content: Content,
+    /// currently edited title, the empty one titles the note by the first line
+    title: String,
+    // states of the content before edits, the latest last
+    undo: Vec<Snapshot>,
+    // states of the content undone, the latest undone last
+    redo: Vec<Snapshot>,
+    // characters typed one after another are undone at once
+    last_edit: Option<EditKind>,
+    find: Option<FindBar>,
+}
+
+struct Snapshot {
+    text: String,
+    cursor: Cursor,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum EditKind {
+    Typing,
+    Deleting,
+    Other,
+}
+
+/// The text to find and to replace it with, matches are selected in the editor one by one
+pub struct FindBar {
+    input_id: widget::Id,
+    query: String,
+    replacement: String,
+    is_replace: bool,
+    match_case: bool,
+    // index of the selected match
+    current: Option<usize>,
+    // the number of matches, it is counted again when the query or the text is changed
+    count: usize,
+}
+
+impl FindBar {
+    pub fn input_id(&self) -> widget::Id {
+        self.input_id.clone()
+    }
+
+    pub fn get_query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn get_replacement(&self) -> &str {
+        &self.replacement
+    }
+
+    pub fn is_replace(&self) -> bool {
+        self.is_replace
+    }
+
+    pub fn is_match_case(&self) -> bool {
+        self.match_case
+    }
+
+    pub fn get_current(&self) -> Option<usize> {
+        self.current
+    }
+}
+
+impl EditContext {
+    pub fn new(content: &str, title: &str) -> Self {
+        Self {
+            content: Content::with_text(content),
+            title: title.to_string(),
+            undo: Vec::new(),
+            redo: Vec::new(),
+            last_edit: None,
+            find: None,
+        }
+    }
+
+    pub fn content(&self) -> &Content {
+        &self.content
+    }
+
+    pub fn get_title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn set_title(&mut self, title: String) {
+        self.title = title;
+    }
+
+    pub fn text(&self) -> String {
+        self.content.text()
+    }
+
+    pub fn cursor(&self) -> Cursor {
+        self.content.cursor()
+    }
+
+    /// Places the cursor, the position out of the text is moved to its end
+    pub fn move_to(&mut self, position: Position) {
+        let text = self.content.text();
+        let offset = position_to_offset(&text, position);
+        self.content.move_to(Cursor {
+            position: offset_to_position(&text, offset),
+            selection: None,
+        });
+    }
+
+    /// Performs the editor action, edits are recorded to be undone.
+    /// Enter at the end of the list item begins the next item
+    pub fn perform(&mut self, action: Action) {
+        if matches!(action, Action::Edit(Edit::Enter)) && self.continue_list() {
+            self.update_match_count();
+            return;
+        }
+        let is_edit = matches!(action, Action::Edit(_));
+        match &action {
+            Action::Edit(edit) => {
+                let kind = match edit {
+                    Edit::Insert(c) if !c.is_whitespace() => EditKind::Typing,
+                    Edit::Backspace | Edit::Delete => EditKind::Deleting,
+                    _ => EditKind::Other,
+                };
+                if kind == EditKind::Other || self.last_edit != Some(kind) {
+                    self.push_undo();
+                }
+                self.last_edit = Some(kind);
+                self.redo.clear();
+            }
+            Action::Scroll { .. } => {}
+            // moving the cursor ends typing
+            _ => self.last_edit = None,
+        }
+        self.content.perform(action);
+        if is_edit {
+            self.update_match_count();
+        }
+    }
+
+    /// Returns whether there was an edit to undo
+    pub fn undo(&mut self) -> bool {
+        let Some(snapshot) = self.undo.pop() else {
+            return false;
+        };
+        let current = self.snapshot();
+        self.redo.push(current);
+        self.restore(snapshot);
+        true
+    }
+
+    /// Returns whether there was an undone edit to redo
+    pub fn redo(&mut self) -> bool {
+        let Some(snapshot) = self.redo.pop() else {
+            return false;
+        };
+        let current = self.snapshot();
+        self.undo.push(current);
+        self.restore(snapshot);
+        true
+    }
+
+    /// Indents lines under the cursor or the selection, outdents them if `forward` is false
+    pub fn indent(&mut self, forward: bool) {
+        let cursor = self.content.cursor();
+        let anchor = cursor.selection.unwrap_or(cursor.position).line;
+        let lines = anchor.min(cursor.position.line)..=anchor.max(cursor.position.line);
+        let text = self.content.text();
+        self.begin_edit();
+        let mut column = cursor.position.column;
+        for (index, line) in text.split('\n').enumerate() {
+            if !lines.contains(&index) {
+                continue;
+            }
+            let (changed, shift) = if forward {
+                (indent_line(line), None)
+            } else {
+                let (changed, removed) = outdent_line(line);
+                (changed, Some(removed))
+            };
+            if index == cursor.position.line {
+                column = match shift {
+                    None => column + (changed.len() - line.len()),
+                    Some(removed) => column.saturating_sub(removed),
+                };
+            }
+            self.replace_line(index, line.len(), &changed);
+        }
+        self.content.move_to(Cursor {
+            position: Position {
+                line: cursor.position.line,
+                column,
+            },
+            selection: None,
+        });
+        self.update_match_count();
+    }
+
+    /// Marks the task on the line under the cursor as done and vice versa,
+    /// another line becomes the open task
+    pub fn toggle_task(&mut self) {
+        let position = self.content.cursor().position;
+        let text = self.content.text();
+        let Some(line) = text.split('\n').nth(position.line) else {
+            return;
+        };
+        let changed = toggle_line_task(line);
+        self.begin_edit();
+        self.replace_line(position.line, line.len(), &changed);
+        // the cursor stays at the same text
+        let column = (position.column + changed.len()).saturating_sub(line.len());
+        self.content.move_to(Cursor {
+            position: Position {
+                line: position.line,
+                column: column.min(changed.len()),
+            },
+            selection: None,
+        });
+        self.update_match_count();
+    }
+
+    /// Inserts the text at the cursor replacing the selection
+    pub fn insert(&mut self, text: String) {
+        self.begin_edit();
+        self.content
+            .perform(Action::Edit(Edit::Paste(Arc::new(text))));
+        self.update_match_count();
+    }
+
+    // Enter at the end of the list item begins the next item, Enter at the end of the item
+    // without the text removes its marker, returns whether the list is continued
+    fn continue_list(&mut self) -> bool {
+        let cursor = self.content.cursor();
+        if cursor.selection.is_some() {
+            return false;
+        }
+        let text = self.content.text();
+        let Some(line) = text.split('\n').nth(cursor.position.line) else {
+            return false;
+        };
+        if cursor.position.column != line.len() {
+            return false;
+        }
+        match continue_list(line) {
+            Some(ListContinuation::Continue(marker)) => {
+                self.begin_edit();
+                self.content.perform(Action::Edit(Edit::Enter));
+                self.content
+                    .perform(Action::Edit(Edit::Paste(Arc::new(marker))));
+                true
+            }
+            Some(ListContinuation::End) => {
+                self.begin_edit();
+                self.replace_line(cursor.position.line, line.len(), "");
+                true
+            }
+            None => false,
+        }
+    }
+
+    // the edit made by the helper is undone at once
+    fn begin_edit(&mut self) {
+        self.push_undo();
+        self.redo.clear();
+        self.last_edit = None;
+    }
+
+    // replaces the text of the line which is `len` bytes long, the cursor is left after it
+    fn replace_line(&mut self, line: usize, len: usize, text: &str) {
+        self.content.move_to(Cursor {
+            position: Position { line, column: len },
+            selection: Some(Position { line, column: 0 }),
+        });
+        if !text.is_empty() {
+            self.content
+                .perform(Action::Edit(Edit::Paste(Arc::new(text.to_string()))));
+        } else if len > 0 {
+            // Delete without the selection would remove the line end
+            self.content.perform(Action::Edit(Edit::Delete));
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            text: self.content.text(),
+            cursor: self.content.cursor(),
+        }
+    }
+
+    fn push_undo(&mut self) {
+        let snapshot = self.snapshot();
+        // the edit which changed nothing, like Backspace at the beginning, is not recorded
+        if self
+            .undo
+            .last()
+            .is_some_and(|previous| previous.text == snapshot.text)
+        {
+            return;
+        }
+        if self.undo.len() == UNDO_LIMIT {
+            self.undo.remove(0);
+        }
+        self.undo.push(snapshot);
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.content = Content::with_text(&snapshot.text);
+        self.content.move_to(snapshot.cursor);
+        self.last_edit = None;
+        self.update_match_count();
+    }
+
+    // replaces the whole text as one edit to undo, the cursor is placed at `offset`
+    fn replace_text(&mut self, text: &str, offset: usize) {
+        self.begin_edit();
+        self.content = Content::with_text(text);
+        self.content.move_to(Cursor {
+            position: offset_to_position(text, offset),
+            selection: None,
+        });
+        self.update_match_count();
+    }
+
+    // operations of the find/replace bar
+
+    pub fn find_bar(&self) -> Option<&FindBar> {
+        self.find.as_ref()
+    }
+
+    /// Opens the find bar or switches it to replacing, returns the input to focus
+    pub fn open_find(&mut self, is_replace: bool) -> widget::Id {
+        let find = self.find.get_or_insert_with(|| FindBar {
+            input_id: widget::Id::unique(),
+            query: String::new(),
+            replacement: String::new(),
+            is_replace,
+            match_case: false,
+            current: None,
+            count: 0,
+        });
+        find.is_replace = is_replace;
+        find.input_id()
+    }
+
+    pub fn close_find(&mut self) {
+        self.find = None;
+    }
+
+    pub fn update_find_query(&mut self, query: String) {
+        if let Some(find) = &mut self.find {
+            find.query = query;
+            find.current = None;
+        }
+        self.update_match_count();
+    }
+
+    pub fn update_replacement(&mut self, replacement: String) {
+        if let Some(find) = &mut self.find {
+            find.replacement = replacement;
+        }
+    }
+
+    pub fn set_match_case(&mut self, on: bool) {
+        if let Some(find) = &mut self.find {
+            find.match_case = on;
+            find.current = None;
+        }
+        self.update_match_count();
+    }
+
+    /// Returns the number of matches of the find bar query in the text
+    pub fn count_matches(&self) -> usize {
+        self.find.as_ref().map_or(0, |find| find.count)
+    }
+
+    // the text is searched only while the find bar is open
+    fn update_match_count(&mut self) {
+        if let Some(find) = &mut self.find {
+            find.count = find_all(&self.content.text(), &find.query, find.match_case).len();
+        }
+    }
+
+    /// Selects the match after the cursor or before it, the search wraps around the text
+    pub fn find_next(&mut self, forward: bool) {
+        let Some(find) = &mut self.find else {
+            return;
+        };
+        let text = self.content.text();
+        let found = find_all(&text, &find.query, find.match_case);
+        let (from, to) = selection_range(&text, self.content.cursor());
+        let index = if forward {
+            found
+                .iter()
+                .position(|(start, _)| *start >= to)
+                .or((!found.is_empty()).then_some(0))
+        } else {
+            found
+                .iter()
+                .rposition(|(_, end)| *end <= from)
+                .or(found.len().checked_sub(1))
+        };
+        find.current = index;
+        if let Some((start, end)) = index.and_then(|index| found.get(index)) {
+            self.last_edit = None;
+            self.content.move_to(Cursor {
+                position: offset_to_position(&text, *end),
+                selection: Some(offset_to_position(&text, *start)),
+            });
+        }
+    }
+
+    /// Replaces the selected match and selects the next one, the first call only selects
+    /// the match if none is selected
+    pub fn replace_current(&mut self) {
+        let Some(find) = &self.find else {
+            return;
+        };
+        let text = self.content.text();
+        let found = find_all(&text, &find.query, find.match_case);
+        let selected = selection_range(&text, self.content.cursor());
+        let Some((start, end)) = find
+            .current
+            .and_then(|index| found.get(index))
+            .filter(|range| **range == selected)
+            .copied()
+        else {
+            self.find_next(true);
+            return;
+        };
+        let replaced = format!("{}{}{}", &text[..start], find.replacement, &text[end..]);
+        let offset = start + find.replacement.len();
+        self.replace_text(&replaced, offset);
+        self.find_next(true);
+    }
+
+    /// Replaces all matches, returns their number
+    pub fn replace_all(&mut self) -> usize {
+        let Some(find) = &mut self.find else {
+            return 0;
+        };
+        let text = self.content.text();
+        let (replaced, count) = replace_all(&text, &find.query, &find.replacement, find.match_case);
+        find.current = None;
+        if count > 0 {
+            let offset = position_to_offset(&text, self.content.cursor().position);
+            self.replace_text(&replaced, offset.min(replaced.len()));
+        }
+        count
+    }
+}
+
+// the editor position is the line and the byte index in the line
+fn position_to_offset(text: &str, position: Position) -> usize {
+    let mut offset = 0;
+    for (index, line) in text.split('\n').enumerate() {
+        if index == position.line {
+            return offset + floor_char_boundary(line, position.column);
+        }
+        offset += line.len() + 1;
+    }
+    text.len()
+}
+
+fn offset_to_position(text: &str, offset: usize) -> Position {
+    let before = &text[..floor_char_boundary(text, offset)];
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    Position {
+        line: before.matches('\n').count(),
+        column: before.len() - line_start,
+    }
+}
+
+fn floor_char_boundary(text: &str, offset: usize) -> usize {
+    let mut offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+// byte offsets of the selection ordered, both are the cursor without the selection
+fn selection_range(text: &str, cursor: Cursor) -> (usize, usize) {
+    let position = position_to_offset(text, cursor.position);
+    let anchor = cursor
+        .selection
+        .map_or(position, |selection| position_to_offset(text, selection));
+    (position.min(anchor), position.max(anchor))
+}
+
+#[test]
+fn convert_positions_and_offsets() {
+    // Cyrillic letters are two bytes long
+    let text = "ab\nпривет\n";
+    let position = |line, column| Position { line, column };
+    assert_eq!(position_to_offset(text, position(0, 1)), 1);
+    assert_eq!(position_to_offset(text, position(1, 0)), 3);
+    assert_eq!(position_to_offset(text, position(1, 4)), 7);
+    // the column inside of the character is moved to its beginning
+    assert_eq!(position_to_offset(text, position(1, 5)), 7);
+    // columns and lines out of the text are moved to the line end and the text end
+    assert_eq!(position_to_offset(text, position(1, 100)), 15);
+    assert_eq!(position_to_offset(text, position(5, 0)), text.len());
+
+    assert_eq!(offset_to_position(text, 1), position(0, 1));
+    assert_eq!(offset_to_position(text, 8), position(1, 4));
+    assert_eq!(offset_to_position(text, 15), position(1, 12));
+    assert_eq!(offset_to_position(text, 100), position(2, 0));
+
+    assert_eq!(floor_char_boundary("привет", 3), 2);
+    assert_eq!(floor_char_boundary("привет", 20), 12);
+    assert_eq!(floor_char_boundary("", 5), 0);
+
+    let selection = Cursor {
+        position: position(1, 4),
+        selection: Some(position(0, 1)),
+    };
+    assert_eq!(selection_range(text, selection), (1, 7));
+    let reversed = Cursor {
+        position: position(0, 1),
+        selection: Some(position(1, 4)),
+    };
+    assert_eq!(selection_range(text, reversed), (1, 7));
+    let cursor = Cursor {
+        position: position(1, 4),
+        selection: None,
+    };
+    assert_eq!(selection_range(text, cursor), (7, 7));
+}
+
+#[test]
+fn undo_typed_words_at_once() {
+    use cosmic::widget::text_editor::Motion;
+
+    let mut context = EditContext::new("", "");
+    let type_text = |context: &mut EditContext, text: &str| {
+        for c in text.chars() {
+            context.perform(Action::Edit(Edit::Insert(c)));
+        }
+        context.text()
+    };
+    let empty = context.text();
+    let word = type_text(&mut context, "ab");
+    let space = type_text(&mut context, " ");
+    let next_word = type_text(&mut context, "cd");
+    // moving the cursor ends the typed word
+    context.perform(Action::Move(Motion::Left));
+    type_text(&mut context, "e");
+
+    assert!(context.undo());
+    assert_eq!(context.text(), next_word);
+    assert!(context.undo());
+    assert_eq!(context.text(), space);
+    assert!(context.undo());
+    assert_eq!(context.text(), word);
+    assert!(context.undo());
+    assert_eq!(context.text(), empty);
+    assert!(!context.undo());
+
+    assert!(context.redo());
+    assert_eq!(context.text(), word);
+    // the new edit drops undone ones
+    type_text(&mut context, "x");
+    assert!(!context.redo());
+}
+
+#[test]
+fn keep_limited_number_of_edits() {
+    let mut context = EditContext::new("", "");
+    // every space is a separate edit
+    for _ in 0..UNDO_LIMIT + 50 {
+        context.perform(Action::Edit(Edit::Insert(' ')));
+    }
+    let mut undone = 0;
+    while context.undo() {
+        undone += 1;
+    }
+    assert_eq!(undone, UNDO_LIMIT);
+}
+
+#[test]
+fn replace_matches_and_undo() {
+    let mut context = EditContext::new("cat dog cat", "");
+    let original = context.text();
+    context.open_find(true);
+    context.update_find_query("cat".to_string());
+    context.update_replacement("bird".to_string());
+    assert_eq!(context.count_matches(), 2);
+
+    // the first call only selects the match
+    context.replace_current();
+    assert_eq!(context.text(), original);
+    assert_eq!(context.find_bar().and_then(FindBar::get_current), Some(0));
+    context.replace_current();
+    let first_replaced = original.replacen("cat", "bird", 1);
+    assert_eq!(context.text(), first_replaced);
+    assert_eq!(context.count_matches(), 1);
+    context.replace_current();
+    assert_eq!(context.text(), original.replace("cat", "bird"));
+    assert_eq!(context.count_matches(), 0);
+    assert!(context.undo());
+    assert_eq!(context.text(), first_replaced);
+    assert!(context.undo());
+    assert_eq!(context.text(), original);
+
+    // all matches are replaced as one edit
+    assert_eq!(context.replace_all(), 2);
+    assert_eq!(context.text(), original.replace("cat", "bird"));
+    assert!(context.undo());
+    assert_eq!(context.text(), original);
+    assert_eq!(context.count_matches(), 2);
+}
@@ -1,6 +1,6 @@
 use super::{
     service::Message,
-    utils::{cosmic_font, with_background},
+    utils::{background_color, cosmic_font, with_background},
 };
 use crate::{
     fl,
@@ -25,6 +25,7 @@ pub struct EditStyleDialog {
     color_picker_model: widget::ColorPickerModel,
     avail_fonts: Vec<String>,
     font_size_text: String,
+    is_markdown: bool,
     is_new: bool,
 }
 
@@ -36,15 +37,16 @@ impl EditStyleDialog {
             style_id,
             name: style.get_name().to_string(),
             font,
-            bgcolor: style.get_background_color(),
+            bgcolor: background_color(style),
             color_picker_model: widget::ColorPickerModel::new(
                 fl!("edit-style-hex"),
                 fl!("edit-style-rgb"),
-                Some(style.get_background_color()),
-                Some(style.get_background_color()),
+                Some(background_color(style)),
+                Some(background_color(style)),
             ),
             avail_fonts: get_avail_fonts().iter().map(ToString::to_string).collect(),
             font_size_text,
+            is_markdown: style.is_markdown(),
             is_new,
         }
     }
@@ -66,6 +68,10 @@ impl EditStyleDialog {
         self.font_size_text = font_size.to_string();
     }
 
+    pub fn update_markdown(&mut self, is_markdown: bool) {
+        self.is_markdown = is_markdown;
+    }
+
     pub fn get_id(&self) -> Uuid {
         self.style_id
     }
@@ -82,6 +88,10 @@ impl EditStyleDialog {
         self.bgcolor
     }
 
+    pub fn is_markdown(&self) -> bool {
+        self.is_markdown
+    }
+
     pub fn on_color_picker_update(
         &mut self,
         event: ColorPickerUpdate,
@@ -128,7 +138,7 @@ impl EditStyleDialog {
     }
 
     fn build_edit_style_control(&self) -> Element<'_, Message> {
-        widget::column::with_capacity(4)
+        widget::column::with_capacity(5)
             .spacing(cosmic::theme::spacing().space_m)
             .push(
                 widget::row::with_capacity(1).push(
@@ -171,6 +181,13 @@ impl EditStyleDialog {
                     .font(cosmic_font(self.font.style))
                     .size(self.font.size),
             )
+            .push(
+                widget::row::with_capacity(2)
+                    .spacing(cosmic::theme::spacing().space_m)
+                    .align_y(Alignment::Center)
+                    .push(widget::text(fl!("edit-style-markdown")).width(Length::Fill))
+                    .push(widget::toggler(self.is_markdown).on_toggle(Message::MarkdownUpdate)),
+            )
             .push(
                 widget::column::with_capacity(2)
                     .spacing(cosmic::theme::spacing().space_m)
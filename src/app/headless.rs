@@ -0,0 +1,388 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::{
+    APP_ID, Command, NotesStorage, ServiceFlags,
+    notes_api::{self, PendingRequest, Request, Response},
+    send_notifications,
+    service_core::{Followup, ServiceCore},
+};
+use crate::{notes::NotesFormat, notifications};
+use chrono::{DateTime, Utc};
+use cosmic::iced_futures::futures::{SinkExt, Stream, StreamExt, channel::mpsc};
+use std::{collections::HashMap, ops::ControlFlow, time::Duration};
+use tokio::{
+    signal::unix::{SignalKind, signal},
+    time::{Instant, Interval},
+};
+use zbus::{object_server::SignalEmitter, zvariant::OwnedValue};
+
+/// Runs notes-service without windows until QUIT command, SIGINT or SIGTERM.
+/// The notes collection is loaded, autosaved and saved on exit the same way as with windows,
+/// both the notes API and D-Bus activation commands are served on the session bus.
+///
+/// # Errors
+///
+/// Returns error if the session bus is not available or notes-service is already running
+pub fn run_headless(flags: ServiceFlags) -> anyhow::Result<()> {
+    tokio::runtime::Runtime::new()?.block_on(serve(flags))
+}
+
+async fn serve(flags: ServiceFlags) -> anyhow::Result<()> {
+    let storage = NotesStorage::new(&flags);
+    let config = storage.load_config();
+    let mut service = ServiceCore::new(storage, config);
+
+    let (request_sender, mut requests) = mpsc::channel(1);
+    let notes_api = notes_api::connect(
+        request_sender,
+        notes_api::capabilities(false, service.storage.location()),
+    )
+    .await?;
+    let signal_emitter = notes_api::signal_emitter(&notes_api)?;
+    // reminders are snoozed or dismissed by actions of their notifications
+    let mut actions = notifications::receive_actions(&notes_api)
+        .await
+        .inspect_err(|e| tracing::warn!("failed receiving actions of notifications: {e}"))
+        .ok();
+    let (command_sender, mut commands) = mpsc::channel(1);
+    let _activation = zbus::connection::Builder::session()?
+        .name(APP_ID)?
+        .serve_at(
+            format!("/{}", APP_ID.replace('.', "/")),
+            ActivationInterface {
+                sender: command_sender,
+            },
+        )?
+        .build()
+        .await?;
+    tracing::info!("notes-service is running without windows");
+
+    // the same startup sequence as with windows: import, then create the note given by --new
+    if let Some((import_file, format)) = flags.import {
+        import_notes(&mut service, import_file, format).await;
+    } else if service.notes.is_default_collection() {
+        let import_file = service.config.import_file.clone();
+        import_notes(&mut service, import_file, NotesFormat::default()).await;
+    }
+    if let Some((content, style)) = flags.new_note {
+        service.apply(&Request::CreateNote(content, style));
+    }
+
+    let mut autosave = (service.config.autosave_period_ms > 0).then(|| {
+        let period = Duration::from_millis(service.config.autosave_period_ms);
+        tokio::time::interval_at(Instant::now() + period, period)
+    });
+    let mut reminders = (service.config.reminder_check_period_ms > 0).then(|| {
+        let period = Duration::from_millis(service.config.reminder_check_period_ms);
+        tokio::time::interval(period)
+    });
+    let mut terminate = signal(SignalKind::terminate())?;
+    loop {
+        tokio::select! {
+            Some(pending) = requests.next() => {
+                if on_notes_request(&mut service, &pending).await.is_break() {
+                    break;
+                }
+            }
+            Some(command) = commands.next() => {
+                if on_command(&mut service, &command).await.is_break() {
+                    break;
+                }
+            }
+            () = tick(autosave.as_mut()) => service.autosave(),
+            () = tick(reminders.as_mut()) => on_reminders(&mut service, Utc::now()),
+            Some(key) = next_action(actions.as_mut()) => {
+                if let Some(request) = service.on_notification_action(&key) {
+                    service.apply(&request);
+                }
+            }
+            _ = tokio::signal::ctrl_c() => {
+                tracing::info!("interrupted");
+                break;
+            }
+            _ = terminate.recv() => {
+                tracing::info!("terminated");
+                break;
+            }
+        }
+        notify_clients(&mut service, &signal_emitter).await;
+    }
+    service.quit();
+    notify_clients(&mut service, &signal_emitter).await;
+    Ok(())
+}
+
+// never completes if actions of notifications are not received
+async fn next_action(actions: Option<&mut (impl Stream<Item = String> + Unpin)>) -> Option<String> {
+    match actions {
+        Some(actions) => actions.next().await,
+        None => std::future::pending().await,
+    }
+}
+
+// never completes if the interval is off
+async fn tick(interval: Option<&mut Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+async fn on_notes_request(service: &mut ServiceCore, pending: &PendingRequest) -> ControlFlow<()> {
+    tracing::debug!("handling D-Bus request {:?}", pending.request());
+    if let Request::Command(command) = pending.request() {
+        pending.respond(Ok(Response::Done));
+        return on_command(service, command).await;
+    }
+    match notes_api::apply(&mut service.notes, &service.config, pending.request()) {
+        Ok(response) => pending.respond(Ok(response)),
+        Err(e) => {
+            tracing::warn!("failed D-Bus request {:?}: {e}", pending.request());
+            pending.respond(Err(e.to_string()));
+        }
+    }
+    ControlFlow::Continue(())
+}
+
+// there are no windows to follow notes, so import and export are awaited right away
+async fn on_command(service: &mut ServiceCore, command: &Command) -> ControlFlow<()> {
+    match service.on_command(command) {
+        Followup::Quit => return ControlFlow::Break(()),
+        Followup::Request(request) => service.apply(&request),
+        Followup::Import(file, format) => import_notes(service, file, format).await,
+        Followup::Export(file, format) => {
+            let attachments_dir = service.storage.attachments_dir().to_path_buf();
+            let result =
+                ServiceCore::export_notes(file, service.notes.clone(), format, attachments_dir)
+                    .await;
+            service.on_exported(result);
+        }
+        Followup::Windowed => tracing::debug!("{command} requires windows, ignored"),
+        Followup::Done | Followup::Windows => {}
+    }
+    ControlFlow::Continue(())
+}
+
+async fn import_notes(service: &mut ServiceCore, file: String, format: NotesFormat) {
+    let attachments_dir = service.storage.attachments_dir().to_path_buf();
+    let result = ServiceCore::import_notes(file, format, attachments_dir).await;
+    service.on_imported(result);
+}
+
+// there are no windows to raise, so the due note is notified about and made visible
+fn on_reminders(service: &mut ServiceCore, now: DateTime<Utc>) {
+    for note_id in service.ring_reminders(now) {
+        service.apply(&Request::SetNoteVisible(note_id, true));
+    }
+}
+
+// D-Bus clients are notified about changes made since the previous call,
+// desktop notifications are shown on the same bus
+async fn notify_clients(service: &mut ServiceCore, emitter: &SignalEmitter<'_>) {
+    notes_api::emit(emitter, service.change_signals()).await;
+    send_notifications(emitter.connection(), service.take_notifications()).await;
+}
+
+/// The same D-Bus activation interface as libcosmic serves for windowed notes-service,
+/// so notes-applet and notes-cli send commands without knowing how the service runs
+struct ActivationInterface {
+    sender: mpsc::Sender<Command>,
+}
+
+#[zbus::interface(name = "org.freedesktop.DbusActivation")]
+impl ActivationInterface {
+    async fn activate(&self, _platform_data: HashMap<String, OwnedValue>) {}
+
+    async fn open(&self, _uris: Vec<String>, _platform_data: HashMap<String, OwnedValue>) {}
+
+    async fn activate_action(
+        &self,
+        action_name: &str,
+        parameter: Vec<String>,
+        _platform_data: HashMap<String, OwnedValue>,
+    ) -> zbus::fdo::Result<()> {
+        tracing::info!("handling dbus_activation message {action_name}");
+        let command = Command::from_action(action_name, &parameter)
+            .map_err(|e| zbus::fdo::Error::InvalidArgs(e.to_string()))?;
+        self.sender
+            .clone()
+            .send(command)
+            .await
+            .map_err(|e| zbus::fdo::Error::Failed(format!("notes-service is not responding: {e}")))
+    }
+}
+
+// removes the data directory of the test service when the test ends, failed one too
+#[cfg(test)]
+struct DataDirGuard(std::path::PathBuf);
+
+#[cfg(test)]
+impl Drop for DataDirGuard {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+// the service keeping notes in its own temporary data directory
+#[cfg(test)]
+fn test_service(config: crate::config::Config) -> (ServiceCore, DataDirGuard) {
+    let data_dir = std::env::temp_dir().join(format!("sticky-notes-{}", uuid::Uuid::new_v4()));
+    let flags = ServiceFlags {
+        data_dir: Some(data_dir.clone()),
+        ..Default::default()
+    };
+    (
+        ServiceCore::new(NotesStorage::new(&flags), config),
+        DataDirGuard(data_dir),
+    )
+}
+
+#[tokio::test]
+async fn commands_without_windows() {
+    use crate::config::Config;
+
+    let (mut service, _data_dir) = test_service(Config::default());
+    let count = service.notes.get_notes_count();
+
+    let new_note = Command::NewNote("buy milk".to_string(), String::new());
+    assert!(on_command(&mut service, &new_note).await.is_continue());
+    assert!(
+        on_command(&mut service, &Command::HideAllNotes)
+            .await
+            .is_continue()
+    );
+    assert!(service.notes.is_unsaved());
+    assert!(
+        on_command(&mut service, &Command::SaveNotes)
+            .await
+            .is_continue()
+    );
+    assert!(!service.notes.is_unsaved());
+    assert!(on_command(&mut service, &Command::Quit).await.is_break());
+
+    // the next start loads saved notes
+    let service = ServiceCore::new(service.storage.clone(), Config::default());
+    assert_eq!(service.notes.get_notes_count(), count + 1);
+    assert!(
+        service
+            .notes
+            .iter_notes()
+            .any(|(_, note)| note.get_content() == "buy milk" && !note.is_visible())
+    );
+}
+
+#[tokio::test]
+async fn failures_are_notified() {
+    use crate::{config::Config, notifications::Category};
+
+    let (mut service, data_dir) = test_service(Config {
+        notify_import_export_done: true,
+        ..Config::default()
+    });
+    let missing_file = data_dir.0.join("missing").join("notes.json");
+    let missing_file = missing_file.display().to_string();
+
+    let export = Command::ExportNotes(Some((missing_file.clone(), NotesFormat::Json)));
+    assert!(on_command(&mut service, &export).await.is_continue());
+    let import = Command::ImportNotes(Some((missing_file, NotesFormat::Json)));
+    assert!(on_command(&mut service, &import).await.is_continue());
+    let categories: Vec<Category> = service
+        .take_notifications()
+        .iter()
+        .map(|notification| notification.category)
+        .collect();
+    assert_eq!(categories, [Category::ExportFailed, Category::LoadFailed]);
+
+    // turned off categories are not notified
+    service.config.notify_load_failed = false;
+    import_notes(&mut service, String::new(), NotesFormat::Json).await;
+    assert!(service.take_notifications().is_empty());
+}
+
+#[tokio::test]
+async fn due_reminders_are_notified() {
+    use crate::{
+        config::Config,
+        notes::{Reminder, Repeat},
+        notifications::Category,
+    };
+
+    let (mut service, _data_dir) = test_service(Config::default());
+    let now = Utc::now();
+    let note_id = service.notes.new_note();
+    let note = service
+        .notes
+        .try_get_note_mut(&note_id)
+        .expect("new note must exist");
+    note.set_content("call back".to_string());
+    note.set_visibility(false);
+    note.set_reminder(Some(Reminder::new(now, Repeat::Once)));
+
+    on_reminders(&mut service, now);
+    let notifications = service.take_notifications();
+    let [notification] = notifications.as_slice() else {
+        panic!("one notification is expected instead of {notifications:?}");
+    };
+    assert_eq!(
+        (notification.category, notification.summary.as_str()),
+        (Category::Reminder, "call back")
+    );
+    let note = service.notes.try_get_note(&note_id).expect("note exists");
+    assert!(note.is_visible());
+    assert!(note.get_reminder().is_some_and(Reminder::is_ringing));
+
+    // actions of the notification snooze or dismiss the reminder, other actions are ignored
+    let requests: Vec<Option<Request>> = notification
+        .actions
+        .iter()
+        .map(|(key, _)| service.on_notification_action(key))
+        .collect();
+    assert_eq!(
+        requests,
+        [
+            Some(Request::SnoozeReminder(note_id, 10)),
+            Some(Request::DismissReminder(note_id))
+        ]
+    );
+    assert_eq!(service.on_notification_action("default"), None);
+
+    // the ringing reminder is notified once, the dismissed one-off reminder is gone
+    on_reminders(&mut service, now);
+    assert!(service.take_notifications().is_empty());
+    service.apply(&Request::DismissReminder(note_id));
+    let note = service.notes.try_get_note(&note_id).expect("note exists");
+    assert!(note.get_reminder().is_none());
+}
+
+#[tokio::test]
+async fn attachments_are_removed_with_deleted_notes_on_quit() {
+    use crate::{config::Config, notes::try_attach_file};
+
+    let (mut service, data_dir) = test_service(Config::default());
+    let source = data_dir.0.join("photo.png");
+    std::fs::create_dir_all(&data_dir.0).expect("data directory is created");
+    std::fs::write(&source, b"image").expect("attached file is written");
+    let attachments_dir = service.storage.attachments_dir().to_path_buf();
+    let attach = |service: &mut ServiceCore| {
+        let note_id = service.notes.new_note();
+        let attachment = try_attach_file(&attachments_dir, &source).expect("file is attached");
+        let file = attachments_dir.join(attachment.get_file());
+        service
+            .notes
+            .try_get_note_mut(&note_id)
+            .expect("new note must exist")
+            .add_attachment(attachment);
+        (note_id, file)
+    };
+    let (_, kept) = attach(&mut service);
+    let (deleted_id, removed) = attach(&mut service);
+
+    // the deleted note might be restored until exit, so its file is kept on saving
+    service.notes.delete_note(deleted_id);
+    assert!(service.save_notes());
+    assert!(kept.exists() && removed.exists());
+    service.quit();
+    assert!(kept.exists() && !removed.exists());
+}
@@ -0,0 +1,131 @@
+use super::service::Message;
+use crate::{
+    fl,
+    notes::{DiffLine, NotesCollection, diff_lines},
+};
+use cosmic::prelude::*;
+use cosmic::{
+    iced::{Alignment, Color, Length},
+    widget,
+};
+use uuid::Uuid;
+
+const REVISION_TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+const ADDED_COLOR: Color = Color::from_rgb(0.18, 0.55, 0.24);
+const REMOVED_COLOR: Color = Color::from_rgb(0.75, 0.16, 0.16);
+
+/// The window listing previous versions of the note content, the selected one is compared
+/// with the current content and may replace it
+pub struct HistoryView {
+    note_id: Uuid,
+    // index of the selected revision, the oldest is 0
+    selected: Option<usize>,
+}
+
+impl HistoryView {
+    pub fn new(note_id: Uuid) -> Self {
+        Self {
+            note_id,
+            selected: None,
+        }
+    }
+
+    pub fn get_note_id(&self) -> Uuid {
+        self.note_id
+    }
+
+    pub fn select(&mut self, index: Option<usize>) {
+        self.selected = index;
+    }
+
+    /// The locked note and the note being edited can't be rolled back
+    pub fn build_view<'a>(
+        &'a self,
+        notes: &'a NotesCollection,
+        is_editing: bool,
+    ) -> Element<'a, Message> {
+        let spacing = cosmic::theme::spacing();
+        let Ok(note) = notes.try_get_note(&self.note_id) else {
+            return widget::text(fl!("history-note-deleted")).into();
+        };
+        let revisions = note.get_revisions();
+        if revisions.is_empty() {
+            return widget::text(fl!("history-empty")).into();
+        }
+        // the latest revision is on top
+        let list = revisions.iter().enumerate().rev().fold(
+            widget::column::with_capacity(revisions.len()).spacing(spacing.space_xxs),
+            |list, (index, revision)| {
+                let first_line = revision.get_content().lines().next().unwrap_or_default();
+                let item = widget::row::with_capacity(2)
+                    .spacing(spacing.space_s)
+                    .align_y(Alignment::Center)
+                    .push(widget::text::caption(
+                        revision.get_time().format(REVISION_TIME_FORMAT).to_string(),
+                    ))
+                    .push(widget::text::body(first_line).width(Length::Fill));
+                let button = widget::button::custom(item)
+                    .width(Length::Fill)
+                    .on_press(Message::HistorySelect(index));
+                list.push(if self.selected == Some(index) {
+                    button.class(cosmic::theme::Button::Suggested)
+                } else {
+                    button.class(cosmic::theme::Button::Text)
+                })
+            },
+        );
+        let mut view = widget::column::with_capacity(3)
+            .spacing(spacing.space_s)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .push(
+                widget::scrollable(list)
+                    .width(Length::Fill)
+                    .height(Length::FillPortion(1)),
+            );
+        if let Some((index, revision)) = self
+            .selected
+            .and_then(|index| revisions.get(index).map(|revision| (index, revision)))
+        {
+            let rollback = widget::button::suggested(fl!("history-rollback"));
+            let rollback = if note.is_locked() || is_editing {
+                rollback
+            } else {
+                rollback.on_press(Message::HistoryRollback(index))
+            };
+            view = view
+                .push(
+                    widget::row::with_capacity(2)
+                        .spacing(spacing.space_s)
+                        .align_y(Alignment::Center)
+                        .push(widget::text::heading(fl!("history-diff")).width(Length::Fill))
+                        .push(rollback),
+                )
+                .push(
+                    widget::scrollable(build_diff_view(revision.get_content(), note.get_content()))
+                        .width(Length::Fill)
+                        .height(Length::FillPortion(2)),
+                );
+        }
+        view.into()
+    }
+}
+
+// lines of the revision missing in the current content are red, new lines are green
+fn build_diff_view<'a>(revision: &str, current: &str) -> Element<'a, Message> {
+    let diff = diff_lines(revision, current);
+    diff.into_iter()
+        .fold(
+            widget::column::with_capacity(8).width(Length::Fill),
+            |column, line| {
+                column.push(match line {
+                    DiffLine::Same(text) => widget::text::monotext(format!("  {text}")),
+                    DiffLine::Removed(text) => widget::text::monotext(format!("- {text}"))
+                        .class(cosmic::style::Text::Color(REMOVED_COLOR)),
+                    DiffLine::Added(text) => widget::text::monotext(format!("+ {text}"))
+                        .class(cosmic::style::Text::Color(ADDED_COLOR)),
+                })
+            },
+        )
+        .into()
+}
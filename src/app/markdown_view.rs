@@ -0,0 +1,207 @@
+use super::{service::Message, utils::cosmic_font};
+use crate::notes::{ChecklistPart, NoteStyle, find_links, split_tasks};
+use cosmic::prelude::*;
+use cosmic::{
+    font::{self, Font},
+    iced::{
+        Alignment, Border, Color, Length, Padding, highlighter,
+        widget::{markdown, rich_text, span, text},
+    },
+    iced_core::text::Highlighter as _,
+    widget,
+};
+
+// pixels per a space of the task indent
+const INDENT_STEP: f32 = 4.0;
+
+/// Markdown of the note parsed when its content changes rather than on every view,
+/// tasks are split out of it to be toggled by the line number
+#[derive(Debug, Default)]
+pub struct MarkdownContent {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug)]
+enum Part {
+    Markdown(Vec<markdown::Item>),
+    Task {
+        line: usize,
+        indent: usize,
+        is_done: bool,
+        items: Vec<markdown::Item>,
+    },
+}
+
+impl MarkdownContent {
+    pub fn parse(content: &str) -> Self {
+        let parts = split_tasks(content)
+            .into_iter()
+            .map(|part| match part {
+                ChecklistPart::Text(text) => Part::Markdown(markdown::parse(&text).collect()),
+                ChecklistPart::Task {
+                    line,
+                    indent,
+                    is_done,
+                    text,
+                } => Part::Task {
+                    line,
+                    indent,
+                    is_done,
+                    items: markdown::parse(&text).collect(),
+                },
+            })
+            .collect();
+        Self { parts }
+    }
+}
+
+/// Renders the parsed content by the Markdown widget with the font of the note style,
+/// links are opened by `Message::OpenUrl`. Tasks are checkboxes toggled by `on_task`
+/// with the line number of the task, they are disabled without it.
+pub fn build_markdown_view<'a, F>(
+    content: &'a MarkdownContent,
+    style: &NoteStyle,
+    on_task: Option<F>,
+) -> Element<'a, Message>
+where
+    F: Fn(usize) -> Message + Copy + 'a,
+{
+    let settings = markdown_settings(style);
+    let spacing = cosmic::theme::spacing();
+    let mut column = widget::column::with_capacity(content.parts.len()).spacing(spacing.space_xxs);
+    for part in &content.parts {
+        column = column.push(match part {
+            Part::Markdown(items) => markdown::view(items, settings).map(Message::OpenUrl),
+            Part::Task {
+                line,
+                indent,
+                is_done,
+                items,
+            } => {
+                let line = *line;
+                let checkbox = widget::checkbox("", *is_done);
+                let checkbox = match on_task {
+                    Some(on_task) => checkbox.on_toggle(move |_| on_task(line)),
+                    None => checkbox,
+                };
+                #[allow(clippy::cast_precision_loss)]
+                let indent = *indent as f32 * INDENT_STEP;
+                widget::row::with_capacity(3)
+                    .spacing(spacing.space_xxs)
+                    .align_y(Alignment::Center)
+                    .push(widget::horizontal_space().width(Length::Fixed(indent)))
+                    .push(checkbox)
+                    .push(markdown::view(items, settings).map(Message::OpenUrl))
+                    .into()
+            }
+        });
+    }
+    column.width(Length::Fill).into()
+}
+
+// the font of the note style and colors of the current theme
+fn markdown_settings(style: &NoteStyle) -> markdown::Settings {
+    let theme = cosmic::theme::active();
+    let cosmic = theme.cosmic();
+    markdown::Settings::with_text_size(
+        style.get_font().size,
+        markdown::Style {
+            font: cosmic_font(style.get_font().style),
+            inline_code_highlight: markdown::Highlight {
+                background: cosmic::iced::Color::from(cosmic.bg_component_color()).into(),
+                border: Border::default().rounded(cosmic.corner_radii.radius_xs),
+            },
+            inline_code_padding: Padding::from([1, 2]),
+            inline_code_color: cosmic.on_bg_component_color().into(),
+            inline_code_font: font::mono(),
+            code_block_font: font::mono(),
+            link_color: cosmic.accent_text_color().into(),
+        },
+    )
+}
+
+/// Renders the note content as plain text with the font of the note style,
+/// only links are detected there to be opened by `Message::OpenUrl`
+pub fn build_plain_view<'a>(content: &str, style: &NoteStyle) -> Element<'a, Message> {
+    let (font, size) = (
+        cosmic_font(style.get_font().style),
+        f32::from(style.get_font().size),
+    );
+    let mut spans: Vec<text::Span<'a, String, Font>> = Vec::new();
+    let mut end = 0;
+    for (range, target) in find_links(content) {
+        if range.start > end {
+            spans.push(span(content[end..range.start].to_string()));
+        }
+        spans.push(
+            span(content[range.clone()].to_string())
+                .underline(true)
+                .link(target),
+        );
+        end = range.end;
+    }
+    if end < content.len() {
+        spans.push(span(content[end..].to_string()));
+    }
+    let spans: Vec<_> = spans
+        .into_iter()
+        .map(|item| item.font(font).size(size))
+        .collect();
+    rich_text(spans)
+        .on_link_click(Message::OpenUrl)
+        .width(Length::Fill)
+        .into()
+}
+
+/// Returns the theme of code colors readable on the background of the note
+pub fn code_theme(bgcolor: Color) -> highlighter::Theme {
+    let luminance = 0.299 * bgcolor.r + 0.587 * bgcolor.g + 0.114 * bgcolor.b;
+    if luminance < 0.5 {
+        highlighter::Theme::Base16Ocean
+    } else {
+        highlighter::Theme::InspiredGitHub
+    }
+}
+
+/// Renders code with the monospace font colored by the highlighter of the text editor,
+/// `syntax` names the language like the file extension, e.g. `rs`
+pub fn build_code_view<'a>(
+    code: &str,
+    syntax: &str,
+    theme: highlighter::Theme,
+    size: f32,
+) -> Element<'a, Message> {
+    let mut highlighter = highlighter::Highlighter::new(&highlighter::Settings {
+        theme,
+        token: syntax.to_string(),
+    });
+    // links are not detected in code
+    let mut spans: Vec<text::Span<'a, String, Font>> = Vec::new();
+    for (index, line) in code.lines().enumerate() {
+        if index > 0 {
+            spans.push(span("\n"));
+        }
+        let mut start = 0;
+        for (range, highlight) in highlighter.highlight_line(line) {
+            let end = range.end.min(line.len());
+            if start < range.start {
+                spans.push(span(line[start..range.start].to_string()));
+            }
+            if range.start < end {
+                spans.push(
+                    span(line[range.start..end].to_string())
+                        .color_maybe(highlight.to_format().color),
+                );
+            }
+            start = start.max(end);
+        }
+        if start < line.len() {
+            spans.push(span(line[start..].to_string()));
+        }
+    }
+    let spans: Vec<_> = spans
+        .into_iter()
+        .map(|item| item.font(font::mono()).size(size))
+        .collect();
+    rich_text(spans).width(Length::Fill).into()
+}
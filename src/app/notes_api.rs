@@ -0,0 +1,733 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::{Command, service::Message};
+use crate::{
+    config::Config,
+    dbus::{
+        BUS_NAME, Capabilities, NoteInfo, OBJECT_PATH, PROTOCOL_VERSION, StyleInfo, color_from_hex,
+    },
+    notes::{
+        ChangeTracker, Color, Font, FontStyle, NoteData, NoteStyle, NotesChange, NotesCollection,
+        NotesCollectionError, NotesFormat, Reminder, normalize_tag,
+    },
+};
+use chrono::{Duration, Utc};
+use cosmic::iced_futures::futures::{SinkExt, StreamExt, channel::mpsc};
+use cosmic::prelude::*;
+use std::{
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::oneshot;
+use uuid::Uuid;
+use zbus::{fdo, object_server::SignalEmitter};
+
+/// Requests of D-Bus clients to the notes collection
+#[derive(Debug, Clone, PartialEq)]
+pub enum Request {
+    ListNotes,
+    GetNote(Uuid),
+    CreateNote(String, String), // (content, style)
+    UpdateNoteContent(Uuid, String),
+    SetNoteTitle(Uuid, Option<String>), // None titles the note by the first line
+    SetNoteStyle(Uuid, String),
+    SetNoteLocked(Uuid, bool),
+    SetNoteVisible(Uuid, bool),
+    SetNoteGeometry(Uuid, usize, usize, usize, usize), // (left, top, width, height)
+    SetNoteReminder(Uuid, Option<Reminder>),
+    SnoozeReminder(Uuid, u32), // (note, minutes)
+    DismissReminder(Uuid),
+    SetNoteTags(Uuid, Vec<String>), // normalized tags
+    ListTags,
+    DeleteNote(Uuid),
+    RestoreNote(Uuid),
+    ListStyles,
+    CreateStyle(String, Font, Color), // (name, font, background)
+    // Commands of the main menu as D-Bus activation gets them
+    Command(Command),
+}
+
+#[derive(Debug)]
+pub enum Response {
+    Notes(Vec<NoteInfo>),
+    Note(NoteInfo),
+    Styles(Vec<StyleInfo>),
+    Tags(Vec<String>),
+    Created(Uuid),
+    Done,
+}
+
+type Reply = Result<Response, String>;
+
+/// D-Bus signal about the change of the notes collection, carries the new values
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangeSignal {
+    NoteCreated(NoteInfo),
+    NoteContentChanged(Uuid, String, String), // (note, title, content)
+    NoteGeometryChanged(Uuid, usize, usize, usize, usize), // (note, left, top, width, height)
+    NoteDeleted(Uuid),
+    NoteRestored(NoteInfo),
+    StyleChanged(StyleInfo),
+    StyleDeleted(Uuid),
+    CollectionSaved,
+}
+
+/// The request and the channel to return the response to the waiting D-Bus client
+#[derive(Debug, Clone)]
+pub struct PendingRequest {
+    request: Request,
+    reply: Arc<Mutex<Option<oneshot::Sender<Reply>>>>,
+}
+
+impl PendingRequest {
+    pub fn request(&self) -> &Request {
+        &self.request
+    }
+
+    // the response is sent only once, subsequent calls are ignored
+    pub fn respond(&self, reply: Reply) {
+        if let Some(tx) = self.reply.lock().ok().and_then(|mut reply| reply.take())
+            && tx.send(reply).is_err()
+        {
+            tracing::warn!("D-Bus client has gone before getting response");
+        }
+    }
+}
+
+/// Serves the notes API on the session bus while the service is running.
+/// Each call is forwarded to the service as `Message::NotesRequest` and waits for the response,
+/// the service gets `Message::NotesApiConnected` to emit change signals.
+pub fn serve(storage: String) -> Task<cosmic::Action<Message>> {
+    Task::stream(cosmic::iced_futures::stream::channel(
+        1,
+        async move |mut tx| {
+            let (sender, mut requests) = mpsc::channel(1);
+            let connected = connect(sender, capabilities(true, storage)).await;
+            match connected.and_then(|connection| {
+                signal_emitter(&connection).map(|emitter| (connection, emitter))
+            }) {
+                Ok((_connection, emitter)) => {
+                    if tx
+                        .send(Message::NotesApiConnected(emitter).into())
+                        .await
+                        .is_err()
+                    {
+                        return;
+                    }
+                    while let Some(pending) = requests.next().await {
+                        if tx
+                            .send(Message::NotesRequest(pending).into())
+                            .await
+                            .is_err()
+                        {
+                            break;
+                        }
+                    }
+                }
+                Err(e) => tracing::error!("failed serving notes API: {e}"),
+            }
+        },
+    ))
+}
+
+/// Serves the notes API on the session bus until the returned connection is dropped,
+/// each call is sent to `sender` and waits for the response.
+pub async fn connect(
+    sender: mpsc::Sender<PendingRequest>,
+    capabilities: Capabilities,
+) -> zbus::Result<zbus::Connection> {
+    let connection = zbus::connection::Builder::session()?
+        .name(BUS_NAME)?
+        .serve_at(
+            OBJECT_PATH,
+            NotesInterface {
+                sender,
+                capabilities,
+            },
+        )?
+        .build()
+        .await?;
+    tracing::info!("serving notes API as {BUS_NAME}");
+    Ok(connection)
+}
+
+/// Returns capabilities of notes-service keeping notes in `storage`,
+/// commands opening windows are not supported without them
+#[must_use]
+pub fn capabilities(with_windows: bool, storage: String) -> Capabilities {
+    let windowed = [
+        Command::RestoreNotes.name(),
+        Command::SearchNotes.name(),
+        Command::OpenSettings.name(),
+        Command::OpenAbout.name(),
+    ];
+    Capabilities {
+        protocol_version: PROTOCOL_VERSION,
+        commands: Command::NAMES
+            .iter()
+            .filter(|name| with_windows || !windowed.contains(name))
+            .map(ToString::to_string)
+            .collect(),
+        storage,
+    }
+}
+
+/// Returns the emitter of the notes API signals on the connection returned by [`connect`]
+pub fn signal_emitter(connection: &zbus::Connection) -> zbus::Result<SignalEmitter<'static>> {
+    SignalEmitter::new(connection, OBJECT_PATH)
+}
+
+/// Takes changes of the notes collection made since the previous call as signals to emit
+pub fn change_signals(
+    tracker: &mut ChangeTracker,
+    notes: &NotesCollection,
+    config: &Config,
+) -> Vec<ChangeSignal> {
+    tracker
+        .take_changes(notes)
+        .into_iter()
+        .filter_map(|change| change_signal(notes, config, change))
+        .collect()
+}
+
+/// Emits signals returned by [`change_signals`]
+pub async fn emit(emitter: &SignalEmitter<'_>, signals: Vec<ChangeSignal>) {
+    for signal in signals {
+        let result = match &signal {
+            ChangeSignal::NoteCreated(note) => NotesInterface::note_created(emitter, note).await,
+            ChangeSignal::NoteContentChanged(note_id, title, content) => {
+                NotesInterface::note_content_changed(emitter, &note_id.to_string(), title, content)
+                    .await
+            }
+            ChangeSignal::NoteGeometryChanged(note_id, left, top, width, height) => {
+                NotesInterface::note_geometry_changed(
+                    emitter,
+                    &note_id.to_string(),
+                    *left as u64,
+                    *top as u64,
+                    *width as u64,
+                    *height as u64,
+                )
+                .await
+            }
+            ChangeSignal::NoteDeleted(note_id) => {
+                NotesInterface::note_deleted(emitter, &note_id.to_string()).await
+            }
+            ChangeSignal::NoteRestored(note) => NotesInterface::note_restored(emitter, note).await,
+            ChangeSignal::StyleChanged(style) => {
+                NotesInterface::style_changed(emitter, style).await
+            }
+            ChangeSignal::StyleDeleted(style_id) => {
+                NotesInterface::style_deleted(emitter, &style_id.to_string()).await
+            }
+            ChangeSignal::CollectionSaved => NotesInterface::collection_saved(emitter).await,
+        };
+        if let Err(e) = result {
+            tracing::warn!("failed emitting {signal:?}: {e}");
+        }
+    }
+}
+
+// the change is skipped if the note or the style is not found anymore
+fn change_signal(
+    notes: &NotesCollection,
+    config: &Config,
+    change: NotesChange,
+) -> Option<ChangeSignal> {
+    // the note might have been deleted after the change
+    let find_note = |note_id: Uuid| {
+        notes.try_get_note(&note_id).ok().or_else(|| {
+            notes
+                .iter_deleted_notes()
+                .find_map(|(id, note)| (*id == note_id).then_some(note))
+        })
+    };
+    match change {
+        NotesChange::NoteCreated(note_id) => find_note(note_id)
+            .map(|note| ChangeSignal::NoteCreated(note_info(notes, config, note_id, note))),
+        NotesChange::NoteContentChanged(note_id) => find_note(note_id).map(|note| {
+            ChangeSignal::NoteContentChanged(
+                note_id,
+                note.get_title(config.title_chars).to_string(),
+                note.get_content().to_string(),
+            )
+        }),
+        NotesChange::NoteGeometryChanged(note_id) => find_note(note_id).map(|note| {
+            ChangeSignal::NoteGeometryChanged(
+                note_id,
+                note.left(),
+                note.top(),
+                note.width(),
+                note.height(),
+            )
+        }),
+        NotesChange::NoteDeleted(note_id) => Some(ChangeSignal::NoteDeleted(note_id)),
+        NotesChange::NoteRestored(note_id) => find_note(note_id)
+            .map(|note| ChangeSignal::NoteRestored(note_info(notes, config, note_id, note))),
+        NotesChange::StyleChanged(style_id) => notes.try_get_style(&style_id).ok().map(|style| {
+            ChangeSignal::StyleChanged(StyleInfo::new(
+                style_id,
+                style,
+                style_id == notes.get_default_style_id(),
+            ))
+        }),
+        NotesChange::StyleDeleted(style_id) => Some(ChangeSignal::StyleDeleted(style_id)),
+        NotesChange::CollectionSaved => Some(ChangeSignal::CollectionSaved),
+    }
+}
+
+/// Applies the request to the notes collection, windows are up to the caller
+pub fn apply(
+    notes: &mut NotesCollection,
+    config: &Config,
+    request: &Request,
+) -> Result<Response, NotesCollectionError> {
+    match request {
+        Request::ListNotes => Ok(Response::Notes(
+            notes
+                .iter_notes()
+                .map(|(note_id, note)| note_info(notes, config, *note_id, note))
+                .collect(),
+        )),
+
+        Request::GetNote(note_id) => {
+            let note = notes.try_get_note(note_id)?;
+            Ok(Response::Note(note_info(notes, config, *note_id, note)))
+        }
+
+        Request::CreateNote(content, style) => {
+            let style_id = notes.try_find_style(style)?;
+            let note_id = notes.new_note();
+            let note = notes.try_get_note_mut(&note_id)?;
+            note.set_style(style_id);
+            note.set_content(content.clone());
+            Ok(Response::Created(note_id))
+        }
+
+        Request::UpdateNoteContent(note_id, content) => {
+            notes
+                .try_get_note_mut(note_id)?
+                .set_content(content.clone());
+            Ok(Response::Done)
+        }
+
+        Request::SetNoteStyle(note_id, style) => {
+            let style_id = notes.try_find_style(style)?;
+            notes.try_get_note_mut(note_id)?.set_style(style_id);
+            Ok(Response::Done)
+        }
+
+        Request::SetNoteLocked(note_id, is_locked) => {
+            notes.try_get_note_mut(note_id)?.set_locking(*is_locked);
+            Ok(Response::Done)
+        }
+
+        Request::SetNoteVisible(note_id, is_visible) => {
+            notes.try_get_note_mut(note_id)?.set_visibility(*is_visible);
+            Ok(Response::Done)
+        }
+
+        Request::SetNoteGeometry(note_id, left, top, width, height) => {
+            let note = notes.try_get_note_mut(note_id)?;
+            note.set_position(*left, *top);
+            note.set_size(*width, *height);
+            Ok(Response::Done)
+        }
+
+        Request::SetNoteReminder(note_id, reminder) => {
+            notes
+                .try_get_note_mut(note_id)?
+                .set_reminder(reminder.clone());
+            Ok(Response::Done)
+        }
+
+        Request::SnoozeReminder(note_id, minutes) => {
+            let until = Utc::now() + Duration::minutes(i64::from(*minutes));
+            notes.try_get_note_mut(note_id)?.snooze_reminder(until);
+            Ok(Response::Done)
+        }
+
+        Request::DismissReminder(note_id) => {
+            notes
+                .try_get_note_mut(note_id)?
+                .dismiss_reminder(Utc::now());
+            Ok(Response::Done)
+        }
+
+        Request::SetNoteTitle(note_id, title) => {
+            notes.try_get_note_mut(note_id)?.set_title(title.clone());
+            Ok(Response::Done)
+        }
+
+        Request::SetNoteTags(note_id, tags) => {
+            notes.try_get_note_mut(note_id)?.set_tags(tags.clone());
+            Ok(Response::Done)
+        }
+
+        Request::ListTags => Ok(Response::Tags(notes.get_tags(config.hashtags_as_tags))),
+
+        Request::DeleteNote(note_id) => {
+            notes.try_get_note(note_id)?;
+            notes.delete_note(*note_id);
+            Ok(Response::Done)
+        }
+
+        Request::RestoreNote(note_id) => {
+            notes.try_restore_deleted_note(*note_id)?;
+            Ok(Response::Done)
+        }
+
+        Request::ListStyles => {
+            let default_style_id = notes.get_default_style_id();
+            Ok(Response::Styles(
+                notes
+                    .iter_styles()
+                    .map(|(style_id, style)| {
+                        StyleInfo::new(*style_id, style, *style_id == default_style_id)
+                    })
+                    .collect(),
+            ))
+        }
+
+        Request::CreateStyle(name, font, background) => {
+            let style_id = notes.new_style(name.clone());
+            let style = notes.try_get_style_mut(&style_id)?;
+            style.set_font(font.clone());
+            style.set_background_color(*background);
+            Ok(Response::Created(style_id))
+        }
+
+        // commands are executed by the service itself, there is nothing to change here
+        Request::Command(..) => Ok(Response::Done),
+    }
+}
+
+fn note_info(notes: &NotesCollection, config: &Config, note_id: Uuid, note: &NoteData) -> NoteInfo {
+    NoteInfo::new(
+        note_id,
+        note,
+        note.get_title(config.title_chars),
+        notes
+            .try_get_style(&note.style())
+            .map_or("", NoteStyle::get_name),
+    )
+}
+
+struct NotesInterface {
+    sender: mpsc::Sender<PendingRequest>,
+    capabilities: Capabilities,
+}
+
+#[zbus::interface(name = "com.github.aae.sticky_notes.Notes")]
+impl NotesInterface {
+    async fn get_capabilities(&self) -> Capabilities {
+        self.capabilities.clone()
+    }
+
+    async fn list_notes(&self) -> fdo::Result<Vec<NoteInfo>> {
+        match self.request(Request::ListNotes).await? {
+            Response::Notes(notes) => Ok(notes),
+            other => Err(unexpected(&other)),
+        }
+    }
+
+    async fn get_note(&self, id: &str) -> fdo::Result<NoteInfo> {
+        match self.request(Request::GetNote(parse_id(id)?)).await? {
+            Response::Note(note) => Ok(note),
+            other => Err(unexpected(&other)),
+        }
+    }
+
+    async fn create_note(&self, content: &str, style: &str) -> fdo::Result<String> {
+        match self
+            .request(Request::CreateNote(content.to_string(), style.to_string()))
+            .await?
+        {
+            Response::Created(note_id) => Ok(note_id.to_string()),
+            other => Err(unexpected(&other)),
+        }
+    }
+
+    async fn update_note_content(&self, id: &str, content: &str) -> fdo::Result<()> {
+        self.request(Request::UpdateNoteContent(
+            parse_id(id)?,
+            content.to_string(),
+        ))
+        .await
+        .map(|_| ())
+    }
+
+    async fn set_note_style(&self, id: &str, style: &str) -> fdo::Result<()> {
+        self.request(Request::SetNoteStyle(parse_id(id)?, style.to_string()))
+            .await
+            .map(|_| ())
+    }
+
+    async fn set_note_locked(&self, id: &str, is_locked: bool) -> fdo::Result<()> {
+        self.request(Request::SetNoteLocked(parse_id(id)?, is_locked))
+            .await
+            .map(|_| ())
+    }
+
+    async fn set_note_visible(&self, id: &str, is_visible: bool) -> fdo::Result<()> {
+        self.request(Request::SetNoteVisible(parse_id(id)?, is_visible))
+            .await
+            .map(|_| ())
+    }
+
+    async fn set_note_geometry(
+        &self,
+        id: &str,
+        left: u64,
+        top: u64,
+        width: u64,
+        height: u64,
+    ) -> fdo::Result<()> {
+        self.request(Request::SetNoteGeometry(
+            parse_id(id)?,
+            parse_size(left)?,
+            parse_size(top)?,
+            parse_size(width)?,
+            parse_size(height)?,
+        ))
+        .await
+        .map(|_| ())
+    }
+
+    async fn set_note_reminder(&self, id: &str, time: &str, repeat: &str) -> fdo::Result<()> {
+        // the empty time removes the reminder
+        let reminder = if time.is_empty() {
+            None
+        } else {
+            Some(Reminder::parse(time, repeat, Utc::now()).map_err(invalid_args)?)
+        };
+        self.request(Request::SetNoteReminder(parse_id(id)?, reminder))
+            .await
+            .map(|_| ())
+    }
+
+    async fn snooze_note_reminder(&self, id: &str, minutes: u32) -> fdo::Result<()> {
+        self.request(Request::SnoozeReminder(parse_id(id)?, minutes))
+            .await
+            .map(|_| ())
+    }
+
+    async fn dismiss_note_reminder(&self, id: &str) -> fdo::Result<()> {
+        self.request(Request::DismissReminder(parse_id(id)?))
+            .await
+            .map(|_| ())
+    }
+
+    async fn set_note_title(&self, id: &str, title: &str) -> fdo::Result<()> {
+        let title = (!title.trim().is_empty()).then(|| title.to_string());
+        self.request(Request::SetNoteTitle(parse_id(id)?, title))
+            .await
+            .map(|_| ())
+    }
+
+    async fn set_note_tags(&self, id: &str, tags: Vec<String>) -> fdo::Result<()> {
+        let tags = tags
+            .iter()
+            .map(|tag| {
+                normalize_tag(tag).ok_or_else(|| fdo::Error::InvalidArgs(format!("tag {tag:?}")))
+            })
+            .collect::<fdo::Result<Vec<_>>>()?;
+        self.request(Request::SetNoteTags(parse_id(id)?, tags))
+            .await
+            .map(|_| ())
+    }
+
+    async fn list_tags(&self) -> fdo::Result<Vec<String>> {
+        match self.request(Request::ListTags).await? {
+            Response::Tags(tags) => Ok(tags),
+            other => Err(unexpected(&other)),
+        }
+    }
+
+    async fn delete_note(&self, id: &str) -> fdo::Result<()> {
+        self.request(Request::DeleteNote(parse_id(id)?))
+            .await
+            .map(|_| ())
+    }
+
+    async fn restore_note(&self, id: &str) -> fdo::Result<()> {
+        self.request(Request::RestoreNote(parse_id(id)?))
+            .await
+            .map(|_| ())
+    }
+
+    async fn list_styles(&self) -> fdo::Result<Vec<StyleInfo>> {
+        match self.request(Request::ListStyles).await? {
+            Response::Styles(styles) => Ok(styles),
+            other => Err(unexpected(&other)),
+        }
+    }
+
+    async fn create_style(
+        &self,
+        name: &str,
+        font_style: &str,
+        font_size: u16,
+        background: &str,
+    ) -> fdo::Result<String> {
+        let font = Font {
+            style: FontStyle::from_str(font_style).map_err(invalid_args)?,
+            size: font_size,
+        };
+        let background = color_from_hex(background).map_err(invalid_args)?;
+        match self
+            .request(Request::CreateStyle(name.to_string(), font, background))
+            .await?
+        {
+            Response::Created(style_id) => Ok(style_id.to_string()),
+            other => Err(unexpected(&other)),
+        }
+    }
+
+    async fn connect_applet(&self) -> fdo::Result<()> {
+        self.command(Command::Connect).await
+    }
+
+    async fn quit(&self) -> fdo::Result<()> {
+        self.command(Command::Quit).await
+    }
+
+    async fn load_notes(&self) -> fdo::Result<()> {
+        self.command(Command::LoadNotes).await
+    }
+
+    async fn save_notes(&self) -> fdo::Result<()> {
+        self.command(Command::SaveNotes).await
+    }
+
+    async fn import_notes(&self, path: &str, format: &str) -> fdo::Result<()> {
+        self.command(Command::ImportNotes(file_args(path, format)?))
+            .await
+    }
+
+    async fn export_notes(&self, path: &str, format: &str) -> fdo::Result<()> {
+        self.command(Command::ExportNotes(file_args(path, format)?))
+            .await
+    }
+
+    async fn set_all_notes_visible(&self, is_visible: bool) -> fdo::Result<()> {
+        let command = if is_visible {
+            Command::ShowAllNotes
+        } else {
+            Command::HideAllNotes
+        };
+        self.command(command).await
+    }
+
+    async fn lock_all_notes(&self) -> fdo::Result<()> {
+        self.command(Command::LockAll).await
+    }
+
+    async fn show_tagged_notes(&self, tag: &str) -> fdo::Result<()> {
+        let tag =
+            normalize_tag(tag).ok_or_else(|| fdo::Error::InvalidArgs(format!("tag {tag:?}")))?;
+        self.command(Command::ShowTagged(tag)).await
+    }
+
+    async fn open_restore_window(&self) -> fdo::Result<()> {
+        self.command(Command::RestoreNotes).await
+    }
+
+    async fn open_search_window(&self) -> fdo::Result<()> {
+        self.command(Command::SearchNotes).await
+    }
+
+    async fn open_settings_window(&self) -> fdo::Result<()> {
+        self.command(Command::OpenSettings).await
+    }
+
+    async fn open_about_window(&self) -> fdo::Result<()> {
+        self.command(Command::OpenAbout).await
+    }
+
+    #[zbus(signal)]
+    async fn note_created(emitter: &SignalEmitter<'_>, note: &NoteInfo) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn note_content_changed(
+        emitter: &SignalEmitter<'_>,
+        id: &str,
+        title: &str,
+        content: &str,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn note_geometry_changed(
+        emitter: &SignalEmitter<'_>,
+        id: &str,
+        left: u64,
+        top: u64,
+        width: u64,
+        height: u64,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn note_deleted(emitter: &SignalEmitter<'_>, id: &str) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn note_restored(emitter: &SignalEmitter<'_>, note: &NoteInfo) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn style_changed(emitter: &SignalEmitter<'_>, style: &StyleInfo) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn style_deleted(emitter: &SignalEmitter<'_>, id: &str) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn collection_saved(emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+}
+
+impl NotesInterface {
+    async fn request(&self, request: Request) -> fdo::Result<Response> {
+        let (tx, rx) = oneshot::channel();
+        let pending = PendingRequest {
+            request,
+            reply: Arc::new(Mutex::new(Some(tx))),
+        };
+        self.sender
+            .clone()
+            .send(pending)
+            .await
+            .map_err(|e| fdo::Error::Failed(format!("notes-service is not responding: {e}")))?;
+        rx.await
+            .map_err(|e| fdo::Error::Failed(format!("request was dropped: {e}")))?
+            .map_err(fdo::Error::Failed)
+    }
+
+    async fn command(&self, command: Command) -> fdo::Result<()> {
+        self.request(Request::Command(command)).await.map(|_| ())
+    }
+}
+
+fn parse_id(id: &str) -> fdo::Result<Uuid> {
+    Uuid::parse_str(id).map_err(|e| fdo::Error::InvalidArgs(format!("{id}: {e}")))
+}
+
+fn parse_size(value: u64) -> fdo::Result<usize> {
+    usize::try_from(value).map_err(invalid_args)
+}
+
+// the empty path and format stand for configured ones
+fn file_args(path: &str, format: &str) -> fdo::Result<Option<(String, NotesFormat)>> {
+    if path.is_empty() {
+        Ok(None)
+    } else if format.is_empty() {
+        Ok(Some((path.to_string(), NotesFormat::default())))
+    } else {
+        let format = NotesFormat::from_str(format).map_err(invalid_args)?;
+        Ok(Some((path.to_string(), format)))
+    }
+}
+
+fn invalid_args<E: std::error::Error>(e: E) -> fdo::Error {
+    fdo::Error::InvalidArgs(e.to_string())
+}
+
+fn unexpected(response: &Response) -> fdo::Error {
+    fdo::Error::Failed(format!("unexpected response {response:?}"))
+}
@@ -0,0 +1,102 @@
+use super::{
+    Command,
+    applet::Message,
+    utils::{to_f32, with_background},
+};
+use crate::{dbus::NoteInfo, fl, icons::IconSet};
+use cosmic::prelude::*;
+use cosmic::{
+    applet as cosmic_applet,
+    iced::{Alignment, Color, Length},
+    widget,
+};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const SWATCH_SIZE: usize = 8;
+const LIST_MAX_HEIGHT: f32 = 240.0;
+
+/// Builds the list of notes for the applet popup: a click on the note shows and focuses
+/// its sticky window, the toggler hides or shows the note
+pub fn build_notes_list<'a>(
+    notes: &'a [NoteInfo],
+    backgrounds: &HashMap<String, Color>, // style ID -> background
+    icons: &IconSet,
+    icon_size: u16,
+) -> Element<'a, Message> {
+    let items = notes.iter().filter_map(|note| {
+        let note_id = Uuid::parse_str(&note.id).ok()?;
+        Some(build_note_list_item(
+            note_id,
+            note,
+            backgrounds.get(&note.style_id).copied(),
+            icons,
+            icon_size,
+        ))
+    });
+    widget::container(widget::scrollable(widget::column::with_children(items)))
+        .max_height(LIST_MAX_HEIGHT)
+        .into()
+}
+
+/// Builds the input creating a new note of the chosen style on Enter
+pub fn build_quick_capture<'a>(
+    input_id: widget::Id,
+    text: &'a str,
+    style_names: &'a [String],
+    style_index: Option<usize>,
+) -> Element<'a, Message> {
+    widget::row::with_capacity(2)
+        .spacing(cosmic::theme::spacing().space_xs)
+        .align_y(Alignment::Center)
+        .push(
+            widget::text_input(fl!("quick-capture-placeholder"), text)
+                .id(input_id)
+                .width(Length::Fill)
+                .on_input(Message::CaptureInput)
+                .on_submit(|_| Message::CaptureSubmit),
+        )
+        .push(widget::dropdown(
+            style_names,
+            style_index,
+            Message::CaptureStyle,
+        ))
+        .into()
+}
+
+fn build_note_list_item<'a>(
+    note_id: Uuid,
+    note: &'a NoteInfo,
+    bgcolor: Option<Color>,
+    icons: &IconSet,
+    icon_size: u16,
+) -> Element<'a, Message> {
+    let spacing = cosmic::theme::spacing();
+    let mut title = widget::row::with_capacity(3)
+        .spacing(spacing.space_xs)
+        .align_y(Alignment::Center);
+    if let Some(note_bg) = bgcolor {
+        let swatch_size = Length::Fixed(to_f32(SWATCH_SIZE));
+        title = title.push(with_background(
+            widget::Space::new(swatch_size, swatch_size).into(),
+            note_bg,
+        ));
+    }
+    title = title.push(widget::text::body(note.title.as_str()).width(Length::Fill));
+    if note.is_locked {
+        title = title.push(widget::icon(icons.unlock()).size(icon_size));
+    }
+    widget::row::with_capacity(2)
+        .spacing(spacing.space_xs)
+        .align_y(Alignment::Center)
+        .push(
+            cosmic_applet::menu_button(title)
+                .width(Length::Fill)
+                .on_press(Message::Signal(Command::ShowNote(note_id))),
+        )
+        .push(
+            widget::toggler(note.is_visible)
+                .on_toggle(move |is_visible| Message::NoteVisible(note_id, is_visible)),
+        )
+        .into()
+}
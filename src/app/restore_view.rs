@@ -1,9 +1,8 @@
-use super::{service::Message, utils::with_background};
-use crate::{
-    fl,
-    icons::IconSet,
-    notes::{NoteData, NoteStyle, NotesCollection},
+use super::{
+    service::Message,
+    utils::{background_color, with_background},
 };
+use crate::{config::Config, fl, icons::IconSet, notes::NotesCollection};
 use cosmic::prelude::*;
 use cosmic::{
     iced::{Color, Length, widget::keyed_column},
@@ -13,9 +12,10 @@ use uuid::Uuid;
 
 pub fn build_restore_view<'a>(
     notes: &'a NotesCollection,
+    config: &Config,
     icons: &IconSet,
-    icon_size: u16,
 ) -> Element<'a, Message> {
+    let icon_size = config.toolbar_icon_size;
     widget::column::with_capacity(2)
         .spacing(cosmic::theme::spacing().space_m)
         .push(widget::text(fl!("recently-deleted-description")))
@@ -26,10 +26,10 @@ pub fn build_restore_view<'a>(
                         *note_id,
                         build_note_list_item(
                             *note_id,
-                            note,
+                            note.get_title(config.title_chars),
                             notes
                                 .try_get_note_style(*note_id)
-                                .map(NoteStyle::get_background_color)
+                                .map(background_color)
                                 .ok(),
                             icons,
                             icon_size,
@@ -47,7 +47,7 @@ pub fn build_restore_view<'a>(
 
 fn build_note_list_item<'a>(
     note_id: Uuid,
-    note: &'a NoteData,
+    title: &'a str,
     bgcolor: Option<Color>,
     icons: &IconSet,
     icon_size: u16,
@@ -55,7 +55,7 @@ fn build_note_list_item<'a>(
     let child = widget::row::with_capacity(2)
         .spacing(cosmic::theme::spacing().space_s)
         .width(Length::Fill)
-        .push(widget::text(note.get_title()).width(Length::Fill))
+        .push(widget::text(title).width(Length::Fill))
         .push(
             icons
                 .undo()
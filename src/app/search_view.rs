@@ -0,0 +1,146 @@
+use super::{
+    service::Message,
+    utils::{background_color, with_background},
+};
+use crate::{
+    fl,
+    icons::IconSet,
+    notes::{NotesCollection, SearchMatch},
+};
+use cosmic::prelude::*;
+use cosmic::{
+    font,
+    iced::{Alignment, Length, widget::keyed_column},
+    widget,
+};
+
+/// The window searching notes as the query is typed
+pub struct SearchView {
+    input_id: widget::Id,
+    query: String,
+    // notes are searched when the query or notes change, not on every view
+    found: Vec<SearchMatch>,
+}
+
+impl SearchView {
+    pub fn new() -> Self {
+        Self {
+            input_id: widget::Id::unique(),
+            query: String::new(),
+            found: Vec::new(),
+        }
+    }
+
+    pub fn input_id(&self) -> widget::Id {
+        self.input_id.clone()
+    }
+
+    pub fn update_query(&mut self, query: String, notes: &NotesCollection, title_chars: usize) {
+        self.query = query;
+        self.update_results(notes, title_chars);
+    }
+
+    /// Searches the query again after notes or the length of titles are changed
+    pub fn update_results(&mut self, notes: &NotesCollection, title_chars: usize) {
+        self.found = notes.search(&self.query, title_chars);
+    }
+
+    pub fn build_view<'a>(
+        &'a self,
+        notes: &'a NotesCollection,
+        icons: &IconSet,
+        icon_size: u16,
+    ) -> Element<'a, Message> {
+        let results: Element<'a, Message> = if self.found.is_empty() && !self.query.is_empty() {
+            widget::text(fl!("search-nothing-found")).into()
+        } else {
+            widget::scrollable(
+                keyed_column(self.found.iter().map(|found| {
+                    let note_id = found.note_id;
+                    let bgcolor = notes.try_get_style(&found.style_id).map(background_color);
+                    let item = build_search_item(found, icons, icon_size);
+                    (
+                        note_id,
+                        match bgcolor {
+                            Ok(note_bg) => with_background(item, note_bg),
+                            Err(_) => item,
+                        },
+                    )
+                }))
+                .spacing(cosmic::theme::spacing().space_xxs),
+            )
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+        };
+        widget::column::with_capacity(2)
+            .spacing(cosmic::theme::spacing().space_m)
+            .push(
+                widget::search_input(fl!("search-placeholder"), &self.query)
+                    .id(self.input_id.clone())
+                    .on_input(Message::SearchInput)
+                    .on_clear(Message::SearchInput(String::new())),
+            )
+            .push(results)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+}
+
+// the title, the snippet with the highlighted match and actions on the note
+fn build_search_item<'a>(
+    found: &'a SearchMatch,
+    icons: &IconSet,
+    icon_size: u16,
+) -> Element<'a, Message> {
+    let spacing = cosmic::theme::spacing();
+    let snippet = widget::row::with_capacity(3)
+        .push(widget::text::body(&found.before))
+        .push(widget::text::body(&found.matched).font(font::bold()))
+        .push(widget::text::body(&found.after));
+    let mut actions = widget::row::with_capacity(2)
+        .spacing(spacing.space_xxs)
+        .align_y(Alignment::Center);
+    if found.is_deleted {
+        actions = actions.push(
+            icons
+                .undo()
+                .apply(widget::button::icon)
+                .icon_size(icon_size)
+                .on_press(Message::NoteRestore(found.note_id))
+                .width(Length::Shrink),
+        );
+    } else {
+        actions = actions
+            .push(
+                widget::button::text(fl!("search-show"))
+                    .on_press(Message::SearchShow(found.note_id)),
+            )
+            .push(
+                icons
+                    .edit()
+                    .apply(widget::button::icon)
+                    .icon_size(icon_size)
+                    .on_press(Message::SearchEdit(found.note_id))
+                    .width(Length::Shrink),
+            );
+    }
+    let mut title = widget::column::with_capacity(2)
+        .width(Length::Fill)
+        .push(widget::text::heading(&found.title));
+    if found.is_deleted {
+        title = title.push(widget::text::caption(fl!("search-deleted")));
+    }
+    widget::column::with_capacity(2)
+        .spacing(spacing.space_xxs)
+        .push(
+            widget::row::with_capacity(2)
+                .spacing(spacing.space_s)
+                .align_y(Alignment::Center)
+                .push(title)
+                .push(actions),
+        )
+        .push(snippet)
+        .into()
+}
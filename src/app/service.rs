@@ -2,43 +2,65 @@
 
 use crate::{
     app::{
-        Command,
+        Command, NotesStorage, ServiceFlags,
         about_window::AboutWindow,
+        clipboard::ClipboardImage,
+        edit_context::EditContext,
         edit_style::EditStyleDialog,
+        history_view::HistoryView,
+        notes_api::{self, ChangeSignal, PendingRequest, Request, Response},
         popup_variant,
         restore_view::build_restore_view,
+        search_view::SearchView,
+        send_notifications,
+        service_core::{Followup, ServiceCore},
         settings_view::build_settings_view,
         sticky_window::StickyWindow,
         utils::{to_f32, to_usize},
     },
-    config::Config,
+    config::{Config, DEF_DATE_FORMAT},
     fl, icons,
-    notes::{Font, FontStyle, NoteData, NotesCollection},
+    notes::{
+        Attachment, Font, FontStyle, Language, MAX_HISTORY_DAYS, MAX_HISTORY_REVISIONS,
+        MAX_TITLE_CHARS, MIN_TITLE_CHARS, NoteData, NotesCollection, NotesCollectionError,
+        NotesFormat, clear_done_tasks, format_date_stamp, move_done_tasks_down, parse_file_list,
+        parse_tags, toggle_task, try_attach_data, try_attach_file,
+    },
+    notifications::{self, Category},
 };
+use chrono::{Local, Utc};
+use cosmic::iced_futures::futures::{SinkExt, StreamExt};
 use cosmic::prelude::*;
 use cosmic::{
-    app::CosmicFlags,
-    cosmic_config::{self, ConfigSet, CosmicConfigEntry},
+    cosmic_config::{self, CosmicConfigEntry},
     dbus_activation,
     iced::{
         self, Color, Event, Point, Size, Subscription,
         core::mouse::Button as MouseButton,
         event::Status as EventStatus,
+        keyboard::{Event as KeyboardEvent, Key},
         mouse::Event as MouseEvent,
         window::{self, Event as WindowEvent, Id, Position},
     },
     widget,
 };
 use palette::bool_mask::BoolMask;
-use std::{collections::HashMap, str::FromStr, time::Duration};
+use std::{collections::HashMap, path::PathBuf, time::Duration};
 use uuid::Uuid;
-
-pub struct ServiceFlags;
-
-impl CosmicFlags for ServiceFlags {
-    type SubCommand = usize;
-    type Args = Vec<String>;
-}
+use zbus::object_server::SignalEmitter;
+
+// Ctrl+Shift+F opens the search window from any window of the service
+const SEARCH_SHORTCUT_KEY: &str = "f";
+// Ctrl+V in view mode attaches files copied to the clipboard
+const PASTE_SHORTCUT_KEY: &str = "v";
+// Ctrl+Z undoes the edit and Ctrl+Shift+Z redoes it in edit mode
+const UNDO_SHORTCUT_KEY: &str = "z";
+// Ctrl+F opens the find bar and Ctrl+H opens it with the replacement in edit mode
+const FIND_SHORTCUT_KEY: &str = "f";
+const REPLACE_SHORTCUT_KEY: &str = "h";
+// Ctrl+T toggles the task on the line and Ctrl+Shift+D inserts the date in edit mode
+const TASK_SHORTCUT_KEY: &str = "t";
+const DATE_SHORTCUT_KEY: &str = "d";
 
 /// Messages emitted by the application and its widgets.
 #[derive(Debug, Clone)]
@@ -53,23 +75,45 @@ pub enum Message {
     // Windows creating
     StickyWindowCreated(Id, Uuid), // (window_id, note_id)
     RestoreWindowCreated(Id),
+    SearchWindowCreated(Id),
+    HistoryWindowCreated(Id, Uuid), // (window_id, note_id)
     SettingsWindowCreated(Id),
     EditStyleWindowCreated(Id, Uuid, bool), // (window_id, style_id, is_new)
     AboutWindowCreated(Id),
     // Settings actions
-    SetDefaultStyle(usize), // set default style by index
-    // Notes collection load results
-    LoadNotesCompleted(NotesCollection),
-    LoadNotesFailed(String), // error message
-    // Export notes results
-    ExportNotesCompleted,
-    ExportNotesFailed(String), // error message
+    SetDefaultStyle(usize),  // set default style by index
+    HashtagsAsTags(bool),    // #hashtags of content are tags of notes
+    TitleChars(usize),       // length of titles of notes titled by the first line
+    HistoryRevisions(usize), // number of previous versions of content kept for every note
+    HistoryDays(usize),      // age in days of previous versions of content kept
+    // Notes collection import results, the error message on failure
+    NotesImported(Result<NotesCollection, String>),
+    // Export notes results, the error message on failure
+    NotesExported(Result<(), String>),
     // Redirect editor actions to the edit context
     Edit(Id, widget::text_editor::Action),
+    EditUndo(Id),
+    EditRedo(Id),
+    EditIndent(Id, bool), // indent lines under the cursor, outdent them if false
+    EditToggleTask(Id),   // mark the task on the line under the cursor as done or open
+    EditDateStamp(Id),    // insert the current date and time at the cursor
+    // Find/replace bar of sticky windows in edit mode
+    FindOpen(Id, bool),       // open the find bar, with the replacement if true
+    FindClose(Id),            // close the find bar
+    FindInput(Id, String),    // update the text to find
+    FindMatchCase(Id, bool),  // match case of the text to find or not
+    FindNext(Id, bool),       // select the next match, the previous one if false
+    ReplaceInput(Id, String), // update the replacement
+    ReplaceNext(Id),          // replace the selected match and select the next one
+    ReplaceAll(Id),           // replace all matches
     // "system" events handling
     AppWindowEvent((Id, WindowEvent)),
     AppMouseEvent((Id, MouseEvent)),
     DbusActivation(dbus_activation::Message),
+    // Request of D-Bus notes API client
+    NotesRequest(PendingRequest),
+    // Notes API is served, changes are emitted as signals since now
+    NotesApiConnected(SignalEmitter<'static>),
     // Ignorable dummy message (example: message is caught in dbus_activation::subscription() while it isn't a DbusActivation)
     Ignore,
     // response on window::get_position() request
@@ -82,6 +126,31 @@ pub enum Message {
     NoteNew,                      // create new note with default style and begin edit
     NoteDelete(Id),               // delete note
     NoteRestore(Uuid),            // restore note
+    NoteSnooze(Id),               // snooze ringing reminder of note
+    NoteDismiss(Id),              // dismiss ringing reminder of note
+    NoteTitleInput(Id, String),   // update typed title of note being edited
+    NoteTags(Id),                 // open / close the input of note tags
+    NoteTagsInput(Id, String),    // update typed tags of note
+    NoteTagsSubmit(Id),           // set typed tags of note
+    NoteTaskToggle(Id, usize),    // mark the task on the line of note as done or open
+    NoteTasksDown(Id),            // move done tasks of note below open ones
+    NoteTasksClear(Id),           // remove done tasks of note
+    NoteHistory(Id),              // show previous versions of note content
+    NoteCopyLink(Id, usize),      // copy the link of note by index to clipboard
+    NoteCodeLanguage(Id, usize),  // make note code of the language by index, 0 is not code
+    // Attachments of sticky windows
+    NotePaste(Id),                      // attach files copied to the clipboard to note
+    NotePasteFiles(Id, Option<String>), // attach files listed in the clipboard text to note
+    NotePasteImage(Id, Option<ClipboardImage>), // attach image copied to the clipboard to note
+    NoteAttached(Uuid, Vec<Attachment>), // add attachments copied in background to note
+    NoteAttachmentRemove(Id, String),   // remove attachment of note by the stored file name
+    // History window
+    HistorySelect(usize), // compare the revision by index with the current content
+    HistoryRollback(usize), // replace the current content with the revision by index
+    // Search window
+    SearchInput(String), // update the searched text
+    SearchShow(Uuid),    // show and focus the found note
+    SearchEdit(Uuid),    // show the found note in edit mode
     // Styles view buttons
     StyleNew,                                             // add new style
     StyleEdit(Uuid),                                      // edit style by style_id
@@ -92,10 +161,15 @@ pub enum Message {
     ColorUpdate(widget::color_picker::ColorPickerUpdate), // update currently edited style color
     FontStyleUpdate(FontStyle),                           // update currently edited style font
     FontSizeUpdate(u16),                                  // update currently edited style font size
+    MarkdownUpdate(bool), // update currently edited style markdown rendering
     // Open URL
     OpenUrl(String),
     // Test autosave timeout
     AutosaveTimeout,
+    // Test reminders of notes are due
+    ReminderTimeout,
+    // The action with the key is invoked on the desktop notification
+    NotificationAction(String),
 }
 
 /// The application model stores app-specific state used to describe its interface and
@@ -103,13 +177,15 @@ pub enum Message {
 pub struct ServiceModel {
     // Application state which is managed by the COSMIC runtime.
     core: cosmic::Core,
-    /// Configuration data that persists between application runs.
-    config: Config,
-    // Collection of notes & styles
-    notes: NotesCollection,
+    // Collection of notes & styles with the config and the storage, shared with headless service
+    service: ServiceCore,
+    // Sticky windows are not opened until notes are shown explicitly
+    no_windows: bool,
     settings_window_id: Option<Id>,
     edit_style: Option<(Id, EditStyleDialog)>,
     restore_window_id: Option<Id>,
+    search_window: Option<(Id, SearchView)>,
+    history_window: Option<(Id, HistoryView)>,
     about_window: Option<(Id, AboutWindow)>,
     // sticky windows by ID
     sticky_windows: HashMap<Id, StickyWindow>,
@@ -119,6 +195,8 @@ pub struct ServiceModel {
     popup_menu_id: Option<Id>,
     // Applet is connected
     applet_connected: bool,
+    // Emits notes API signals after notes API is served
+    signal_emitter: Option<SignalEmitter<'static>>,
     icons: icons::IconSet,
 }
 
@@ -145,56 +223,59 @@ impl cosmic::Application for ServiceModel {
     }
 
     /// Initializes the application with any given flags and startup commands.
-    fn init(
-        core: cosmic::Core,
-        _flags: Self::Flags,
-    ) -> (Self, Task<cosmic::Action<Self::Message>>) {
+    fn init(core: cosmic::Core, flags: Self::Flags) -> (Self, Task<cosmic::Action<Self::Message>>) {
         // Load config
-        let config = cosmic_config::Config::new(Self::APP_ID, Config::VERSION)
-            .map(|context| match Config::get_entry(&context) {
-                Ok(config) => config,
-                Err((errors, config)) => {
-                    for why in errors {
-                        tracing::error!("error loading app config: {why}");
-                    }
-                    config
-                }
-            })
-            .unwrap_or_default();
-
-        // Load notes from config if config/notes is not empty
-        let notes = Self::load_notes_or_default(&config.notes);
+        let storage = NotesStorage::new(&flags);
+        let config = storage.load_config();
 
         // Construct the app model with the runtime's core.
         let mut app = ServiceModel {
             core,
-            // Optional configuration file for an application.
-            config,
-            notes,
+            // Load notes from data file or from config if config/notes is not empty
+            service: ServiceCore::new(storage, config),
+            no_windows: flags.no_windows,
             settings_window_id: None,
             edit_style: None,
             restore_window_id: None,
+            search_window: None,
+            history_window: None,
             about_window: None,
             sticky_windows: HashMap::new(),
             cursor_window: None,
             popup_menu_id: None,
             applet_connected: false,
+            signal_emitter: None,
             icons: icons::IconSet::new(),
         };
 
         // Create a startup commands: spawn note windows and (optionally) import indicator-stickynotes data
         let mut startup_tasks: Vec<Task<cosmic::Action<Message>>> = app.spawn_sticky_windows();
-        // Import notes: if notes is default and empty (so, it was not loaded from config)
+        // Serve notes API for D-Bus clients
+        startup_tasks.push(notes_api::serve(app.service.storage.location()));
+        // Import notes: either from the file given by --import or,
+        // if notes is default and empty (so, it was not loaded from config)
         // and if indicator-stickynotes is set try import from it
-        if app.notes.is_default_collection() {
+        let import_task = if let Some((import_file, format)) = flags.import {
+            app.import_notes(import_file, format)
+        } else if app.service.notes.is_default_collection() {
             // try read import_file name from config or construct default path to indicator-stickynotes data file
-            startup_tasks.push(cosmic::task::future(Self::import_notes(
-                app.config.import_file.clone(),
-            )));
+            app.import_notes(
+                app.service.config.import_file.clone(),
+                NotesFormat::default(),
+            )
+        } else {
+            Task::none()
+        };
+        // Create a note given by --new when notes are loaded
+        if let Some((content, style)) = flags.new_note {
+            let new_note = Command::NewNote(content, style);
+            startup_tasks.push(import_task.chain(Task::done(Message::Signal(new_note).into())));
+        } else {
+            startup_tasks.push(import_task);
         }
         // Launch background task to perform autosave if autosave_period_ms > 0
-        if app.config.autosave_period_ms > 0 {
-            let pause = app.config.autosave_period_ms;
+        if app.service.config.autosave_period_ms > 0 {
+            let pause = app.service.config.autosave_period_ms;
             tracing::debug!("launch testing autosave notes every {pause} msec");
             startup_tasks.push(
                 // iced_futures::stream::channel will create and read mpsc channel under the hood:
@@ -211,6 +292,22 @@ impl cosmic::Application for ServiceModel {
                 )),
             );
         }
+        // Launch background task to ring reminders if reminder_check_period_ms > 0
+        if app.service.config.reminder_check_period_ms > 0 {
+            let pause = app.service.config.reminder_check_period_ms;
+            tracing::debug!("launch testing reminders every {pause} msec");
+            startup_tasks.push(Task::stream(cosmic::iced_futures::stream::channel(
+                1,
+                async move |mut tx| {
+                    loop {
+                        tokio::time::sleep(Duration::from_millis(pause)).await;
+                        if let Err(e) = tx.try_send(Message::ReminderTimeout.into()) {
+                            tracing::warn!("failed sending reminder signal: {e}");
+                        }
+                    }
+                },
+            )));
+        }
 
         (app, cosmic::task::batch(startup_tasks))
     }
@@ -227,14 +324,41 @@ impl cosmic::Application for ServiceModel {
     /// Constructs views for other windows.
     fn view_window(&self, id: Id) -> Element<'_, Self::Message> {
         if let Some(sticky_window) = self.sticky_windows.get(&id) {
-            sticky_window.build_view(id, &self.notes, &self.icons)
+            sticky_window.build_view(
+                id,
+                &self.service.notes,
+                &self.service.config,
+                &self.icons,
+                self.service.storage.attachments_dir(),
+            )
         } else if let Some(window_id) = self.restore_window_id
             && window_id == id
         {
             widget::container(build_restore_view(
-                &self.notes,
+                &self.service.notes,
+                &self.service.config,
+                &self.icons,
+            ))
+            .class(cosmic::style::Container::Background)
+            .padding(cosmic::theme::spacing().space_s)
+            .into()
+        } else if let Some((window_id, search)) = &self.search_window
+            && *window_id == id
+        {
+            widget::container(search.build_view(
+                &self.service.notes,
                 &self.icons,
-                self.config.toolbar_icon_size,
+                self.service.config.toolbar_icon_size,
+            ))
+            .class(cosmic::style::Container::Background)
+            .padding(cosmic::theme::spacing().space_s)
+            .into()
+        } else if let Some((window_id, history)) = &self.history_window
+            && *window_id == id
+        {
+            widget::container(history.build_view(
+                &self.service.notes,
+                self.is_note_editing(history.get_note_id()),
             ))
             .class(cosmic::style::Container::Background)
             .padding(cosmic::theme::spacing().space_s)
@@ -243,9 +367,9 @@ impl cosmic::Application for ServiceModel {
             && window_id == id
         {
             widget::container(build_settings_view(
-                &self.notes,
+                &self.service.notes,
+                &self.service.config,
                 &self.icons,
-                self.config.toolbar_icon_size,
             ))
             .class(cosmic::style::Container::Background)
             .padding(cosmic::theme::spacing().space_s)
@@ -267,11 +391,16 @@ impl cosmic::Application for ServiceModel {
         } else if let Some(window_id) = self.popup_menu_id
             && window_id == id
         {
-            super::build_main_popup_view(self.core(), Message::Signal, |cmd| match cmd {
-                // some commands are senseless when working through the sticky window menu
-                Command::HideAllNotes | Command::ShowAllNotes => false,
-                _ => true,
-            })
+            super::build_main_popup_view(
+                self.core(),
+                Message::Signal,
+                |cmd| match cmd {
+                    // some commands are senseless when working through the sticky window menu
+                    Command::HideAllNotes | Command::ShowAllNotes => false,
+                    _ => true,
+                },
+                None,
+            )
         } else {
             // There is no visible main window at all
             widget::text(fl!("problem-text")).into()
@@ -289,15 +418,86 @@ impl cosmic::Application for ServiceModel {
         let subscriptions = vec![
             // Watch for application configuration changes.
             self.core()
-                .watch_config::<Config>(Self::APP_ID)
+                .watch_config::<Config>(self.service.storage.config_id())
                 .map(|update| {
                     for e in update.errors {
                         tracing::error!("config error: {e}");
                     }
                     Message::UpdateConfig(update.config)
                 }),
-            // subscribe to some interested events from mouse and window:
+            // subscribe to some interested events from mouse, keyboard and window:
             iced::event::listen_with(|evt, status, id| match evt {
+                Event::Keyboard(KeyboardEvent::KeyPressed {
+                    key: Key::Character(c),
+                    modifiers,
+                    ..
+                }) if modifiers.control()
+                    && modifiers.shift()
+                    && c.eq_ignore_ascii_case(SEARCH_SHORTCUT_KEY) =>
+                {
+                    Some(Message::Signal(Command::SearchNotes))
+                }
+                // the editor handles Ctrl+V itself
+                Event::Keyboard(KeyboardEvent::KeyPressed {
+                    key: Key::Character(c),
+                    modifiers,
+                    ..
+                }) if modifiers.control()
+                    && !modifiers.shift()
+                    && c.eq_ignore_ascii_case(PASTE_SHORTCUT_KEY)
+                    && status == EventStatus::Ignored =>
+                {
+                    Some(Message::NotePaste(id))
+                }
+                // the title and the find inputs handle Ctrl+Z themselves
+                Event::Keyboard(KeyboardEvent::KeyPressed {
+                    key: Key::Character(c),
+                    modifiers,
+                    ..
+                }) if modifiers.control()
+                    && c.eq_ignore_ascii_case(UNDO_SHORTCUT_KEY)
+                    && status == EventStatus::Ignored =>
+                {
+                    if modifiers.shift() {
+                        Some(Message::EditRedo(id))
+                    } else {
+                        Some(Message::EditUndo(id))
+                    }
+                }
+                Event::Keyboard(KeyboardEvent::KeyPressed {
+                    key: Key::Character(c),
+                    modifiers,
+                    ..
+                }) if modifiers.control()
+                    && !modifiers.shift()
+                    && (c.eq_ignore_ascii_case(FIND_SHORTCUT_KEY)
+                        || c.eq_ignore_ascii_case(REPLACE_SHORTCUT_KEY)) =>
+                {
+                    Some(Message::FindOpen(
+                        id,
+                        c.eq_ignore_ascii_case(REPLACE_SHORTCUT_KEY),
+                    ))
+                }
+                Event::Keyboard(KeyboardEvent::KeyPressed {
+                    key: Key::Character(c),
+                    modifiers,
+                    ..
+                }) if modifiers.control()
+                    && !modifiers.shift()
+                    && c.eq_ignore_ascii_case(TASK_SHORTCUT_KEY) =>
+                {
+                    Some(Message::EditToggleTask(id))
+                }
+                Event::Keyboard(KeyboardEvent::KeyPressed {
+                    key: Key::Character(c),
+                    modifiers,
+                    ..
+                }) if modifiers.control()
+                    && modifiers.shift()
+                    && c.eq_ignore_ascii_case(DATE_SHORTCUT_KEY) =>
+                {
+                    Some(Message::EditDateStamp(id))
+                }
                 Event::Mouse(MouseEvent::CursorMoved { .. })
                 | Event::Window(WindowEvent::RedrawRequested(_)) => None,
                 Event::Mouse(mouse_event) => {
@@ -342,8 +542,81 @@ impl cosmic::Application for ServiceModel {
     ///
     /// Tasks may be returned for asynchronous execution of code in the background
     /// on the application's async runtime.
-    #[allow(clippy::too_many_lines)]
     fn update(&mut self, message: Self::Message) -> Task<cosmic::Action<Self::Message>> {
+        let mut tasks = vec![self.on_message(message)];
+        // D-Bus clients are notified about changes made while handling the message
+        let signals = self.service.change_signals();
+        // found notes and the shown content follow the same changes
+        if let Some((_window_id, search)) = &mut self.search_window
+            && !signals.is_empty()
+        {
+            search.update_results(&self.service.notes, self.service.config.title_chars);
+        }
+        for signal in &signals {
+            if let ChangeSignal::NoteContentChanged(note_id, _, content) = signal
+                && let Some(window_id) = self.find_sticky_window(*note_id)
+                && let Some(sticky_window) = self.sticky_windows.get_mut(&window_id)
+            {
+                sticky_window.update_content(content);
+            }
+        }
+        if let Some(emitter) = self.signal_emitter.clone()
+            && !signals.is_empty()
+        {
+            tasks.push(Task::future(async move {
+                notes_api::emit(&emitter, signals).await;
+                Message::Ignore.into()
+            }));
+        }
+        tasks.push(self.send_notifications());
+        cosmic::task::batch(tasks)
+    }
+
+    /// Called when a window is resized.
+    fn on_window_resize(&mut self, id: window::Id, width: f32, height: f32) {
+        if self.sticky_windows.contains_key(&id) {
+            let minimum = self.service.config.sticky_window_minimum();
+            match self.try_get_note_mut(id) {
+                Ok(note) => {
+                    note.set_size(
+                        to_usize(width).max(minimum.width),
+                        to_usize(height).max(minimum.height),
+                    );
+                }
+                Err(e) => tracing::error!("failed to update sticky window size: {e}"),
+            }
+        }
+    }
+
+    /// Called when the escape key is pressed.
+    fn on_escape(&mut self) -> Task<cosmic::Action<Self::Message>> {
+        tracing::trace!("ESC is pressed");
+        if let Some(window_id) = self.core.focused_window()
+            && let Some(window) = self.sticky_windows.get_mut(&window_id)
+            && window.is_editing()
+        {
+            if let Err(e) = window.finish_edit() {
+                tracing::error!("failed finishing edit in sticky window [{window_id}]: {e}");
+            } else {
+                tracing::debug!("finished edit in sticky window [{window_id}]");
+            }
+        }
+        if self.popup_menu_id.is_some() {
+            self.close_popup()
+        } else {
+            Task::none()
+        }
+    }
+
+    #[cfg(feature = "cosmic")]
+    fn style(&self) -> Option<cosmic::iced_runtime::Appearance> {
+        Some(cosmic::applet::style())
+    }
+}
+
+impl ServiceModel {
+    #[allow(clippy::too_many_lines)]
+    fn on_message(&mut self, message: Message) -> Task<cosmic::Action<Message>> {
         match message {
             Message::Signal(command) => {
                 return Task::done(Message::CloseMenuIfOpen.into()).chain(self.on_signal(&command));
@@ -359,7 +632,16 @@ impl cosmic::Application for ServiceModel {
             }
 
             Message::UpdateConfig(config) => {
-                self.config = config;
+                let is_titles_changed = self.service.config.title_chars != config.title_chars;
+                let is_history_changed =
+                    self.service.config.get_history_limits() != config.get_history_limits();
+                self.service.config = config;
+                if is_history_changed {
+                    self.prune_revisions();
+                }
+                if is_titles_changed {
+                    return self.update_window_titles();
+                }
             }
 
             Message::DbusActivation(msg) => {
@@ -367,29 +649,62 @@ impl cosmic::Application for ServiceModel {
                 return Self::on_dbus_activation_message(msg);
             }
 
+            Message::NotesRequest(pending) => {
+                return self.on_notes_request(&pending);
+            }
+
+            Message::NotesApiConnected(emitter) => {
+                let connection = emitter.connection().clone();
+                self.signal_emitter = Some(emitter);
+                return Self::receive_notification_actions(connection);
+            }
+
             Message::Ignore => {}
 
             Message::SetDefaultStyle(style_index) => {
-                if let Err(e) = self.notes.try_set_default_style_by_index(style_index) {
+                if let Err(e) = self
+                    .service
+                    .notes
+                    .try_set_default_style_by_index(style_index)
+                {
                     tracing::error!("failed changing default style: {e}");
+                    self.service
+                        .notify(Category::StyleFailed, fl!("notify-style-failed"), e);
                 }
             }
 
-            Message::LoadNotesCompleted(imported) => {
-                self.notes = imported;
-                return cosmic::task::batch(self.spawn_sticky_windows());
+            Message::HashtagsAsTags(on) => {
+                self.update_config(|config, context| config.set_hashtags_as_tags(context, on));
+            }
+
+            Message::TitleChars(chars) => {
+                let chars = chars.clamp(MIN_TITLE_CHARS, MAX_TITLE_CHARS);
+                self.update_config(|config, context| config.set_title_chars(context, chars));
+                return self.update_window_titles();
+            }
+
+            Message::HistoryRevisions(revisions) => {
+                let revisions = revisions.min(MAX_HISTORY_REVISIONS);
+                self.update_config(|config, context| {
+                    config.set_history_revisions(context, revisions)
+                });
+                self.prune_revisions();
             }
 
-            Message::LoadNotesFailed(msg) => {
-                tracing::error!("failed loading notes: {msg}");
+            Message::HistoryDays(days) => {
+                let days = days.min(MAX_HISTORY_DAYS);
+                self.update_config(|config, context| config.set_history_days(context, days));
+                self.prune_revisions();
             }
 
-            Message::ExportNotesCompleted => {
-                tracing::debug!("export completed successfully");
+            Message::NotesImported(result) => {
+                if self.service.on_imported(result) {
+                    return cosmic::task::batch(self.spawn_sticky_windows());
+                }
             }
 
-            Message::ExportNotesFailed(msg) => {
-                tracing::error!("failed exporting notes: {msg}");
+            Message::NotesExported(result) => {
+                self.service.on_exported(result);
             }
 
             // message related to windows management
@@ -398,12 +713,23 @@ impl cosmic::Application for ServiceModel {
                     id,
                     StickyWindow::new(
                         note_id,
-                        self.config.toolbar_icon_size,
+                        self.service.config.toolbar_icon_size,
                         self.applet_connected.is_false().then_some(popup_variant()),
                     ),
                 );
-                if let Ok(note) = self.notes.try_get_note(&note_id) {
-                    return self.set_window_title(note.get_title().to_string(), id);
+                if let Ok(note) = self.service.notes.try_get_note(&note_id) {
+                    if let Some(sticky_window) = self.sticky_windows.get_mut(&id) {
+                        sticky_window.update_content(note.get_content());
+                    }
+                    // the edit unfinished before the previous exit goes on
+                    if let Some(draft) = note.get_draft()
+                        && let Some(sticky_window) = self.sticky_windows.get_mut(&id)
+                        && let Err(e) = sticky_window.resume_edit(draft)
+                    {
+                        tracing::error!("[{id}] failed to resume edit: {e}");
+                    }
+                    let title = note.get_title(self.service.config.title_chars).to_string();
+                    return self.set_window_title(title, id);
                 }
             }
 
@@ -415,6 +741,28 @@ impl cosmic::Application for ServiceModel {
                 return self.set_window_title(fl!("recently-deleted-title"), id);
             }
 
+            Message::SearchWindowCreated(id) => {
+                let search = SearchView::new();
+                let input_id = search.input_id();
+                if self.search_window.replace((id, search)).is_some() {
+                    tracing::warn!("replacing existing search window with new one");
+                }
+                return self
+                    .set_window_title(fl!("search-title"), id)
+                    .chain(widget::text_input::focus(input_id));
+            }
+
+            Message::HistoryWindowCreated(id, note_id) => {
+                if self
+                    .history_window
+                    .replace((id, HistoryView::new(note_id)))
+                    .is_some()
+                {
+                    tracing::warn!("replacing existing history window with new one");
+                }
+                return self.set_window_title(self.history_title(note_id), id);
+            }
+
             Message::SettingsWindowCreated(id) => {
                 if self.settings_window_id.is_some() {
                     tracing::warn!("replacing existing settings window ID with new one");
@@ -424,7 +772,7 @@ impl cosmic::Application for ServiceModel {
             }
 
             Message::EditStyleWindowCreated(window_id, style_id, is_new) => {
-                match self.notes.try_get_style(&style_id) {
+                match self.service.notes.try_get_style(&style_id) {
                     Ok(style) => {
                         if self.edit_style.is_some() {
                             tracing::warn!("replacing existing edit style dialog with new one");
@@ -454,6 +802,90 @@ impl cosmic::Application for ServiceModel {
                 }
             }
 
+            Message::EditUndo(window_id) => {
+                if let Some(context) = self.try_get_edit_context(window_id) {
+                    context.undo();
+                }
+            }
+
+            Message::EditRedo(window_id) => {
+                if let Some(context) = self.try_get_edit_context(window_id) {
+                    context.redo();
+                }
+            }
+
+            Message::EditIndent(window_id, forward) => {
+                if let Some(context) = self.try_get_edit_context(window_id) {
+                    context.indent(forward);
+                }
+            }
+
+            Message::EditToggleTask(window_id) => {
+                if let Some(context) = self.try_get_edit_context(window_id) {
+                    context.toggle_task();
+                }
+            }
+
+            Message::EditDateStamp(window_id) => {
+                let stamp = format_date_stamp(&Local::now(), &self.service.config.date_format)
+                    .unwrap_or_else(|| {
+                        tracing::error!("broken date format {:?}", self.service.config.date_format);
+                        Local::now().format(DEF_DATE_FORMAT).to_string()
+                    });
+                if let Some(context) = self.try_get_edit_context(window_id) {
+                    context.insert(stamp);
+                }
+            }
+
+            Message::FindOpen(window_id, is_replace) => {
+                if let Some(context) = self.try_get_edit_context(window_id) {
+                    return widget::text_input::focus(context.open_find(is_replace));
+                }
+            }
+
+            Message::FindClose(window_id) => {
+                if let Some(context) = self.try_get_edit_context(window_id) {
+                    context.close_find();
+                }
+            }
+
+            Message::FindInput(window_id, query) => {
+                if let Some(context) = self.try_get_edit_context(window_id) {
+                    context.update_find_query(query);
+                }
+            }
+
+            Message::FindMatchCase(window_id, on) => {
+                if let Some(context) = self.try_get_edit_context(window_id) {
+                    context.set_match_case(on);
+                }
+            }
+
+            Message::FindNext(window_id, forward) => {
+                if let Some(context) = self.try_get_edit_context(window_id) {
+                    context.find_next(forward);
+                }
+            }
+
+            Message::ReplaceInput(window_id, replacement) => {
+                if let Some(context) = self.try_get_edit_context(window_id) {
+                    context.update_replacement(replacement);
+                }
+            }
+
+            Message::ReplaceNext(window_id) => {
+                if let Some(context) = self.try_get_edit_context(window_id) {
+                    context.replace_current();
+                }
+            }
+
+            Message::ReplaceAll(window_id) => {
+                if let Some(context) = self.try_get_edit_context(window_id) {
+                    let count = context.replace_all();
+                    tracing::debug!("[{window_id}] replaced {count} matches");
+                }
+            }
+
             Message::AppMouseEvent((id, event)) => {
                 return self.on_mouse_event(id, &event);
             }
@@ -479,13 +911,13 @@ impl cosmic::Application for ServiceModel {
                 if is_on {
                     self.on_start_edit(id);
                 } else {
-                    self.on_finish_edit(id);
+                    return self.on_finish_edit(id);
                 }
             }
 
             Message::NoteStyle(id) => {
                 if let Some(sticky_window) = self.sticky_windows.get_mut(&id) {
-                    sticky_window.allow_select_style(self.notes.get_style_names());
+                    sticky_window.allow_select_style(self.service.notes.get_style_names());
                 } else {
                     tracing::error!("{id}: sticky window is not found to change style");
                 }
@@ -495,6 +927,10 @@ impl cosmic::Application for ServiceModel {
                 self.on_style_selected(id, style_index);
             }
 
+            Message::NoteCodeLanguage(id, index) => {
+                self.on_code_language_selected(id, index);
+            }
+
             Message::NoteNew => {
                 return self.on_new_note_window();
             }
@@ -507,6 +943,159 @@ impl cosmic::Application for ServiceModel {
                 return self.on_restore_note(note_id);
             }
 
+            Message::NoteSnooze(id) => {
+                if let Some(sticky_window) = self.sticky_windows.get(&id) {
+                    let minutes = self.service.config.reminder_snooze_min;
+                    return self.apply_request(&Request::SnoozeReminder(
+                        sticky_window.get_note_id(),
+                        minutes,
+                    ));
+                }
+            }
+
+            Message::NoteDismiss(id) => {
+                if let Some(sticky_window) = self.sticky_windows.get(&id) {
+                    return self
+                        .apply_request(&Request::DismissReminder(sticky_window.get_note_id()));
+                }
+            }
+
+            Message::NoteTags(id) => {
+                if let Some(sticky_window) = self.sticky_windows.get_mut(&id)
+                    && let Ok(note) = self
+                        .service
+                        .notes
+                        .try_get_note(&sticky_window.get_note_id())
+                {
+                    sticky_window.toggle_edit_tags(note.get_tags());
+                }
+            }
+
+            Message::NoteTitleInput(id, input) => {
+                if let Some(sticky_window) = self.sticky_windows.get_mut(&id) {
+                    sticky_window.update_title_input(input);
+                }
+            }
+
+            Message::NoteTagsInput(id, input) => {
+                if let Some(sticky_window) = self.sticky_windows.get_mut(&id) {
+                    sticky_window.update_tags_input(input);
+                }
+            }
+
+            Message::NoteTagsSubmit(id) => {
+                if let Some(sticky_window) = self.sticky_windows.get_mut(&id)
+                    && let Some(input) = sticky_window.finish_edit_tags()
+                {
+                    let note_id = sticky_window.get_note_id();
+                    return self.apply_request(&Request::SetNoteTags(note_id, parse_tags(&input)));
+                }
+            }
+
+            Message::NotePaste(id) => {
+                if self
+                    .sticky_windows
+                    .get(&id)
+                    .is_some_and(|sticky_window| !sticky_window.is_editing())
+                {
+                    return iced::clipboard::read()
+                        .map(move |text| Message::NotePasteFiles(id, text).into());
+                }
+            }
+
+            Message::NotePasteFiles(id, text) => {
+                let files = text.as_deref().map(parse_file_list).unwrap_or_default();
+                if files.is_empty() {
+                    // not a file list, try the copied image
+                    return iced::clipboard::read_data::<ClipboardImage>()
+                        .map(move |image| Message::NotePasteImage(id, image).into());
+                }
+                return self.on_attach_files(id, files);
+            }
+
+            Message::NotePasteImage(id, image) => match image {
+                Some(image) => return self.on_attach_image(id, image),
+                None => tracing::debug!("nothing to attach in the clipboard"),
+            },
+
+            Message::NoteAttached(note_id, attachments) => {
+                match self.service.notes.try_get_note_mut(&note_id) {
+                    Ok(note) if note.is_locked() => {
+                        tracing::debug!("locked note is not attached to");
+                    }
+                    Ok(note) => {
+                        for attachment in attachments {
+                            note.add_attachment(attachment);
+                        }
+                    }
+                    Err(e) => tracing::error!("failed attaching files: {e}"),
+                }
+            }
+
+            Message::NoteAttachmentRemove(id, file) => match self.try_get_note_mut(id) {
+                Ok(note) => {
+                    note.remove_attachment(&file);
+                }
+                Err(e) => tracing::error!("failed removing attachment: {e}"),
+            },
+
+            Message::NoteTaskToggle(id, line) => {
+                return self.on_update_tasks(id, |content| toggle_task(content, line));
+            }
+
+            Message::NoteTasksDown(id) => {
+                return self.on_update_tasks(id, |content| Some(move_done_tasks_down(content)));
+            }
+
+            Message::NoteTasksClear(id) => {
+                return self.on_update_tasks(id, |content| Some(clear_done_tasks(content)));
+            }
+
+            Message::NoteCopyLink(id, index) => {
+                if let Some(link) = self
+                    .sticky_windows
+                    .get(&id)
+                    .and_then(|sticky_window| sticky_window.get_links().get(index))
+                {
+                    tracing::debug!("copy URL {link}");
+                    return iced::clipboard::write(link.clone());
+                }
+            }
+
+            Message::NoteHistory(id) => {
+                if let Some(sticky_window) = self.sticky_windows.get(&id) {
+                    return self.spawn_history_window(sticky_window.get_note_id());
+                }
+            }
+
+            Message::HistorySelect(index) => {
+                if let Some((_window_id, history)) = &mut self.history_window {
+                    history.select(Some(index));
+                }
+            }
+
+            Message::HistoryRollback(index) => {
+                return self.on_history_rollback(index);
+            }
+
+            Message::SearchInput(query) => {
+                if let Some((_window_id, search)) = &mut self.search_window {
+                    search.update_query(
+                        query,
+                        &self.service.notes,
+                        self.service.config.title_chars,
+                    );
+                }
+            }
+
+            Message::SearchShow(note_id) => {
+                return self.apply_request(&Request::SetNoteVisible(note_id, true));
+            }
+
+            Message::SearchEdit(note_id) => {
+                return self.on_edit_note(note_id);
+            }
+
             Message::StyleNew => {
                 return self.on_new_style();
             }
@@ -526,6 +1115,7 @@ impl cosmic::Application for ServiceModel {
                         dialog.get_name(),
                         dialog.get_font(),
                         dialog.get_background_color(),
+                        dialog.is_markdown(),
                     );
                     return window::close(window_id);
                 }
@@ -535,9 +1125,11 @@ impl cosmic::Application for ServiceModel {
                 if let Some((window_id, dialog)) = self.edit_style.take() {
                     // must delete new style because it is in collection now
                     if dialog.is_new_style()
-                        && let Err(e) = self.notes.delete_style(dialog.get_id())
+                        && let Err(e) = self.service.notes.delete_style(dialog.get_id())
                     {
                         tracing::error!("failed to delete new style: {e}");
+                        self.service
+                            .notify(Category::StyleFailed, fl!("notify-style-failed"), e);
                     }
                     return window::close(window_id);
                 }
@@ -567,6 +1159,12 @@ impl cosmic::Application for ServiceModel {
                 }
             }
 
+            Message::MarkdownUpdate(is_markdown) => {
+                if let Some((_window_id, dialog)) = &mut self.edit_style {
+                    dialog.update_markdown(is_markdown);
+                }
+            }
+
             Message::OpenUrl(url) => match open::that_detached(&url) {
                 Ok(()) => tracing::debug!("go to URL {url}"),
                 Err(err) => tracing::error!("failed to open {url:?}: {err}"),
@@ -575,53 +1173,20 @@ impl cosmic::Application for ServiceModel {
             Message::AutosaveTimeout => {
                 self.on_autosave();
             }
-        }
-        Task::none()
-    }
 
-    /// Called when a window is resized.
-    fn on_window_resize(&mut self, id: window::Id, width: f32, height: f32) {
-        if self.sticky_windows.contains_key(&id) {
-            let minimum = self.config.sticky_window_minimum();
-            match self.try_get_note_mut(id) {
-                Ok(note) => {
-                    note.set_size(
-                        to_usize(width).max(minimum.width),
-                        to_usize(height).max(minimum.height),
-                    );
+            Message::ReminderTimeout => {
+                return self.on_reminders();
+            }
+
+            Message::NotificationAction(key) => {
+                if let Some(request) = self.service.on_notification_action(&key) {
+                    return self.apply_request(&request);
                 }
-                Err(e) => tracing::error!("failed to update sticky window size: {e}"),
             }
         }
+        Task::none()
     }
 
-    /// Called when the escape key is pressed.
-    fn on_escape(&mut self) -> Task<cosmic::Action<Self::Message>> {
-        tracing::trace!("ESC is pressed");
-        if let Some(window_id) = self.core.focused_window()
-            && let Some(window) = self.sticky_windows.get_mut(&window_id)
-            && window.is_editing()
-        {
-            if let Err(e) = window.finish_edit() {
-                tracing::error!("failed finishing edit in sticky window [{window_id}]: {e}");
-            } else {
-                tracing::debug!("finished edit in sticky window [{window_id}]");
-            }
-        }
-        if self.popup_menu_id.is_some() {
-            self.close_popup()
-        } else {
-            Task::none()
-        }
-    }
-
-    #[cfg(feature = "cosmic")]
-    fn style(&self) -> Option<cosmic::iced_runtime::Appearance> {
-        Some(cosmic::applet::style())
-    }
-}
-
-impl ServiceModel {
     #[cfg(not(feature = "cosmic"))]
     fn open_popup(&mut self, _parent_window_id: Id) -> Task<cosmic::Action<Message>> {
         Task::none()
@@ -663,12 +1228,57 @@ impl ServiceModel {
     }
 
     fn on_signal(&mut self, command: &Command) -> Task<cosmic::Action<Message>> {
-        tracing::trace!("handling command {command}");
         match command {
-            Command::Ignored => {
-                // nothing to do
+            Command::NewNote(content, style) if content.is_empty() && style.is_empty() => {
+                return self.on_new_note_window();
+            }
+            // edits are saved as drafts, so the notes being edited stay in edit mode
+            Command::SaveNotes => self.keep_drafts(),
+            _ => {}
+        }
+        match self.service.on_command(command) {
+            Followup::Done => Task::none(),
+            Followup::Quit => {
+                self.on_quit();
+                // the failure of saving notes must be shown before exit
+                self.send_notifications().chain(iced::exit())
             }
+            Followup::Request(request) => self.apply_request(&request),
+            Followup::Import(file, format) => self.import_notes(file, format),
+            Followup::Export(file, format) => {
+                //todo: stop editing all sticky windows (?) or ask user about
+                let export = ServiceCore::export_notes(
+                    file,
+                    self.service.notes.clone(),
+                    format,
+                    self.service.storage.attachments_dir().to_path_buf(),
+                );
+                cosmic::task::future(async move { Message::NotesExported(export.await) })
+            }
+            Followup::Windows => self.on_windows_command(command),
+            Followup::Windowed => self.on_windowed_command(command),
+        }
+    }
 
+    // sticky windows follow notes changed by the command
+    fn on_windows_command(&mut self, command: &Command) -> Task<cosmic::Action<Message>> {
+        match command {
+            Command::HideAllNotes => cosmic::task::batch(self.close_sticky_windows()),
+            Command::ShowTagged(_) => {
+                self.no_windows = false;
+                self.update_sticky_windows()
+            }
+            _ => {
+                if *command == Command::ShowAllNotes {
+                    self.no_windows = false;
+                }
+                cosmic::task::batch(self.spawn_sticky_windows())
+            }
+        }
+    }
+
+    fn on_windowed_command(&mut self, command: &Command) -> Task<cosmic::Action<Message>> {
+        match command {
             Command::Connect => {
                 tracing::debug!("notes-applet is connected; hide menu in sticky windows");
                 self.applet_connected = true;
@@ -676,72 +1286,14 @@ impl ServiceModel {
                 self.sticky_windows
                     .values_mut()
                     .for_each(StickyWindow::hide_popup_menu);
+                Task::none()
             }
-
-            Command::Quit => {
-                self.on_quit();
-                return iced::exit();
-            }
-
-            // messages related to loading and saving notes
-            Command::LoadNotes => {
-                if self.notes.is_unsaved() {
-                    // todo: ask to overwrite unsaved notes
-                    tracing::error!("drop unsaved changes while loading collection");
-                }
-                self.notes = Self::load_notes_or_default(&self.config.notes);
-            }
-
-            Command::SaveNotes => {
-                //todo: stop editing all sticky windows or ask user
-                if let Err(e) = self.save_notes() {
-                    tracing::error!("failed saving notes: {e}");
-                }
-            }
-
-            Command::ImportNotes => {
-                if self.notes.is_unsaved() {
-                    // todo: ask to overwrite unsaved notes
-                    tracing::error!("drop unsaved changes while importing collection");
-                }
-                let import_file = self.config.import_file.clone();
-                // opposite to other cases return real task instead of none()
-                return cosmic::task::future(Self::import_notes(import_file));
-            }
-
-            Command::ExportNotes => {
-                //todo: stop editing all sticky windows (?) or ask user about
-                let export_file = self.config.import_file.clone();
-                let notes = self.notes.clone();
-                return cosmic::task::future(Self::export_notes(export_file, notes));
-            }
-
-            Command::ShowAllNotes => {
-                return self.on_change_notes_visibility(true);
-            }
-
-            Command::HideAllNotes => {
-                return self.on_change_notes_visibility(false);
-            }
-
-            Command::LockAll => {
-                self.notes.for_each_note_mut(|note| note.set_locking(true));
-            }
-
-            Command::RestoreNotes => {
-                return self.spawn_restore_notes_window();
-            }
-
-            Command::OpenSettings => {
-                return self.spawn_settings_window();
-            }
-
-            Command::OpenAbout => {
-                return self.spawn_about_window();
-            }
+            Command::RestoreNotes => self.spawn_restore_notes_window(),
+            Command::SearchNotes => self.spawn_search_window(),
+            Command::OpenSettings => self.spawn_settings_window(),
+            Command::OpenAbout => self.spawn_about_window(),
+            _ => Task::none(),
         }
-
-        Task::none()
     }
 
     #[allow(clippy::single_match)]
@@ -749,10 +1301,10 @@ impl ServiceModel {
         match msg.msg {
             dbus_activation::Details::ActivateAction {
                 action: action_name,
-                args: _,
+                args,
             } => {
                 tracing::info!("handling dbus_activation message {}", &action_name);
-                match Command::from_str(action_name.as_str()) {
+                match Command::from_action(&action_name, &args) {
                     Ok(cmd) => {
                         return Task::done(Message::Signal(cmd).into());
                     }
@@ -767,127 +1319,256 @@ impl ServiceModel {
         Task::none()
     }
 
-    fn on_quit(&mut self) {
-        // save changes if any to persistent storage
-        if self.notes.is_unsaved() {
-            if let Err(e) = self.save_notes() {
-                tracing::error!("failed saving notes on exit: {e}");
-            } else {
-                tracing::info!("notes collection was saved");
+    fn on_notes_request(&mut self, pending: &PendingRequest) -> Task<cosmic::Action<Message>> {
+        tracing::debug!("handling D-Bus request {:?}", pending.request());
+        match self.handle_notes_request(pending.request()) {
+            Ok((response, task)) => {
+                pending.respond(Ok(response));
+                task
+            }
+            Err(e) => {
+                tracing::warn!("failed D-Bus request {:?}: {e}", pending.request());
+                pending.respond(Err(e.to_string()));
+                Task::none()
             }
-        } else {
-            tracing::info!("notes collection is unchanged, skip saving");
         }
-        // warn if deleted notes were dropped
-        let count_deleted = self.notes.iter_deleted_notes().count();
-        if count_deleted > 0 {
-            //todo: what about saving deleted notes too? Maybe with their TTLs
-            tracing::warn!("completely drop some deleted notes on exit: {count_deleted}");
+    }
+
+    fn handle_notes_request(
+        &mut self,
+        request: &Request,
+    ) -> Result<(Response, Task<cosmic::Action<Message>>), NotesCollectionError> {
+        if let Request::Command(command) = request {
+            let message = Message::Signal(command.clone());
+            return Ok((Response::Done, Task::done(message.into())));
         }
+        // the window of the note must be found before the note is deleted
+        let window_id = match request {
+            Request::UpdateNoteContent(note_id, _)
+            | Request::SetNoteTitle(note_id, _)
+            | Request::SetNoteVisible(note_id, _)
+            | Request::SetNoteGeometry(note_id, ..)
+            | Request::DeleteNote(note_id) => self.find_sticky_window(*note_id),
+            _ => None,
+        };
+        let response = notes_api::apply(&mut self.service.notes, &self.service.config, request)?;
+        let task = match (request, &response, window_id) {
+            // notes created with --no-windows are opened with the others
+            (Request::CreateNote(..), Response::Created(note_id), _) if !self.no_windows => {
+                self.spawn_note_window(*note_id)
+            }
+            (Request::SetNoteVisible(note_id, true) | Request::RestoreNote(note_id), _, None) => {
+                self.spawn_note_window(*note_id)
+            }
+            // the note is visible already, so raise its window
+            (Request::SetNoteVisible(_, true), _, Some(window_id)) => window::gain_focus(window_id),
+            (Request::SetNoteGeometry(_, left, top, width, height), _, Some(window_id)) => {
+                cosmic::task::batch(vec![
+                    window::move_to(window_id, Point::new(to_f32(*left), to_f32(*top))),
+                    window::resize(window_id, Size::new(to_f32(*width), to_f32(*height))),
+                ])
+            }
+            (
+                Request::UpdateNoteContent(note_id, _) | Request::SetNoteTitle(note_id, _),
+                _,
+                Some(window_id),
+            ) => {
+                let note = self.service.notes.try_get_note(note_id)?;
+                let title = note.get_title(self.service.config.title_chars).to_string();
+                self.set_window_title(title, window_id)
+            }
+            (Request::SetNoteVisible(_, false), _, Some(window_id)) => {
+                // the hidden note is opened in edit mode again when shown
+                self.keep_drafts();
+                self.sticky_windows.remove(&window_id);
+                window::close(window_id)
+            }
+            (Request::DeleteNote(_), _, Some(window_id)) => {
+                self.sticky_windows.remove(&window_id);
+                window::close(window_id)
+            }
+            _ => Task::none(),
+        };
+        Ok((response, task))
     }
 
-    fn on_autosave(&mut self) {
-        tracing::debug!("test if notes are changed");
-        if self.notes.is_unsaved() {
-            tracing::debug!("save notes");
-            if let Err(e) = self.save_notes() {
-                tracing::error!("failed saving notes: {e}");
+    // applies the request of the command the same way as the notes API does
+    fn apply_request(&mut self, request: &Request) -> Task<cosmic::Action<Message>> {
+        match self.handle_notes_request(request) {
+            Ok((_, task)) => task,
+            Err(e) => {
+                tracing::error!("failed {request:?}: {e}");
+                Task::none()
             }
-        } else {
-            tracing::debug!("no changes in notes to save");
         }
     }
 
-    fn try_get_note_mut(&mut self, window_id: Id) -> Result<&mut NoteData, String> {
+    fn find_sticky_window(&self, note_id: Uuid) -> Option<Id> {
         self.sticky_windows
-            .get(&window_id)
-            .ok_or_else(|| format!("Sticky window {window_id} is not found"))
-            .and_then(|sticky_window| {
-                self.notes
-                    .try_get_note_mut(&sticky_window.get_note_id())
-                    .map_err(|e| e.to_string())
-            })
+            .iter()
+            .find_map(|(window_id, window)| (window.get_note_id() == note_id).then_some(*window_id))
     }
 
-    fn load_notes_or_default(json: &str) -> NotesCollection {
-        if json.is_empty() {
-            NotesCollection::default()
-        } else {
-            NotesCollection::try_read(json)
-                .map_err(|e| {
-                    tracing::error!(
-                        "failed loading notes from {}/v{}/notes: {e}",
-                        <Self as cosmic::Application>::APP_ID,
-                        Config::VERSION
-                    );
-                })
-                .unwrap_or_default()
+    fn on_quit(&mut self) {
+        // notes being edited are opened in edit mode on the next start
+        self.keep_drafts();
+        // save changes if any to persistent storage
+        self.service.quit();
+    }
+
+    // the text of notes being edited is kept as drafts until the edit is finished
+    fn keep_drafts(&mut self) {
+        for sticky_window in self.sticky_windows.values() {
+            let Some(draft) = sticky_window.get_draft() else {
+                continue;
+            };
+            match self
+                .service
+                .notes
+                .try_get_note_mut(&sticky_window.get_note_id())
+            {
+                Ok(note) => {
+                    // the edit which changed nothing is not worth the draft
+                    let is_changed = draft.get_content() != note.get_content()
+                        || draft.get_title().trim()
+                            != note.get_explicit_title().unwrap_or_default();
+                    note.set_draft(is_changed.then_some(draft));
+                }
+                Err(e) => tracing::error!("failed keeping draft: {e}"),
+            }
         }
     }
 
-    fn save_notes(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let json = self.notes.try_write()?;
-        let global_config =
-            cosmic_config::Config::new(<Self as cosmic::Application>::APP_ID, Config::VERSION)?;
-        let tx = global_config.transaction();
-        tx.set("notes", json)?;
-        tx.commit()?;
-        self.notes.commit_changes();
-        Ok(())
+    fn on_autosave(&mut self) {
+        self.keep_drafts();
+        self.service.autosave();
     }
 
-    async fn import_notes(configured_import_file: String) -> Message {
-        if configured_import_file.is_empty() {
-            Message::LoadNotesFailed("No import file is set".to_string())
-        } else {
-            let import_file_owned = configured_import_file.clone();
-            match tokio::task::spawn_blocking(move || {
-                NotesCollection::try_import(import_file_owned)
+    // titles of notes titled by the first line depend on the length of titles
+    fn update_window_titles(&mut self) -> Task<cosmic::Action<Message>> {
+        let titles: Vec<(Id, String)> = self
+            .sticky_windows
+            .iter()
+            .filter_map(|(window_id, sticky_window)| {
+                let note = self
+                    .service
+                    .notes
+                    .try_get_note(&sticky_window.get_note_id())
+                    .ok()?;
+                Some((
+                    *window_id,
+                    note.get_title(self.service.config.title_chars).to_string(),
+                ))
             })
-            .await
-            {
-                Ok(task) => match task.await {
-                    Ok(v) => Message::LoadNotesCompleted(v),
-                    Err(e) => {
-                        let msg =
-                            format!("failed reading notes from {configured_import_file}: {e}");
-                        Message::LoadNotesFailed(msg)
-                    }
-                },
-                Err(e) => Message::LoadNotesFailed(format!("{e}")),
-            }
+            .collect();
+        let mut tasks = Vec::with_capacity(titles.len());
+        for (window_id, title) in titles {
+            tasks.push(self.set_window_title(title, window_id));
         }
+        cosmic::task::batch(tasks)
     }
 
-    async fn export_notes(configured_export_file: String, notes: NotesCollection) -> Message {
-        if configured_export_file.is_empty() {
-            Message::ExportNotesFailed("No export file is set".to_string())
-        } else {
-            let export_file_owned = configured_export_file.clone();
-            match tokio::task::spawn_blocking(move || {
-                NotesCollection::try_export(export_file_owned, notes)
-            })
-            .await
-            {
-                Ok(task) => match task.await {
-                    Ok(()) => Message::ExportNotesCompleted,
-                    Err(e) => {
-                        let msg =
-                            format!("failed reading notes from {configured_export_file}: {e}");
-                        Message::ExportNotesFailed(msg)
+    // rings due reminders: the note is notified about and its window is shown and raised
+    fn on_reminders(&mut self) -> Task<cosmic::Action<Message>> {
+        let ringing = self.service.ring_reminders(Utc::now());
+        let mut tasks = Vec::with_capacity(ringing.len());
+        for note_id in ringing {
+            tasks.push(self.apply_request(&Request::SetNoteVisible(note_id, true)));
+        }
+        cosmic::task::batch(tasks)
+    }
+
+    // revisions beyond the history limits are dropped as soon as the limits are changed
+    fn prune_revisions(&mut self) {
+        self.service
+            .notes
+            .prune_revisions(self.service.config.get_history_limits(), Utc::now());
+    }
+
+    // the setting is changed right away and stored into the config,
+    // changes made by others come with UpdateConfig
+    fn update_config(
+        &mut self,
+        set: impl FnOnce(&mut Config, &cosmic_config::Config) -> Result<bool, cosmic_config::Error>,
+    ) {
+        let stored = cosmic_config::Config::new(self.service.storage.config_id(), Config::VERSION)
+            .and_then(|context| set(&mut self.service.config, &context));
+        if let Err(e) = stored {
+            tracing::error!("failed storing config: {e}");
+        }
+    }
+
+    // reminders are snoozed or dismissed by actions of their notifications
+    fn receive_notification_actions(connection: zbus::Connection) -> Task<cosmic::Action<Message>> {
+        Task::stream(cosmic::iced_futures::stream::channel(
+            1,
+            async move |mut tx| match notifications::receive_actions(&connection).await {
+                Ok(mut actions) => {
+                    while let Some(key) = actions.next().await {
+                        if tx
+                            .send(Message::NotificationAction(key).into())
+                            .await
+                            .is_err()
+                        {
+                            break;
+                        }
                     }
-                },
-                Err(e) => Message::ExportNotesFailed(format!("{e}")),
-            }
+                }
+                Err(e) => tracing::warn!("failed receiving actions of notifications: {e}"),
+            },
+        ))
+    }
+
+    fn try_get_note_mut(&mut self, window_id: Id) -> Result<&mut NoteData, String> {
+        self.sticky_windows
+            .get(&window_id)
+            .ok_or_else(|| format!("Sticky window {window_id} is not found"))
+            .and_then(|sticky_window| {
+                self.service
+                    .notes
+                    .try_get_note_mut(&sticky_window.get_note_id())
+                    .map_err(|e| e.to_string())
+            })
+    }
+
+    fn send_notifications(&mut self) -> Task<cosmic::Action<Message>> {
+        let notifications = self.service.take_notifications();
+        if notifications.is_empty() {
+            return Task::none();
         }
+        // the notes API connection is to the session bus as well
+        let Some(connection) = self
+            .signal_emitter
+            .as_ref()
+            .map(|emitter| emitter.connection().clone())
+        else {
+            tracing::warn!("session bus is not connected to show {notifications:?}");
+            return Task::none();
+        };
+        Task::future(async move {
+            send_notifications(&connection, notifications).await;
+            Message::Ignore.into()
+        })
+    }
+
+    fn import_notes(&self, file: String, format: NotesFormat) -> Task<cosmic::Action<Message>> {
+        let import = ServiceCore::import_notes(
+            file,
+            format,
+            self.service.storage.attachments_dir().to_path_buf(),
+        );
+        cosmic::task::future(async move { Message::NotesImported(import.await) })
     }
 
     fn on_new_note_window(&mut self) -> Task<cosmic::Action<Message>> {
-        let note_id = self.notes.new_note();
-        match self.notes.try_get_note(&note_id) {
+        let note_id = self.service.notes.new_note();
+        match self.service.notes.try_get_note(&note_id) {
             Ok(note) => {
-                let (window_id, task) =
-                    Self::spawn_sticky_window(note_id, note, self.config.sticky_window_minimum());
+                let (window_id, task) = Self::spawn_sticky_window(
+                    note_id,
+                    note,
+                    self.service.config.sticky_window_minimum(),
+                );
                 task.chain(
                     cosmic::Task::done(Message::NoteEdit(window_id, true))
                         .map(cosmic::Action::from),
@@ -901,10 +1582,13 @@ impl ServiceModel {
     }
 
     fn on_restore_note(&mut self, note_id: Uuid) -> Task<cosmic::Action<Message>> {
-        match self.notes.try_restore_deleted_note(note_id) {
+        match self.service.notes.try_restore_deleted_note(note_id) {
             Ok(note) => {
-                let (_id, task) =
-                    Self::spawn_sticky_window(note_id, note, self.config.sticky_window_minimum());
+                let (_id, task) = Self::spawn_sticky_window(
+                    note_id,
+                    note,
+                    self.service.config.sticky_window_minimum(),
+                );
                 task
             }
             Err(e) => {
@@ -914,6 +1598,29 @@ impl ServiceModel {
         }
     }
 
+    // shows the note if it is hidden, raises its window and begins edit
+    fn on_edit_note(&mut self, note_id: Uuid) -> Task<cosmic::Action<Message>> {
+        if let Some(window_id) = self.find_sticky_window(note_id) {
+            self.on_start_edit(window_id);
+            return window::gain_focus(window_id);
+        }
+        match self.service.notes.try_get_note_mut(&note_id) {
+            Ok(note) => {
+                note.set_visibility(true);
+                let (window_id, task) = Self::spawn_sticky_window(
+                    note_id,
+                    note,
+                    self.service.config.sticky_window_minimum(),
+                );
+                task.chain(Task::done(Message::NoteEdit(window_id, true).into()))
+            }
+            Err(e) => {
+                tracing::error!("failed to edit note: {e}");
+                Task::none()
+            }
+        }
+    }
+
     fn on_change_note_locking(&mut self, window_id: Id, is_on: bool) {
         match self.try_get_note_mut(window_id) {
             Ok(note) => {
@@ -923,19 +1630,82 @@ impl ServiceModel {
         }
     }
 
-    fn on_change_notes_visibility(&mut self, on: bool) -> Task<cosmic::Action<Message>> {
-        self.notes.for_each_note_mut(|note| note.set_visibility(on));
-        if on {
-            cosmic::task::batch(self.spawn_sticky_windows())
-        } else {
-            cosmic::task::batch(self.close_sticky_windows())
+    // windows of hidden notes are closed, visible notes get windows if they have none
+    fn update_sticky_windows(&mut self) -> Task<cosmic::Action<Message>> {
+        let mut commands = Vec::new();
+        let visibility: Vec<(Uuid, bool)> = self
+            .service
+            .notes
+            .iter_notes()
+            .map(|(note_id, note)| (*note_id, note.is_visible()))
+            .collect();
+        let hidden_windows: Vec<Id> = visibility
+            .iter()
+            .filter(|(_, is_visible)| !is_visible)
+            .filter_map(|(note_id, _)| self.find_sticky_window(*note_id))
+            .collect();
+        if !hidden_windows.is_empty() {
+            // hidden notes are opened in edit mode again when shown
+            self.keep_drafts();
         }
+        for window_id in hidden_windows {
+            self.sticky_windows.remove(&window_id);
+            commands.push(window::close(window_id));
+        }
+        for (note_id, is_visible) in visibility {
+            if is_visible && self.find_sticky_window(note_id).is_none() {
+                commands.push(self.spawn_note_window(note_id));
+            }
+        }
+        cosmic::task::batch(commands)
+    }
+
+    // replaces the content of the note by the one with updated tasks
+    fn on_update_tasks(
+        &mut self,
+        window_id: Id,
+        update: impl FnOnce(&str) -> Option<String>,
+    ) -> Task<cosmic::Action<Message>> {
+        let Some(note_id) = self
+            .sticky_windows
+            .get(&window_id)
+            .map(StickyWindow::get_note_id)
+        else {
+            tracing::error!("{window_id}: sticky window is not found to update tasks");
+            return Task::none();
+        };
+        match self.service.notes.try_get_note(&note_id) {
+            Ok(note) if note.is_locked() => Task::none(),
+            Ok(note) => match update(note.get_content()) {
+                Some(content) if content != note.get_content() => {
+                    self.apply_request(&Request::UpdateNoteContent(note_id, content))
+                }
+                _ => Task::none(),
+            },
+            Err(e) => {
+                tracing::error!("failed updating tasks: {e}");
+                Task::none()
+            }
+        }
+    }
+
+    // shortcuts of edit mode are ignored in other windows and in view mode
+    fn try_get_edit_context(&mut self, window_id: Id) -> Option<&mut EditContext> {
+        self.sticky_windows
+            .get_mut(&window_id)
+            .and_then(|sticky_window| sticky_window.edit_context_mut().ok())
     }
 
     fn on_start_edit(&mut self, window_id: Id) {
         if let Some(sticky_window) = self.sticky_windows.get_mut(&window_id) {
-            if let Ok(note) = self.notes.try_get_note(&sticky_window.get_note_id())
-                && let Err(e) = sticky_window.start_edit(note.get_content())
+            if let Ok(note) = self
+                .service
+                .notes
+                .try_get_note(&sticky_window.get_note_id())
+                && let Err(e) = sticky_window.start_edit(
+                    note.get_content(),
+                    note.get_explicit_title().unwrap_or_default(),
+                )
             {
                 tracing::error!("[{window_id}] failed to start edit: {e}");
             }
@@ -944,16 +1714,34 @@ impl ServiceModel {
         }
     }
 
-    fn on_finish_edit(&mut self, window_id: Id) {
-        if let Some(sticky_window) = self.sticky_windows.get_mut(&window_id) {
-            if let Ok(note) = self.notes.try_get_note_mut(&sticky_window.get_note_id()) {
-                match sticky_window.finish_edit() {
-                    Ok(text) => note.set_content(text),
-                    Err(e) => tracing::error!("[{window_id}] failed to finish edit: {e}"),
-                }
-            }
-        } else {
+    fn on_finish_edit(&mut self, window_id: Id) -> Task<cosmic::Action<Message>> {
+        let Some(sticky_window) = self.sticky_windows.get_mut(&window_id) else {
             tracing::error!("[{window_id}] failed to finish edit: sticky window is not found");
+            return Task::none();
+        };
+        let note_id = sticky_window.get_note_id();
+        let (text, title) = match sticky_window.finish_edit() {
+            Ok(edited) => edited,
+            Err(e) => {
+                tracing::error!("[{window_id}] failed to finish edit: {e}");
+                return Task::none();
+            }
+        };
+        let title_chars = self.service.config.title_chars;
+        let limits = self.service.config.get_history_limits();
+        match self.service.notes.try_get_note_mut(&note_id) {
+            Ok(note) => {
+                note.set_edited_content(text, limits);
+                note.set_title(Some(title));
+                note.set_draft(None);
+                // the window title follows both the explicit title and the first line
+                let title = note.get_title(title_chars).to_string();
+                self.set_window_title(title, window_id)
+            }
+            Err(e) => {
+                tracing::error!("[{window_id}] failed to finish edit: {e}");
+                Task::none()
+            }
         }
     }
 
@@ -961,19 +1749,40 @@ impl ServiceModel {
         if let Some(sticky_window) = self.sticky_windows.get_mut(&window_id) {
             sticky_window.disable_select_style();
             if let Err(e) = self
+                .service
                 .notes
                 .try_set_note_style_by_index(sticky_window.get_note_id(), style_index)
             {
                 tracing::error!("[{window_id}] Failed select style: {e}");
+                self.service
+                    .notify(Category::StyleFailed, fl!("notify-style-failed"), e);
             }
         } else {
             tracing::error!("[{window_id}] sticky window is not found to change style");
         }
     }
 
+    fn on_code_language_selected(&mut self, window_id: Id, index: usize) {
+        let language = index
+            .checked_sub(1)
+            .and_then(|index| Language::ALL.get(index).copied());
+        if let Some(sticky_window) = self.sticky_windows.get(&window_id) {
+            match self
+                .service
+                .notes
+                .try_get_note_mut(&sticky_window.get_note_id())
+            {
+                Ok(note) => note.set_code_language(language),
+                Err(e) => tracing::error!("[{window_id}] Failed to set code language: {e}"),
+            }
+        } else {
+            tracing::error!("[{window_id}] sticky window is not found to set code language");
+        }
+    }
+
     fn on_delete_note(&mut self, id: Id) -> Task<cosmic::Action<Message>> {
         if let Some(sticky_window) = self.sticky_windows.remove(&id) {
-            self.notes.delete_note(sticky_window.get_note_id());
+            self.service.notes.delete_note(sticky_window.get_note_id());
             window::close(id)
         } else {
             Task::none()
@@ -984,9 +1793,9 @@ impl ServiceModel {
         let name = format!(
             "{}-{}",
             fl!("new-style-name"),
-            self.notes.get_styles_count()
+            self.service.notes.get_styles_count()
         );
-        let style_id = self.notes.new_style(name);
+        let style_id = self.service.notes.new_style(name);
         // turn off style selectors for each sticky windows
         self.sticky_windows
             .values_mut()
@@ -995,7 +1804,7 @@ impl ServiceModel {
     }
 
     fn on_delete_style(&mut self, style_id: Uuid) {
-        match self.notes.delete_style(style_id) {
+        match self.service.notes.delete_style(style_id) {
             Ok(()) => {
                 // as default style might be changed turn off style selectors in all of the sticky windows
                 self.sticky_windows
@@ -1004,18 +1813,32 @@ impl ServiceModel {
             }
             Err(e) => {
                 tracing::error!("failed to delete style: {e}");
+                self.service
+                    .notify(Category::StyleFailed, fl!("notify-style-failed"), e);
             }
         }
     }
 
-    fn on_style_updated(&mut self, style_id: Uuid, name: &str, font: Font, bgcolor: Color) {
-        match self.notes.try_get_style_mut(&style_id) {
+    fn on_style_updated(
+        &mut self,
+        style_id: Uuid,
+        name: &str,
+        font: Font,
+        bgcolor: Color,
+        is_markdown: bool,
+    ) {
+        match self.service.notes.try_get_style_mut(&style_id) {
             Ok(style) => {
                 style.set_name(name);
                 style.set_font(font);
-                style.set_background_color(bgcolor);
+                style.set_background_color(bgcolor.into());
+                style.set_markdown(is_markdown);
+            }
+            Err(e) => {
+                tracing::error!("failed to update style: {e}");
+                self.service
+                    .notify(Category::StyleFailed, fl!("notify-style-failed"), e);
             }
-            Err(e) => tracing::error!("failed to update style: {e}"),
         }
     }
 
@@ -1060,6 +1883,94 @@ impl ServiceModel {
         Task::none()
     }
 
+    // returns the id of the unlocked note of the window to attach files to
+    fn get_attach_note_id(&mut self, window_id: Id) -> Option<Uuid> {
+        match self.try_get_note_mut(window_id) {
+            Ok(note) if note.is_locked() => {
+                tracing::debug!("locked note is not attached to");
+                None
+            }
+            Ok(_) => self
+                .sticky_windows
+                .get(&window_id)
+                .map(StickyWindow::get_note_id),
+            Err(e) => {
+                tracing::error!("failed attaching files: {e}");
+                None
+            }
+        }
+    }
+
+    // copies files into the attachments directory in background,
+    // then attaches them to the note of the window
+    fn on_attach_files(
+        &mut self,
+        window_id: Id,
+        files: Vec<PathBuf>,
+    ) -> Task<cosmic::Action<Message>> {
+        let Some(note_id) = self.get_attach_note_id(window_id) else {
+            return Task::none();
+        };
+        let attachments_dir = self.service.storage.attachments_dir().to_path_buf();
+        Task::perform(
+            tokio::task::spawn_blocking(move || {
+                files
+                    .iter()
+                    .filter_map(|file| {
+                        try_attach_file(&attachments_dir, file)
+                            .inspect_err(|e| {
+                                tracing::error!("failed attaching {}: {e}", file.display());
+                            })
+                            .ok()
+                    })
+                    .collect::<Vec<_>>()
+            }),
+            move |res| {
+                let attachments = res.unwrap_or_else(|e| {
+                    tracing::error!("failed attaching files: {e}");
+                    Vec::new()
+                });
+                Message::NoteAttached(note_id, attachments).into()
+            },
+        )
+    }
+
+    // stores the pasted image into the attachments directory in background,
+    // then attaches it to the note of the window
+    fn on_attach_image(
+        &mut self,
+        window_id: Id,
+        image: ClipboardImage,
+    ) -> Task<cosmic::Action<Message>> {
+        let Some(note_id) = self.get_attach_note_id(window_id) else {
+            return Task::none();
+        };
+        let attachments_dir = self.service.storage.attachments_dir().to_path_buf();
+        Task::perform(
+            tokio::task::spawn_blocking(move || {
+                try_attach_data(
+                    &attachments_dir,
+                    ClipboardImage::FILE_NAME,
+                    image.get_data(),
+                )
+            }),
+            move |res| {
+                let attachments = match res.map_err(|e| e.to_string()) {
+                    Ok(Ok(attachment)) => vec![attachment],
+                    Ok(Err(e)) => {
+                        tracing::error!("failed attaching image: {e}");
+                        Vec::new()
+                    }
+                    Err(e) => {
+                        tracing::error!("failed attaching image: {e}");
+                        Vec::new()
+                    }
+                };
+                Message::NoteAttached(note_id, attachments).into()
+            },
+        )
+    }
+
     fn on_window_event(
         &mut self,
         id: Id,
@@ -1068,6 +1979,11 @@ impl ServiceModel {
         tracing::trace!("window: {event:?}");
         match event {
             // WindowEvent::Resized(size) => is handled by on_window_resize() override
+            WindowEvent::FileDropped(path) => {
+                if self.sticky_windows.contains_key(&id) {
+                    return self.on_attach_files(id, vec![path.clone()]);
+                }
+            }
             WindowEvent::Moved(point) => {
                 if self.sticky_windows.contains_key(&id) {
                     match self.try_get_note_mut(id) {
@@ -1086,6 +2002,14 @@ impl ServiceModel {
                 {
                     // restore window has closed, forget its id
                     self.restore_window_id = None;
+                } else if let Some((window_id, _)) = &self.search_window
+                    && *window_id == id
+                {
+                    self.search_window = None;
+                } else if let Some((window_id, _)) = &self.history_window
+                    && *window_id == id
+                {
+                    self.history_window = None;
                 } else if let Some(window_id) = self.settings_window_id
                     && window_id == id
                 {
@@ -1112,14 +2036,38 @@ impl ServiceModel {
     fn spawn_sticky_windows(&mut self) -> Vec<Task<cosmic::Action<Message>>> {
         let existing_windows = std::mem::take(&mut self.sticky_windows);
         let mut commands: Vec<_> = existing_windows.into_keys().map(window::close).collect();
-        commands.extend(self.notes.iter_notes_mut().map(|(note_id, note)| {
-            let (_, spawn_window) =
-                Self::spawn_sticky_window(*note_id, note, self.config.sticky_window_minimum());
+        if self.no_windows {
+            tracing::debug!("sticky windows are not opened until notes are shown");
+            return commands;
+        }
+        commands.extend(self.service.notes.iter_notes_mut().map(|(note_id, note)| {
+            let (_, spawn_window) = Self::spawn_sticky_window(
+                *note_id,
+                note,
+                self.service.config.sticky_window_minimum(),
+            );
             spawn_window
         }));
         commands
     }
 
+    fn spawn_note_window(&self, note_id: Uuid) -> Task<cosmic::Action<Message>> {
+        match self.service.notes.try_get_note(&note_id) {
+            Ok(note) => {
+                let (_, task) = Self::spawn_sticky_window(
+                    note_id,
+                    note,
+                    self.service.config.sticky_window_minimum(),
+                );
+                task
+            }
+            Err(e) => {
+                tracing::error!("failed opening sticky window: {e}");
+                Task::none()
+            }
+        }
+    }
+
     fn spawn_sticky_window(
         note_id: Uuid,
         note: &NoteData,
@@ -1142,7 +2090,7 @@ impl ServiceModel {
 
     fn spawn_restore_notes_window(&self) -> Task<cosmic::Action<Message>> {
         let (_id, spawn_window) = window::open(window::Settings {
-            size: self.config.restore_notes_size(),
+            size: self.service.config.restore_notes_size(),
             ..Default::default()
         });
         let task = spawn_window.map(|id| Message::RestoreWindowCreated(id).into());
@@ -1154,6 +2102,89 @@ impl ServiceModel {
         }
     }
 
+    fn spawn_search_window(&mut self) -> Task<cosmic::Action<Message>> {
+        // the search is never closed while typing, so the existing window is just raised
+        if let Some((window_id, _)) = &self.search_window {
+            return window::gain_focus(*window_id);
+        }
+        let (_id, spawn_window) = window::open(window::Settings {
+            size: self.service.config.search_size(),
+            ..Default::default()
+        });
+        spawn_window.map(|id| Message::SearchWindowCreated(id).into())
+    }
+
+    fn spawn_history_window(&mut self, note_id: Uuid) -> Task<cosmic::Action<Message>> {
+        // the only history window shows the note asked last
+        if let Some((window_id, history)) = &mut self.history_window {
+            let window_id = *window_id;
+            *history = HistoryView::new(note_id);
+            return self
+                .set_window_title(self.history_title(note_id), window_id)
+                .chain(window::gain_focus(window_id));
+        }
+        let (_id, spawn_window) = window::open(window::Settings {
+            size: self.service.config.history_size(),
+            ..Default::default()
+        });
+        spawn_window.map(move |id| Message::HistoryWindowCreated(id, note_id).into())
+    }
+
+    fn history_title(&self, note_id: Uuid) -> String {
+        let title = self
+            .service
+            .notes
+            .try_get_note(&note_id)
+            .map(|note| note.get_title(self.service.config.title_chars))
+            .unwrap_or_default();
+        fl!("history-title", title = title)
+    }
+
+    // the revision replaces the current content like any other change which is not an edit
+    fn on_history_rollback(&mut self, index: usize) -> Task<cosmic::Action<Message>> {
+        let Some((_window_id, history)) = &self.history_window else {
+            return Task::none();
+        };
+        let note_id = history.get_note_id();
+        // the finished edit would overwrite the rolled back content
+        if self.is_note_editing(note_id) {
+            tracing::warn!("rollback of note {note_id} is refused while it is edited");
+            return Task::none();
+        }
+        let limits = self.service.config.get_history_limits();
+        let title = match self.service.notes.try_get_note_mut(&note_id) {
+            Ok(note) if note.is_locked() => {
+                tracing::warn!("rollback of note {note_id} is refused while it is locked");
+                return Task::none();
+            }
+            Ok(note) => {
+                if !note.rollback(index, limits) {
+                    tracing::error!("failed rollback of note {note_id}: no revision {index}");
+                    return Task::none();
+                }
+                note.get_title(self.service.config.title_chars).to_string()
+            }
+            Err(e) => {
+                tracing::error!("failed rollback: {e}");
+                return Task::none();
+            }
+        };
+        if let Some((_window_id, history)) = &mut self.history_window {
+            history.select(None);
+        }
+        // the content of the window follows the change signal
+        self.find_sticky_window(note_id)
+            .map_or_else(Task::none, |window_id| {
+                self.set_window_title(title, window_id)
+            })
+    }
+
+    fn is_note_editing(&self, note_id: Uuid) -> bool {
+        self.find_sticky_window(note_id)
+            .and_then(|window_id| self.sticky_windows.get(&window_id))
+            .is_some_and(StickyWindow::is_editing)
+    }
+
     fn spawn_settings_window(&mut self) -> Task<cosmic::Action<Message>> {
         let (_id, spawn_window) = window::open(window::Settings::default());
         let task = spawn_window.map(|id| Message::SettingsWindowCreated(id).into());
@@ -1167,7 +2198,7 @@ impl ServiceModel {
 
     fn spawn_about_window(&mut self) -> Task<cosmic::Action<Message>> {
         let (_id, spawn_window) = window::open(window::Settings {
-            size: self.config.about_size(),
+            size: self.service.config.about_size(),
             ..Default::default()
         });
         let task = spawn_window.map(|id| Message::AboutWindowCreated(id).into());
@@ -1185,7 +2216,7 @@ impl ServiceModel {
         is_new: bool,
     ) -> Task<cosmic::Action<Message>> {
         let (_id, spawn_window) = window::open(window::Settings {
-            size: self.config.edit_style_size(),
+            size: self.service.config.edit_style_size(),
             ..Default::default()
         });
         let task = spawn_window
@@ -1199,6 +2230,8 @@ impl ServiceModel {
     }
 
     fn close_sticky_windows(&mut self) -> Vec<Task<cosmic::Action<Message>>> {
+        // notes being edited are opened in edit mode again
+        self.keep_drafts();
         let existing_windows = std::mem::take(&mut self.sticky_windows);
         existing_windows
             .into_keys()
@@ -1214,6 +2247,12 @@ impl ServiceModel {
         if let Some(settings_id) = self.settings_window_id.take() {
             commands.push(window::close(settings_id));
         }
+        if let Some((search_id, _)) = self.search_window.take() {
+            commands.push(window::close(search_id));
+        }
+        if let Some((history_id, _)) = self.history_window.take() {
+            commands.push(window::close(history_id));
+        }
         if let Some((about_id, _)) = self.about_window.take() {
             commands.push(window::close(about_id));
         }
@@ -0,0 +1,364 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::{
+    Command, NotesStorage,
+    notes_api::{self, ChangeSignal, Request},
+};
+use crate::{
+    config::Config,
+    fl,
+    notes::{ChangeTracker, NotesCollection, NotesFormat, file_attachments_dir},
+    notifications::{Category, Notification},
+};
+use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+// keys of reminder notification actions are followed by the note ID
+const SNOOZE_ACTION: &str = "snooze-reminder:";
+const DISMISS_ACTION: &str = "dismiss-reminder:";
+
+/// What is left to the front-end after [`ServiceCore::on_command`] changed the notes
+#[derive(Debug, PartialEq)]
+pub enum Followup {
+    // the command is handled completely
+    Done,
+    // the service exits, notes are saved by [`ServiceCore::quit`]
+    Quit,
+    // the request is applied the same way as the notes API does,
+    // so the front-end opens or closes windows of notes
+    Request(Request),
+    // the file is read in background by [`ServiceCore::import_notes`]
+    Import(String, NotesFormat),
+    // the notes are written in background by [`ServiceCore::export_notes`]
+    Export(String, NotesFormat),
+    // the collection is replaced or visibility of notes is changed, windows follow notes
+    Windows,
+    // the command is about windows only
+    Windowed,
+}
+
+/// Notes with their config and storage served either with windows or without them.
+/// Commands, saving, import/export, reminders and notifications are handled here,
+/// the front-ends only open and close windows and run background tasks.
+pub struct ServiceCore {
+    pub(super) config: Config,
+    // Config or data file to keep notes in
+    pub(super) storage: NotesStorage,
+    pub(super) notes: NotesCollection,
+    // Changes of notes not signaled to D-Bus clients yet
+    changes: ChangeTracker,
+    // Desktop notifications to show after handling the message or the request
+    notifications: Vec<Notification>,
+}
+
+impl ServiceCore {
+    /// Loads notes from the data file or from the config
+    pub fn new(storage: NotesStorage, config: Config) -> Self {
+        let notes = storage.load_notes_or_default(&config.notes);
+        Self {
+            config,
+            storage,
+            changes: ChangeTracker::new(&notes),
+            notes,
+            notifications: Vec::new(),
+        }
+    }
+
+    pub fn on_command(&mut self, command: &Command) -> Followup {
+        tracing::trace!("handling command {command}");
+        match command {
+            Command::Ignored => Followup::Done,
+
+            Command::Quit => Followup::Quit,
+
+            Command::NewNote(content, style) => {
+                Followup::Request(Request::CreateNote(content.clone(), style.clone()))
+            }
+
+            Command::LoadNotes => {
+                if self.notes.is_unsaved() {
+                    // todo: ask to overwrite unsaved notes
+                    tracing::error!("drop unsaved changes while loading collection");
+                }
+                self.notes = self.storage.load_notes_or_default(&self.config.notes);
+                Followup::Windows
+            }
+
+            Command::SaveNotes => {
+                self.save_notes();
+                Followup::Done
+            }
+
+            Command::ImportNotes(file_args) => {
+                if self.notes.is_unsaved() {
+                    // todo: ask to overwrite unsaved notes
+                    tracing::error!("drop unsaved changes while importing collection");
+                }
+                let (file, format) = self.configured_file(file_args.as_ref());
+                Followup::Import(file, format)
+            }
+
+            Command::ExportNotes(file_args) => {
+                let (file, format) = self.configured_file(file_args.as_ref());
+                Followup::Export(file, format)
+            }
+
+            Command::ShowAllNotes | Command::HideAllNotes => {
+                let on = *command == Command::ShowAllNotes;
+                self.notes.for_each_note_mut(|note| note.set_visibility(on));
+                Followup::Windows
+            }
+
+            Command::LockAll => {
+                self.notes.for_each_note_mut(|note| note.set_locking(true));
+                Followup::Done
+            }
+
+            Command::ShowNote(note_id) | Command::HideNote(note_id) => {
+                let on = matches!(command, Command::ShowNote(_));
+                Followup::Request(Request::SetNoteVisible(*note_id, on))
+            }
+
+            Command::LockNote(note_id) => Followup::Request(Request::SetNoteLocked(*note_id, true)),
+
+            Command::ShowTagged(tag) => {
+                let count = self
+                    .notes
+                    .show_only_tagged(tag, self.config.hashtags_as_tags);
+                tracing::debug!("{count} notes are tagged #{tag}");
+                Followup::Windows
+            }
+
+            Command::Connect
+            | Command::RestoreNotes
+            | Command::SearchNotes
+            | Command::OpenSettings
+            | Command::OpenAbout => Followup::Windowed,
+        }
+    }
+
+    // the import file of the config is used if the command gives no file
+    fn configured_file(&self, file_args: Option<&(String, NotesFormat)>) -> (String, NotesFormat) {
+        file_args
+            .cloned()
+            .unwrap_or_else(|| (self.config.import_file.clone(), NotesFormat::default()))
+    }
+
+    /// Applies the request without windows, failures are logged
+    pub fn apply(&mut self, request: &Request) {
+        if let Err(e) = notes_api::apply(&mut self.notes, &self.config, request) {
+            tracing::error!("failed {request:?}: {e}");
+        }
+    }
+
+    /// Saves notes to the storage, the failure is notified.
+    /// Returns true if notes are saved.
+    pub fn save_notes(&mut self) -> bool {
+        // revisions get older than the limit while the service runs
+        self.notes
+            .prune_revisions(self.config.get_history_limits(), Utc::now());
+        match self.storage.save_notes(&mut self.notes) {
+            Ok(()) => {
+                self.changes.mark_saved();
+                true
+            }
+            Err(e) => {
+                tracing::error!("failed saving notes: {e}");
+                self.notify(Category::SaveFailed, fl!("notify-save-failed"), e);
+                false
+            }
+        }
+    }
+
+    pub fn autosave(&mut self) {
+        tracing::debug!("test if notes are changed");
+        if self.notes.is_unsaved() {
+            tracing::debug!("save notes");
+            self.save_notes();
+        } else {
+            tracing::debug!("no changes in notes to save");
+        }
+    }
+
+    /// Saves changes if any before exit, deleted notes are dropped with their attachments
+    pub fn quit(&mut self) {
+        let is_saved = if !self.notes.is_unsaved() {
+            tracing::info!("notes collection is unchanged, skip saving");
+            true
+        } else if self.save_notes() {
+            tracing::info!("notes collection was saved");
+            true
+        } else {
+            false
+        };
+        // warn if deleted notes were dropped
+        let count_deleted = self.notes.iter_deleted_notes().count();
+        if count_deleted > 0 {
+            //todo: what about saving deleted notes too? Maybe with their TTLs
+            tracing::warn!("completely drop some deleted notes on exit: {count_deleted}");
+        }
+        // the notes saved before might still refer to the files if saving failed
+        if is_saved {
+            self.storage.collect_garbage(&self.notes);
+        }
+    }
+
+    /// Reads notes and their attachments from the file in background
+    ///
+    /// # Errors
+    ///
+    /// Returns the message of the failure to notify about by [`ServiceCore::on_imported`]
+    pub async fn import_notes(
+        import_file: String,
+        format: NotesFormat,
+        attachments_dir: PathBuf,
+    ) -> Result<NotesCollection, String> {
+        if import_file.is_empty() {
+            return Err("No import file is set".to_string());
+        }
+        let file = import_file.clone();
+        let notes = tokio::task::spawn_blocking(move || NotesCollection::try_import(file, format))
+            .await
+            .map_err(|e| e.to_string())?
+            .await
+            .map_err(|e| format!("failed reading notes from {import_file}: {e}"))?;
+        // attached files are imported from the directory next to the file
+        if let Err(e) = notes
+            .try_copy_attachments(
+                &file_attachments_dir(Path::new(&import_file)),
+                &attachments_dir,
+            )
+            .await
+        {
+            tracing::error!("failed importing attachments: {e}");
+        }
+        Ok(notes)
+    }
+
+    /// Writes notes to the file and their attachments next to it in background
+    ///
+    /// # Errors
+    ///
+    /// Returns the message of the failure to notify about by [`ServiceCore::on_exported`]
+    pub async fn export_notes(
+        export_file: String,
+        notes: NotesCollection,
+        format: NotesFormat,
+        attachments_dir: PathBuf,
+    ) -> Result<(), String> {
+        if export_file.is_empty() {
+            return Err("No export file is set".to_string());
+        }
+        let export_dir = file_attachments_dir(Path::new(&export_file));
+        let (file, exported_notes) = (export_file.clone(), notes.clone());
+        tokio::task::spawn_blocking(move || {
+            NotesCollection::try_export(file, exported_notes, format)
+        })
+        .await
+        .map_err(|e| e.to_string())?
+        .await
+        .map_err(|e| format!("failed writing notes to {export_file}: {e}"))?;
+        // attached files are exported next to the file
+        notes
+            .try_copy_attachments(&attachments_dir, &export_dir)
+            .await
+            .map(|_| ())
+            .map_err(|e| {
+                format!(
+                    "failed exporting attachments to {}: {e}",
+                    export_dir.display()
+                )
+            })
+    }
+
+    /// Replaces notes by imported ones, the result is notified.
+    /// Returns true if notes are replaced.
+    pub fn on_imported(&mut self, result: Result<NotesCollection, String>) -> bool {
+        match result {
+            Ok(notes) => {
+                self.notes = notes;
+                self.notify(Category::ImportExportDone, fl!("notify-imported"), "");
+                true
+            }
+            Err(msg) => {
+                tracing::error!("failed loading notes: {msg}");
+                self.notify(Category::LoadFailed, fl!("notify-load-failed"), msg);
+                false
+            }
+        }
+    }
+
+    pub fn on_exported(&mut self, result: Result<(), String>) {
+        match result {
+            Ok(()) => {
+                tracing::debug!("export completed successfully");
+                self.notify(Category::ImportExportDone, fl!("notify-exported"), "");
+            }
+            Err(msg) => {
+                tracing::error!("failed exporting notes: {msg}");
+                self.notify(Category::ExportFailed, fl!("notify-export-failed"), msg);
+            }
+        }
+    }
+
+    /// Rings due reminders and notifies about them, notifications can snooze or dismiss them.
+    /// Returns notes of ringing reminders to show.
+    pub fn ring_reminders(&mut self, now: DateTime<Utc>) -> Vec<Uuid> {
+        let ringing = self.notes.ring_due_reminders(now);
+        for note_id in &ringing {
+            if let Ok(note) = self.notes.try_get_note(note_id)
+                && self.config.is_notification_enabled(Category::Reminder)
+            {
+                tracing::debug!("reminder of note {note_id} is due");
+                let notification = Notification::new(
+                    Category::Reminder,
+                    note.get_title(self.config.title_chars),
+                    note.get_content(),
+                )
+                .with_action(format!("{SNOOZE_ACTION}{note_id}"), fl!("reminder-snooze"))
+                .with_action(
+                    format!("{DISMISS_ACTION}{note_id}"),
+                    fl!("reminder-dismiss"),
+                );
+                self.notifications.push(notification);
+            }
+        }
+        ringing
+    }
+
+    /// Returns the request to apply for the action invoked on the reminder notification,
+    /// actions of other notifications are ignored
+    pub fn on_notification_action(&self, key: &str) -> Option<Request> {
+        let parse_id = |note_id: &str| Uuid::parse_str(note_id).ok();
+        if let Some(note_id) = key.strip_prefix(SNOOZE_ACTION).and_then(parse_id) {
+            Some(Request::SnoozeReminder(
+                note_id,
+                self.config.reminder_snooze_min,
+            ))
+        } else {
+            key.strip_prefix(DISMISS_ACTION)
+                .and_then(parse_id)
+                .map(Request::DismissReminder)
+        }
+    }
+
+    /// The notification is shown after handling the message if its category is turned on
+    pub fn notify(&mut self, category: Category, summary: String, body: impl ToString) {
+        if self.config.is_notification_enabled(category) {
+            self.notifications
+                .push(Notification::new(category, summary, body.to_string()));
+        }
+    }
+
+    /// Takes notifications to show since the previous call
+    pub fn take_notifications(&mut self) -> Vec<Notification> {
+        std::mem::take(&mut self.notifications)
+    }
+
+    /// Signals about changes made since the previous call,
+    /// the loaded or imported collection is signaled as replaced notes
+    pub fn change_signals(&mut self) -> Vec<ChangeSignal> {
+        notes_api::change_signals(&mut self.changes, &self.notes, &self.config)
+    }
+}
@@ -1,5 +1,12 @@
-use super::{service::Message, styles_view::build_styles_list_view};
-use crate::{fl, icons::IconSet, notes::NotesCollection};
+use super::{Command, service::Message, styles_view::build_styles_list_view};
+use crate::{
+    config::Config,
+    fl,
+    icons::IconSet,
+    notes::{
+        MAX_HISTORY_DAYS, MAX_HISTORY_REVISIONS, MAX_TITLE_CHARS, MIN_TITLE_CHARS, NotesCollection,
+    },
+};
 use cosmic::prelude::*;
 use cosmic::{
     iced::{Alignment, Length},
@@ -8,9 +15,10 @@ use cosmic::{
 
 pub fn build_settings_view<'a>(
     notes: &'a NotesCollection,
+    config: &Config,
     icons: &IconSet,
-    icon_size: u16,
 ) -> Element<'a, Message> {
+    let icon_size = config.toolbar_icon_size;
     let styles = notes.get_style_names();
     if styles.is_empty() {
         tracing::error!("no any sticky window style is available");
@@ -21,7 +29,7 @@ pub fn build_settings_view<'a>(
             .into();
     }
     let default_style_index = notes.try_get_default_style_index().ok();
-    widget::column::with_capacity(4)
+    widget::column::with_capacity(10)
         .spacing(cosmic::theme::spacing().space_s)
         .width(Length::Fill)
         .height(Length::Fill)
@@ -40,5 +48,108 @@ pub fn build_settings_view<'a>(
         )
         .push(widget::button::text(fl!("create-new-style")).on_press(Message::StyleNew))
         .push(build_styles_list_view(notes, icons, icon_size))
+        .push(widget::divider::horizontal::light())
+        .push(build_title_chars_view(config))
+        .push(widget::divider::horizontal::light())
+        .push(build_history_view(config))
+        .push(widget::divider::horizontal::light())
+        .push(build_tags_view(notes, config))
+        .into()
+}
+
+// notes without the title are titled by the beginning of the first line
+fn build_title_chars_view<'a>(config: &Config) -> Element<'a, Message> {
+    let title_chars = config.title_chars;
+    widget::row::with_capacity(2)
+        .spacing(cosmic::theme::spacing().space_m)
+        .align_y(Alignment::Center)
+        .push(widget::text(fl!("title-chars")).width(Length::Fill))
+        .push(widget::spin_button::vertical(
+            title_chars.to_string(),
+            // is required if 'a11y' feature is on
+            fl!("title-chars"),
+            title_chars,
+            1,
+            MIN_TITLE_CHARS,
+            MAX_TITLE_CHARS,
+            Message::TitleChars,
+        ))
+        .into()
+}
+
+// previous versions of the content are kept up to the number and the age, 0 is no limit
+// of the age while no versions are kept at all if their number is 0
+fn build_history_view<'a>(config: &Config) -> Element<'a, Message> {
+    let revisions = config.history_revisions;
+    let days = config.history_days;
+    widget::column::with_capacity(3)
+        .spacing(cosmic::theme::spacing().space_s)
+        .push(widget::text(fl!("history-description")))
+        .push(
+            widget::row::with_capacity(2)
+                .spacing(cosmic::theme::spacing().space_m)
+                .align_y(Alignment::Center)
+                .push(widget::text(fl!("history-revisions")).width(Length::Fill))
+                .push(widget::spin_button::vertical(
+                    revisions.to_string(),
+                    fl!("history-revisions"),
+                    revisions,
+                    1,
+                    0,
+                    MAX_HISTORY_REVISIONS,
+                    Message::HistoryRevisions,
+                )),
+        )
+        .push(
+            widget::row::with_capacity(2)
+                .spacing(cosmic::theme::spacing().space_m)
+                .align_y(Alignment::Center)
+                .push(widget::text(fl!("history-days")).width(Length::Fill))
+                .push(widget::spin_button::vertical(
+                    days.to_string(),
+                    fl!("history-days"),
+                    days,
+                    1,
+                    0,
+                    MAX_HISTORY_DAYS,
+                    Message::HistoryDays,
+                )),
+        )
+        .into()
+}
+
+// pressing the tag shows only notes having it
+fn build_tags_view<'a>(notes: &NotesCollection, config: &Config) -> Element<'a, Message> {
+    let spacing = cosmic::theme::spacing();
+    let mut tags: Vec<Element<'a, Message>> = vec![
+        widget::button::text(fl!("show-all"))
+            .on_press(Message::Signal(Command::ShowAllNotes))
+            .into(),
+    ];
+    tags.extend(
+        notes
+            .get_tags(config.hashtags_as_tags)
+            .into_iter()
+            .map(|tag| {
+                widget::button::text(format!("#{tag}"))
+                    .on_press(Message::Signal(Command::ShowTagged(tag)))
+                    .into()
+            }),
+    );
+    widget::column::with_capacity(3)
+        .spacing(spacing.space_s)
+        .push(widget::text(fl!("tags-description")))
+        .push(
+            widget::row::with_capacity(2)
+                .spacing(spacing.space_m)
+                .align_y(Alignment::Center)
+                .push(widget::text(fl!("hashtags-as-tags")).width(Length::Fill))
+                .push(widget::toggler(config.hashtags_as_tags).on_toggle(Message::HashtagsAsTags)),
+        )
+        .push(
+            widget::flex_row(tags)
+                .row_spacing(spacing.space_xxs)
+                .column_spacing(spacing.space_xxs),
+        )
         .into()
 }
@@ -1,21 +1,46 @@
 use super::{
-    PopupVariant, get_popup_item_by_index,
+    PopupVariant,
+    edit_context::{EditContext, FindBar},
+    get_popup_item_by_index,
+    markdown_view::{
+        MarkdownContent, build_code_view, build_markdown_view, build_plain_view, code_theme,
+    },
     service::Message,
-    utils::{cosmic_font, with_background},
+    utils::{background_color, with_background},
 };
 use crate::{
+    config::Config,
     fl,
     icons::IconSet,
-    notes::{NoteStyle, NotesCollection},
+    notes::{
+        Attachment, Draft, FontStyle, Language, NoteStyle, NotesCollection, Reminder, find_links,
+        format_tags, task_progress,
+    },
 };
 use cosmic::prelude::*;
 use cosmic::{
-    iced::{Color, Length, window::Id},
-    widget::{self, text_editor::Action},
+    iced::{
+        Alignment, Color, Length,
+        keyboard::{Key, key::Named},
+        window::Id,
+    },
+    widget::{
+        self,
+        text_editor::{Action, Binding, KeyPress},
+    },
 };
+use std::path::Path;
 use thiserror::Error;
 use uuid::Uuid;
 
+const REMINDER_TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+// the width of image attachments shown in view mode
+const THUMBNAIL_WIDTH: f32 = 96.0;
+// syntaxes of the highlighter for edited notes which are not code of the chosen language:
+// code is fenced in Markdown of monospace notes and the rest is not highlighted
+const MARKDOWN_SYNTAX: &str = "md";
+const PLAIN_SYNTAX: &str = "txt";
+
 #[derive(Debug, Error)]
 pub enum StickyWindowError {
     #[error("already in edit mode")]
@@ -33,11 +58,14 @@ pub struct StickyWindow {
     popup_menu: Option<PopupVariant>,
     // optionally display a toolbar in view mode (in edit mode the toolbar is always visible)
     view_toolbar: bool,
-}
-
-struct EditContext {
-    /// currently edited content
-    content: widget::text_editor::Content,
+    // currently edited tags of the note as they are typed
+    tags_input: Option<String>,
+    // the choice of the code language in edit mode, the first one is not code
+    language_names: Vec<String>,
+    // the content of the note parsed as Markdown, see [`StickyWindow::update_content`]
+    markdown: MarkdownContent,
+    // targets of links in the content to copy them from the toolbar
+    links: Vec<String>,
 }
 
 impl StickyWindow {
@@ -49,6 +77,12 @@ impl StickyWindow {
             icon_size,
             popup_menu,
             view_toolbar: false,
+            tags_input: None,
+            language_names: std::iter::once(fl!("code-language-none"))
+                .chain(Language::ALL.iter().map(ToString::to_string))
+                .collect(),
+            markdown: MarkdownContent::default(),
+            links: Vec::new(),
         }
     }
 
@@ -56,25 +90,65 @@ impl StickyWindow {
         self.note_id
     }
 
+    /// Parses the content of the note once it is shown or changed
+    pub fn update_content(&mut self, content: &str) {
+        self.markdown = MarkdownContent::parse(content);
+        self.links.clear();
+        for (_, target) in find_links(content) {
+            if !self.links.contains(&target) {
+                self.links.push(target);
+            }
+        }
+    }
+
+    pub fn get_links(&self) -> &[String] {
+        &self.links
+    }
+
     pub fn hide_popup_menu(&mut self) {
         self.popup_menu = None;
     }
 
-    pub fn start_edit(&mut self, init_content: &str) -> Result<(), StickyWindowError> {
+    pub fn start_edit(
+        &mut self,
+        init_content: &str,
+        init_title: &str,
+    ) -> Result<(), StickyWindowError> {
         if self.edit_context.is_some() {
             Err(StickyWindowError::AlreadyEditing)
         } else {
-            self.edit_context = Some(EditContext {
-                content: widget::text_editor::Content::with_text(init_content),
-            });
+            self.edit_context = Some(EditContext::new(init_content, init_title));
             Ok(())
         }
     }
 
-    pub fn finish_edit(&mut self) -> Result<String, StickyWindowError> {
+    /// Begins edit with the unfinished one kept by the note, the cursor is where it was
+    pub fn resume_edit(&mut self, draft: &Draft) -> Result<(), StickyWindowError> {
+        self.start_edit(draft.get_content(), draft.get_title())?;
+        if let Some(context) = &mut self.edit_context {
+            let (line, column) = draft.get_cursor();
+            context.move_to(widget::text_editor::Position { line, column });
+        }
+        Ok(())
+    }
+
+    /// Returns the edited content, title and cursor while editing
+    pub fn get_draft(&self) -> Option<Draft> {
+        self.edit_context.as_ref().map(|context| {
+            let position = context.cursor().position;
+            Draft::new(
+                context.text(),
+                context.get_title().to_string(),
+                (position.line, position.column),
+            )
+        })
+    }
+
+    /// Returns the edited content and title
+    pub fn finish_edit(&mut self) -> Result<(String, String), StickyWindowError> {
         self.edit_context
             .take()
-            .map(|context| context.content.text())
+            .map(|context| (context.text(), context.get_title().to_string()))
             .ok_or(StickyWindowError::EditingIsOff)
     }
 
@@ -85,10 +159,23 @@ impl StickyWindow {
     pub fn do_edit_action(&mut self, action: Action) -> Result<(), StickyWindowError> {
         self.edit_context
             .as_mut()
-            .map(|context| context.content.perform(action))
+            .map(|context| context.perform(action))
             .ok_or(StickyWindowError::EditingIsOff)
     }
 
+    /// Gives access to the undo history and the find bar of the edit
+    pub fn edit_context_mut(&mut self) -> Result<&mut EditContext, StickyWindowError> {
+        self.edit_context
+            .as_mut()
+            .ok_or(StickyWindowError::EditingIsOff)
+    }
+
+    pub fn update_title_input(&mut self, input: String) {
+        if let Some(context) = &mut self.edit_context {
+            context.set_title(input);
+        }
+    }
+
     pub fn allow_select_style(&mut self, style_names: Vec<String>) {
         self.style_names = Some(style_names);
     }
@@ -97,6 +184,25 @@ impl StickyWindow {
         self.style_names = None;
     }
 
+    /// Opens the input of tags filled with `tags` or closes it if it is open
+    pub fn toggle_edit_tags(&mut self, tags: &[String]) {
+        self.tags_input = match self.tags_input {
+            Some(_) => None,
+            None => Some(format_tags(tags)),
+        };
+    }
+
+    pub fn update_tags_input(&mut self, input: String) {
+        if let Some(tags_input) = &mut self.tags_input {
+            *tags_input = input;
+        }
+    }
+
+    /// Closes the input of tags, returns the typed text
+    pub fn finish_edit_tags(&mut self) -> Option<String> {
+        self.tags_input.take()
+    }
+
     // true - toolbar is visible
     // false - toolbar is hidden
     pub fn set_toolbar_visibility(&mut self, is_visible: bool) {
@@ -108,45 +214,90 @@ impl StickyWindow {
         &'a self,
         window_id: Id,
         notes: &'a NotesCollection,
+        config: &Config,
         icons: &IconSet,
+        attachments_dir: &Path,
     ) -> Element<'a, Message> {
         if let Some(edit_context) = &self.edit_context {
-            let bgcolor = notes
-                .try_get_note_style(self.get_note_id())
-                .map_or(Color::WHITE, NoteStyle::get_background_color);
+            let style = notes.try_get_note_style(self.get_note_id()).ok();
+            let bgcolor = style.map_or(Color::WHITE, background_color);
+            let is_code = style.is_some_and(is_code_style);
+            let code_language = notes
+                .try_get_note(&self.note_id)
+                .ok()
+                .and_then(|note| note.get_code_language())
+                .filter(|_| is_code);
+            let syntax = match (code_language, style) {
+                (Some(language), _) => language.get_syntax(),
+                (None, Some(style)) if is_code && style.is_markdown() => MARKDOWN_SYNTAX,
+                _ => PLAIN_SYNTAX,
+            };
 
-            let note_toolbar = widget::row::with_capacity(1).push(
-                icons
-                    .checked()
-                    .apply(widget::button::icon)
-                    .icon_size(self.icon_size)
-                    .on_press(Message::NoteEdit(window_id, false))
-                    .width(Length::Shrink),
-            );
+            let mut note_toolbar = widget::row::with_capacity(3)
+                .spacing(cosmic::theme::spacing().space_s)
+                .align_y(Alignment::Center)
+                .push(
+                    icons
+                        .checked()
+                        .apply(widget::button::icon)
+                        .icon_size(self.icon_size)
+                        .on_press(Message::NoteEdit(window_id, false))
+                        .width(Length::Shrink),
+                )
+                .push(widget::horizontal_space().width(Length::Fill));
+            // only notes of the monospace font are code
+            if is_code {
+                let language_index = code_language
+                    .and_then(|language| Language::ALL.iter().position(|item| *item == language))
+                    .map_or(0, |index| index + 1);
+                note_toolbar = note_toolbar.push(widget::dropdown(
+                    &self.language_names,
+                    Some(language_index),
+                    move |index| Message::NoteCodeLanguage(window_id, index),
+                ));
+            }
+
+            let title_input =
+                widget::text_input(fl!("title-placeholder"), edit_context.get_title())
+                    .on_input(move |input| Message::NoteTitleInput(window_id, input));
 
             let note_content = widget::container(
-                widget::text_editor(&edit_context.content)
+                widget::text_editor(edit_context.content())
+                    .highlight(syntax, code_theme(bgcolor))
+                    .font(if is_code {
+                        cosmic::font::mono()
+                    } else {
+                        cosmic::font::default()
+                    })
                     .on_action(move |act| Message::Edit(window_id, act))
+                    .key_binding(move |key_press| indent_binding(window_id, key_press))
                     .height(Length::Fill),
             )
             .width(Length::Fill)
             .height(Length::Fill);
 
-            with_background(
-                widget::column::with_capacity(2)
-                    .push(note_toolbar)
-                    .push(note_content)
-                    .into(),
-                bgcolor,
-            )
+            let mut column = widget::column::with_capacity(4)
+                .push(note_toolbar)
+                .push(title_input);
+            if let Some(find) = edit_context.find_bar() {
+                column = column.push(build_find_bar(
+                    window_id,
+                    find,
+                    edit_context.count_matches(),
+                ));
+            }
+            with_background(column.push(note_content).into(), bgcolor)
         } else if let Ok(note) = notes.try_get_note(&self.note_id)
             && let Ok(style) = notes.try_get_style(&note.style())
         {
             let is_locked = note.is_locked();
+            let (done_tasks, all_tasks) = task_progress(note.get_content());
+            let has_revisions = !note.get_revisions().is_empty();
 
             let note_toolbar = if self.view_toolbar {
-                let mut toolbar =
-                    widget::row::with_capacity(8).spacing(cosmic::theme::spacing().space_s);
+                let mut toolbar = widget::row::with_capacity(12)
+                    .spacing(cosmic::theme::spacing().space_s)
+                    .align_y(Alignment::Center);
                 // display menu variant optionally:
                 if let Some(menu) = &self.popup_menu {
                     if let PopupVariant::DropdownMenu(popup_list) = menu {
@@ -209,6 +360,24 @@ impl StickyWindow {
                                 .width(Length::Shrink),
                         );
                     }
+                    toolbar = toolbar.push(
+                        icons
+                            .tag()
+                            .apply(widget::button::icon)
+                            .icon_size(self.icon_size)
+                            .on_press(Message::NoteTags(window_id))
+                            .width(Length::Shrink),
+                    );
+                    if has_revisions {
+                        toolbar = toolbar.push(
+                            icons
+                                .history()
+                                .apply(widget::button::icon)
+                                .icon_size(self.icon_size)
+                                .on_press(Message::NoteHistory(window_id))
+                                .width(Length::Shrink),
+                        );
+                    }
                     toolbar = toolbar.push(
                         icons
                             .delete()
@@ -218,6 +387,29 @@ impl StickyWindow {
                             .width(Length::Shrink),
                     );
                 }
+                if !self.links.is_empty() {
+                    toolbar = toolbar.push(
+                        widget::dropdown(&self.links, None, move |index| {
+                            Message::NoteCopyLink(window_id, index)
+                        })
+                        .placeholder(fl!("copy-link")),
+                    );
+                }
+                if all_tasks > 0 {
+                    toolbar =
+                        toolbar.push(widget::text::caption(format!("{done_tasks}/{all_tasks}")));
+                    if !is_locked && done_tasks > 0 {
+                        toolbar = toolbar
+                            .push(
+                                widget::button::text(fl!("tasks-done-down"))
+                                    .on_press(Message::NoteTasksDown(window_id)),
+                            )
+                            .push(
+                                widget::button::text(fl!("tasks-clear-done"))
+                                    .on_press(Message::NoteTasksClear(window_id)),
+                            );
+                    }
+                }
                 toolbar = toolbar
                     .push(widget::horizontal_space().width(Length::Fill))
                     .push(
@@ -233,21 +425,76 @@ impl StickyWindow {
                 widget::row()
             };
 
-            let note_content = widget::column::with_capacity(2)
+            let mut note_content = widget::column::with_capacity(6)
                 .width(Length::Fill)
-                .height(Length::Fill)
-                .push(
-                    widget::text(note.get_content())
-                        .font(cosmic_font(style.get_font().style))
-                        .size(style.get_font().size),
+                .height(Length::Fill);
+            // notes titled by the first line show it in the content anyway
+            if let Some(title) = note.get_explicit_title() {
+                note_content = note_content.push(widget::text::heading(title).width(Length::Fill));
+            }
+            if let Some(input) = &self.tags_input
+                && !is_locked
+            {
+                note_content = note_content.push(
+                    widget::text_input(fl!("tags-placeholder"), input)
+                        .on_input(move |input| Message::NoteTagsInput(window_id, input))
+                        .on_submit(move |_| Message::NoteTagsSubmit(window_id)),
                 );
+            }
+            // the ringing reminder is highlighted until it is snoozed or dismissed
+            if let Some(reminder) = note.get_reminder()
+                && reminder.is_ringing()
+            {
+                note_content = note_content.push(build_reminder_banner(window_id, reminder));
+            }
+            let code_language = note.get_code_language().filter(|_| is_code_style(style));
+            note_content = note_content.push(if let Some(language) = code_language {
+                build_code_view(
+                    note.get_content(),
+                    language.get_syntax(),
+                    code_theme(background_color(style)),
+                    f32::from(style.get_font().size),
+                )
+            } else if style.is_markdown() {
+                // tasks of the locked note are not toggled
+                let on_task =
+                    (!is_locked).then_some(move |line| Message::NoteTaskToggle(window_id, line));
+                build_markdown_view(&self.markdown, style, on_task)
+            } else {
+                build_plain_view(note.get_content(), style)
+            });
+            if !note.get_attachments().is_empty() {
+                // attachments are removed with the toolbar shown like other changes of the note
+                let is_removable = self.view_toolbar && !is_locked;
+                note_content = note_content.push(
+                    widget::flex_row(
+                        note.get_attachments()
+                            .iter()
+                            .map(|attachment| {
+                                build_attachment(
+                                    window_id,
+                                    attachment,
+                                    attachments_dir,
+                                    is_removable.then(|| icons.delete()),
+                                )
+                            })
+                            .collect(),
+                    )
+                    .row_spacing(cosmic::theme::spacing().space_xxs)
+                    .column_spacing(cosmic::theme::spacing().space_xxs),
+                );
+            }
+            let tags = note.get_all_tags(config.hashtags_as_tags);
+            if !tags.is_empty() {
+                note_content = note_content.push(widget::text::caption(format_tags(&tags)));
+            }
 
             with_background(
                 widget::column::with_capacity(2)
                     .push(note_toolbar)
                     .push(note_content)
                     .into(),
-                style.get_background_color(),
+                background_color(style),
             )
         } else {
             // build problem view
@@ -255,3 +502,142 @@ impl StickyWindow {
         }
     }
 }
+
+// code is highlighted only in notes of the monospace font
+fn is_code_style(style: &NoteStyle) -> bool {
+    style.get_font().style == FontStyle::Monospace
+}
+
+// images are thumbnails and other files are buttons with names, both open the file on click
+fn build_attachment<'a>(
+    window_id: Id,
+    attachment: &Attachment,
+    attachments_dir: &Path,
+    remove_icon: Option<widget::icon::Handle>,
+) -> Element<'a, Message> {
+    let path = attachments_dir.join(attachment.get_file());
+    let open = Message::OpenUrl(path.to_string_lossy().to_string());
+    let item: Element<'a, Message> = if attachment.is_image() {
+        widget::button::custom(
+            widget::image(widget::image::Handle::from_path(path))
+                .width(Length::Fixed(THUMBNAIL_WIDTH)),
+        )
+        .class(cosmic::theme::Button::Image)
+        .on_press(open)
+        .into()
+    } else {
+        widget::button::text(attachment.get_name().to_string())
+            .on_press(open)
+            .into()
+    };
+    match remove_icon {
+        Some(icon) => widget::row::with_capacity(2)
+            .align_y(Alignment::Center)
+            .push(item)
+            .push(
+                widget::button::icon(icon)
+                    .on_press(Message::NoteAttachmentRemove(
+                        window_id,
+                        attachment.get_file().to_string(),
+                    ))
+                    .width(Length::Shrink),
+            )
+            .into(),
+        None => item,
+    }
+}
+
+fn build_reminder_banner<'a>(window_id: Id, reminder: &Reminder) -> Element<'a, Message> {
+    let due = reminder.get_due().format(REMINDER_TIME_FORMAT).to_string();
+    widget::container(
+        widget::row::with_capacity(3)
+            .spacing(cosmic::theme::spacing().space_xs)
+            .align_y(Alignment::Center)
+            .push(widget::text::body(fl!("reminder-due", time = due)).width(Length::Fill))
+            .push(
+                widget::button::text(fl!("reminder-snooze"))
+                    .on_press(Message::NoteSnooze(window_id)),
+            )
+            .push(
+                widget::button::text(fl!("reminder-dismiss"))
+                    .on_press(Message::NoteDismiss(window_id)),
+            ),
+    )
+    .class(cosmic::style::Container::Card)
+    .padding(cosmic::theme::spacing().space_xxs)
+    .width(Length::Fill)
+    .into()
+}
+
+// Tab indents lines and Shift+Tab outdents them, the editor keeps the focus then
+fn indent_binding(window_id: Id, key_press: KeyPress) -> Option<Binding<Message>> {
+    if key_press.key == Key::Named(Named::Tab) && !key_press.modifiers.control() {
+        Some(Binding::Custom(Message::EditIndent(
+            window_id,
+            !key_press.modifiers.shift(),
+        )))
+    } else {
+        Binding::from_key_press(key_press)
+    }
+}
+
+// the query with the number of matches and the replacement when replacing
+fn build_find_bar<'a>(window_id: Id, find: &'a FindBar, matches: usize) -> Element<'a, Message> {
+    let spacing = cosmic::theme::spacing();
+    let found = match find.get_current() {
+        Some(current) if !find.get_query().is_empty() => format!("{}/{matches}", current + 1),
+        _ => matches.to_string(),
+    };
+    let mut bar = widget::column::with_capacity(2)
+        .spacing(spacing.space_xxs)
+        .push(
+            widget::row::with_capacity(6)
+                .spacing(spacing.space_xxs)
+                .align_y(Alignment::Center)
+                .push(
+                    widget::text_input(fl!("find-placeholder"), find.get_query())
+                        .id(find.input_id())
+                        .on_input(move |query| Message::FindInput(window_id, query))
+                        .on_submit(move |_| Message::FindNext(window_id, true))
+                        .width(Length::Fill),
+                )
+                .push(widget::text::caption(found))
+                .push(
+                    widget::checkbox(fl!("find-match-case"), find.is_match_case())
+                        .on_toggle(move |on| Message::FindMatchCase(window_id, on)),
+                )
+                .push(
+                    widget::button::text(fl!("find-previous"))
+                        .on_press(Message::FindNext(window_id, false)),
+                )
+                .push(
+                    widget::button::text(fl!("find-next"))
+                        .on_press(Message::FindNext(window_id, true)),
+                )
+                .push(
+                    widget::button::text(fl!("find-close")).on_press(Message::FindClose(window_id)),
+                ),
+        );
+    if find.is_replace() {
+        bar = bar.push(
+            widget::row::with_capacity(3)
+                .spacing(spacing.space_xxs)
+                .align_y(Alignment::Center)
+                .push(
+                    widget::text_input(fl!("replace-placeholder"), find.get_replacement())
+                        .on_input(move |replacement| Message::ReplaceInput(window_id, replacement))
+                        .on_submit(move |_| Message::ReplaceNext(window_id))
+                        .width(Length::Fill),
+                )
+                .push(
+                    widget::button::text(fl!("replace-next"))
+                        .on_press(Message::ReplaceNext(window_id)),
+                )
+                .push(
+                    widget::button::text(fl!("replace-all"))
+                        .on_press(Message::ReplaceAll(window_id)),
+                ),
+        );
+    }
+    bar.into()
+}
@@ -1,6 +1,6 @@
 use super::{
     service::Message,
-    utils::{cosmic_font, with_background},
+    utils::{background_color, cosmic_font, with_background},
 };
 use crate::{
     fl,
@@ -76,5 +76,5 @@ fn build_style_list_item<'a>(
                 .width(Length::Shrink),
         )
         .into();
-    with_background(child, style.get_background_color())
+    with_background(child, background_color(style))
 }
@@ -1,5 +1,4 @@
-use super::service::Message;
-use crate::notes::FontStyle;
+use crate::notes::{FontStyle, NoteStyle};
 use cosmic::prelude::*;
 use cosmic::{
     font::{self, Font},
@@ -27,7 +26,7 @@ pub const fn text_color() -> Srgba {
     Srgba::new(0.08, 0.08, 0.08, 1.0)
 }
 
-pub fn with_background(child: Element<'_, Message>, bgcolor: Color) -> Element<'_, Message> {
+pub fn with_background<M: 'static>(child: Element<'_, M>, bgcolor: Color) -> Element<'_, M> {
     widget::container(child)
         .class(cosmic::style::Container::custom(move |theme: &Theme| {
             let cosmic = theme.cosmic();
@@ -46,6 +45,11 @@ pub fn with_background(child: Element<'_, Message>, bgcolor: Color) -> Element<'
         .into()
 }
 
+/// Converts the note style background into the color to draw with
+pub fn background_color(style: &NoteStyle) -> Color {
+    Color::from(style.get_background_color())
+}
+
 pub fn cosmic_font(font_style: FontStyle) -> Font {
     match font_style {
         FontStyle::Default => font::default(),
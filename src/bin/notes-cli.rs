@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use std::process::ExitCode;
+use sticky_notes::cli;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    // stdout is reserved for the command output
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_max_level(tracing::Level::WARN)
+        .init();
+
+    match cli::run(std::env::args().skip(1).collect()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("notes-cli: {e:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
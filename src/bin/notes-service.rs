@@ -1,12 +1,36 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use sticky_notes::{
-    app::{ServiceFlags, ServiceModel},
+    app::{SERVICE_USAGE, ServiceFlags, ServiceModel, run_headless},
     i18n,
 };
 
 fn main() -> cosmic::iced::Result {
-    tracing_subscriber::fmt::init();
+    let flags = match ServiceFlags::try_parse(std::env::args().skip(1)) {
+        Ok(flags) => flags,
+        Err(e) => {
+            eprintln!("{e}\n\n{SERVICE_USAGE}");
+            std::process::exit(2);
+        }
+    };
+    if flags.help {
+        println!("{SERVICE_USAGE}");
+        return Ok(());
+    }
+    if let Some(level) = flags.log_level {
+        tracing_subscriber::fmt().with_max_level(level).init();
+    } else {
+        tracing_subscriber::fmt::init();
+    }
+
+    // No display is required to run without windows
+    if flags.headless {
+        if let Err(e) = run_headless(flags) {
+            eprintln!("{e:#}");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
 
     // Get the system's preferred languages.
     let requested_languages = i18n_embed::DesktopLanguageRequester::requested_languages();
@@ -25,6 +49,6 @@ fn main() -> cosmic::iced::Result {
         .exit_on_close(false)
         .no_main_window(true);
 
-    // Starts the application's event loop with `()` as the application's flags.
-    cosmic::app::run_single_instance::<ServiceModel>(settings, ServiceFlags)
+    // Starts the application's event loop, the running instance gets --new or --import forwarded.
+    cosmic::app::run_single_instance::<ServiceModel>(settings, flags)
 }
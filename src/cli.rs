@@ -0,0 +1,666 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Command line client to script notes. It talks to running notes-service over D-Bus,
+//! otherwise it operates on the notes collection stored in the application config
+//! or in the data file.
+
+use crate::{
+    dbus::{BUS_NAME, NoteInfo, NotesProxy},
+    notes::{NoteStyle, NotesCollection, NotesFormat, Reminder, file_attachments_dir, parse_tags},
+    service::{Command, NotesStorage, ServiceFlags, send_command},
+};
+use anyhow::{Context, anyhow, bail};
+use chrono::Utc;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncReadExt;
+use uuid::Uuid;
+
+const USAGE: &str = "\
+Usage: notes-cli [OPTIONS] <COMMAND> [ARGS]
+
+Notes are selected by UUID, by title or by the first line of the note.
+If TEXT is omitted or is '-' it is read from stdin.
+
+Options select notes the same way as notes-service does, the running service is used
+only if it keeps notes in the same place:
+  --data-dir <dir>          notes kept in <dir>/notes.json instead of the config
+  --config-profile <name>   notes kept in the separate config named by <name>
+
+Commands:
+  list                      print UUID, style, flags (l - locked, h - hidden) and title of every note
+  print <NOTE>              print the content of the note
+  new [TEXT] [--style STYLE] create a new note, print its UUID
+  append <NOTE> [TEXT]      append the text to the note as a new line
+  style <NOTE> <STYLE>      set the note style by UUID or by name
+  lock <NOTE>               lock the note
+  unlock <NOTE>             unlock the note
+  hide <NOTE>               hide the note
+  show <NOTE>               show the note
+  delete <NOTE>             delete the note
+  remind <NOTE> <TIME|off> [--repeat REPEAT]
+                            remind of the note at TIME (HH:MM, 'YYYY-MM-DD HH:MM' or RFC 3339),
+                            REPEAT is once (default), daily, weekly or days like mon,wed,fri
+  dismiss <NOTE>            stop the ringing reminder, the recurring one rings the next time
+  title <NOTE> [TITLE]      set the title of the note, no title titles it by the first line
+  tag <NOTE> [TAG...]       set tags of the note, no tags remove them
+  tags                      print tags of all notes
+  command <COMMAND> [ARGS]  send the command to notes-service (e.g. SAVE, EXPORT <FILE> json, LOCK <UUID>)
+  help                      print this help";
+
+#[derive(Debug, Clone, PartialEq)]
+enum Action {
+    Help,
+    List,
+    Print(String),
+    New(Option<String>, String), // (text, style)
+    Append(String, Option<String>),
+    Style(String, String),
+    Lock(String, bool),
+    Visibility(String, bool),
+    Delete(String),
+    Remind(String, Option<String>, String), // (note, time or None to remove, repeat)
+    Dismiss(String),
+    Title(String, Option<String>), // (note, title or None to title by the first line)
+    Tag(String, Vec<String>),      // (note, normalized tags)
+    Tags,
+    Command(Command),
+}
+
+/// Runs the command given in `args` (without the program name)
+///
+/// # Errors
+///
+/// Will return `Err` if arguments are invalid or the command failed
+pub async fn run(args: Vec<String>) -> anyhow::Result<()> {
+    let (flags, args) = parse_options(args)?;
+    let action = parse_args(args)?;
+    if action == Action::Help {
+        println!("{USAGE}");
+        return Ok(());
+    }
+    let mut backend = Backend::connect(NotesStorage::new(&flags)).await?;
+    match action {
+        Action::Help => {}
+        Action::List => {
+            for note in backend.list_notes().await? {
+                let flags = match (note.is_locked, note.is_visible) {
+                    (true, true) => "l",
+                    (true, false) => "lh",
+                    (false, false) => "h",
+                    (false, true) => "-",
+                };
+                println!("{}\t{}\t{flags}\t{}", note.id, note.style, note.title);
+            }
+        }
+        Action::Print(key) => {
+            let note = backend.find_note(&key).await?;
+            println!("{}", note.content);
+        }
+        Action::New(text, style) => {
+            let content = text_or_stdin(text).await?;
+            println!("{}", backend.create_note(&content, &style).await?);
+        }
+        Action::Append(key, text) => {
+            let note = backend.find_note(&key).await?;
+            let text = text_or_stdin(text).await?;
+            let content = if note.content.is_empty() {
+                text
+            } else {
+                format!("{}\n{text}", note.content)
+            };
+            backend.update_note_content(&note.id, &content).await?;
+        }
+        Action::Style(key, style) => {
+            let note = backend.find_note(&key).await?;
+            backend.set_note_style(&note.id, &style).await?;
+        }
+        Action::Lock(key, on) => {
+            let note = backend.find_note(&key).await?;
+            backend.set_note_locked(&note.id, on).await?;
+        }
+        Action::Visibility(key, on) => {
+            let note = backend.find_note(&key).await?;
+            backend.set_note_visible(&note.id, on).await?;
+        }
+        Action::Delete(key) => {
+            let note = backend.find_note(&key).await?;
+            backend.delete_note(&note.id).await?;
+        }
+        Action::Remind(key, time, repeat) => {
+            let note = backend.find_note(&key).await?;
+            backend
+                .set_note_reminder(&note.id, time.as_deref().unwrap_or_default(), &repeat)
+                .await?;
+        }
+        Action::Dismiss(key) => {
+            let note = backend.find_note(&key).await?;
+            backend.dismiss_note_reminder(&note.id).await?;
+        }
+        Action::Title(key, title) => {
+            let note = backend.find_note(&key).await?;
+            backend
+                .set_note_title(&note.id, title.as_deref().unwrap_or_default())
+                .await?;
+        }
+        Action::Tag(key, tags) => {
+            let note = backend.find_note(&key).await?;
+            backend.set_note_tags(&note.id, tags).await?;
+        }
+        Action::Tags => {
+            for tag in backend.list_tags().await? {
+                println!("{tag}");
+            }
+        }
+        Action::Command(command) => {
+            backend.send_command(command).await?;
+        }
+    }
+    backend.finish()
+}
+
+// options preceding the command select the notes storage
+fn parse_options(args: Vec<String>) -> anyhow::Result<(ServiceFlags, Vec<String>)> {
+    let mut flags = ServiceFlags::default();
+    let mut args = args.into_iter().peekable();
+    while let Some(option) = args.next_if(|arg| arg.starts_with("--") && arg != "--help") {
+        let value = args
+            .next()
+            .ok_or_else(|| anyhow!("{option} requires a value"))?;
+        match option.as_str() {
+            "--data-dir" => flags.data_dir = Some(PathBuf::from(value)),
+            "--config-profile" => flags.config_profile = Some(value),
+            _ => bail!("unknown option {option}, see 'notes-cli help'"),
+        }
+    }
+    Ok((flags, args.collect()))
+}
+
+fn parse_args(args: Vec<String>) -> anyhow::Result<Action> {
+    let mut args = args.into_iter();
+    let Some(command) = args.next() else {
+        return Ok(Action::Help);
+    };
+    let mut required = |what: &str| {
+        args.next()
+            .ok_or_else(|| anyhow!("{command}: {what} is required"))
+    };
+    let action = match command.as_str() {
+        "help" | "--help" | "-h" => Action::Help,
+        "list" => Action::List,
+        "print" => Action::Print(required("note")?),
+        "append" => Action::Append(required("note")?, args.next()),
+        "style" => Action::Style(required("note")?, required("style")?),
+        "lock" => Action::Lock(required("note")?, true),
+        "unlock" => Action::Lock(required("note")?, false),
+        "hide" => Action::Visibility(required("note")?, false),
+        "show" => Action::Visibility(required("note")?, true),
+        "delete" => Action::Delete(required("note")?),
+        "dismiss" => Action::Dismiss(required("note")?),
+        "tags" => Action::Tags,
+        "title" => Action::Title(required("note")?, args.next()),
+        "tag" => {
+            let note = required("note")?;
+            let tags: Vec<String> = args.by_ref().collect();
+            Action::Tag(note, parse_tags(&tags.join(" ")))
+        }
+        "remind" => {
+            let note = required("note")?;
+            let time = required("time")?;
+            let mut repeat = String::new();
+            while let Some(arg) = args.next() {
+                if arg == "--repeat" {
+                    repeat = args
+                        .next()
+                        .ok_or_else(|| anyhow!("remind: --repeat requires a value"))?;
+                } else {
+                    bail!("remind: unexpected argument {arg}");
+                }
+            }
+            let time = (time != "off").then_some(time);
+            Action::Remind(note, time, repeat)
+        }
+        "command" => {
+            let name = required("command name")?.to_uppercase();
+            let command_args: Vec<String> = args.by_ref().collect();
+            Action::Command(Command::try_from_args(&name, &command_args)?.with_absolute_file())
+        }
+        "new" => {
+            let mut text = None;
+            let mut style = String::new();
+            while let Some(arg) = args.next() {
+                if arg == "--style" {
+                    style = args
+                        .next()
+                        .ok_or_else(|| anyhow!("new: --style requires a value"))?;
+                } else if text.is_none() {
+                    text = Some(arg);
+                } else {
+                    bail!("new: unexpected argument {arg}");
+                }
+            }
+            Action::New(text, style)
+        }
+        _ => bail!("unknown command {command}, see 'notes-cli help'"),
+    };
+    if let Some(arg) = args.next() {
+        bail!("{command}: unexpected argument {arg}");
+    }
+    Ok(action)
+}
+
+async fn text_or_stdin(text: Option<String>) -> anyhow::Result<String> {
+    match text {
+        Some(text) if text != "-" => Ok(text),
+        _ => {
+            let mut input = String::new();
+            tokio::io::stdin()
+                .read_to_string(&mut input)
+                .await
+                .context("failed reading stdin")?;
+            // drop the line end added by echo and alike
+            if input.ends_with('\n') {
+                input.pop();
+            }
+            Ok(input)
+        }
+    }
+}
+
+/// Notes are either managed by the running service or by the client itself
+enum Backend {
+    Service(NotesProxy<'static>),
+    Storage(Box<NotesCollection>, NotesStorage),
+}
+
+impl Backend {
+    async fn connect(storage: NotesStorage) -> anyhow::Result<Self> {
+        match zbus::Connection::session().await {
+            Ok(connection) => {
+                let dbus = zbus::fdo::DBusProxy::new(&connection).await?;
+                if dbus.name_has_owner(BUS_NAME.try_into()?).await? {
+                    let proxy = NotesProxy::new(&connection).await?;
+                    let capabilities = proxy.get_capabilities().await?;
+                    if capabilities.storage == storage.location() {
+                        return Ok(Self::Service(proxy));
+                    }
+                    tracing::info!(
+                        "notes-service keeps notes in {}, using notes stored in {}",
+                        capabilities.storage,
+                        storage.location()
+                    );
+                } else {
+                    tracing::info!("notes-service is not running, using stored notes");
+                }
+            }
+            Err(e) => tracing::warn!("session D-Bus is not available, using stored notes: {e}"),
+        }
+        let config = storage.load_config();
+        let notes = storage
+            .try_load_notes(&config.notes)
+            .map_err(|e| anyhow!("failed loading notes: {e}"))?;
+        Ok(Self::Storage(Box::new(notes), storage))
+    }
+
+    // save changes made without the service
+    fn finish(self) -> anyhow::Result<()> {
+        if let Self::Storage(mut notes, storage) = self
+            && notes.is_unsaved()
+        {
+            storage
+                .save_notes(&mut notes)
+                .map_err(|e| anyhow!("failed saving notes: {e}"))?;
+        }
+        Ok(())
+    }
+
+    async fn list_notes(&self) -> anyhow::Result<Vec<NoteInfo>> {
+        match self {
+            Self::Service(proxy) => Ok(proxy.list_notes().await?),
+            Self::Storage(notes, storage) => {
+                let title_chars = storage.load_config().title_chars;
+                Ok(notes
+                    .iter_notes()
+                    .map(|(note_id, note)| {
+                        NoteInfo::new(
+                            *note_id,
+                            note,
+                            note.get_title(title_chars),
+                            notes
+                                .try_get_style(&note.style())
+                                .map_or("", NoteStyle::get_name),
+                        )
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    // search by UUID, then by title or by the whole first line
+    async fn find_note(&self, key: &str) -> anyhow::Result<NoteInfo> {
+        let notes = self.list_notes().await?;
+        if let Ok(note_id) = Uuid::parse_str(key) {
+            let note_id = note_id.to_string();
+            return notes
+                .into_iter()
+                .find(|note| note.id == note_id)
+                .ok_or_else(|| anyhow!("note {key} is not found"));
+        }
+        let mut found = notes.into_iter().filter(|note| {
+            note.title == key || note.content.lines().next().is_some_and(|line| line == key)
+        });
+        match (found.next(), found.next()) {
+            (Some(note), None) => Ok(note),
+            (Some(_), Some(_)) => bail!("several notes are titled {key}, use UUID instead"),
+            (None, _) => bail!("note {key} is not found"),
+        }
+    }
+
+    async fn create_note(&mut self, content: &str, style: &str) -> anyhow::Result<String> {
+        match self {
+            Self::Service(proxy) => Ok(proxy.create_note(content, style).await?),
+            Self::Storage(notes, _) => {
+                let style_id = notes.try_find_style(style)?;
+                let note_id = notes.new_note();
+                let note = notes.try_get_note_mut(&note_id)?;
+                note.set_style(style_id);
+                note.set_content(content.to_string());
+                Ok(note_id.to_string())
+            }
+        }
+    }
+
+    async fn update_note_content(&mut self, id: &str, content: &str) -> anyhow::Result<()> {
+        match self {
+            Self::Service(proxy) => Ok(proxy.update_note_content(id, content).await?),
+            Self::Storage(notes, _) => {
+                notes
+                    .try_get_note_mut(&Uuid::parse_str(id)?)?
+                    .set_content(content.to_string());
+                Ok(())
+            }
+        }
+    }
+
+    async fn set_note_style(&mut self, id: &str, style: &str) -> anyhow::Result<()> {
+        match self {
+            Self::Service(proxy) => Ok(proxy.set_note_style(id, style).await?),
+            Self::Storage(notes, _) => {
+                let style_id = notes.try_find_style(style)?;
+                notes
+                    .try_get_note_mut(&Uuid::parse_str(id)?)?
+                    .set_style(style_id);
+                Ok(())
+            }
+        }
+    }
+
+    async fn set_note_locked(&mut self, id: &str, on: bool) -> anyhow::Result<()> {
+        match self {
+            Self::Service(proxy) => Ok(proxy.set_note_locked(id, on).await?),
+            Self::Storage(notes, _) => {
+                notes
+                    .try_get_note_mut(&Uuid::parse_str(id)?)?
+                    .set_locking(on);
+                Ok(())
+            }
+        }
+    }
+
+    async fn set_note_visible(&mut self, id: &str, on: bool) -> anyhow::Result<()> {
+        match self {
+            Self::Service(proxy) => Ok(proxy.set_note_visible(id, on).await?),
+            Self::Storage(notes, _) => {
+                notes
+                    .try_get_note_mut(&Uuid::parse_str(id)?)?
+                    .set_visibility(on);
+                Ok(())
+            }
+        }
+    }
+
+    async fn delete_note(&mut self, id: &str) -> anyhow::Result<()> {
+        match self {
+            Self::Service(proxy) => Ok(proxy.delete_note(id).await?),
+            Self::Storage(notes, _) => {
+                // there is no place to keep deleted notes without the service
+                notes.delete_note(Uuid::parse_str(id)?);
+                Ok(())
+            }
+        }
+    }
+
+    // the empty time removes the reminder
+    async fn set_note_reminder(
+        &mut self,
+        id: &str,
+        time: &str,
+        repeat: &str,
+    ) -> anyhow::Result<()> {
+        match self {
+            Self::Service(proxy) => Ok(proxy.set_note_reminder(id, time, repeat).await?),
+            Self::Storage(notes, _) => {
+                let reminder = if time.is_empty() {
+                    None
+                } else {
+                    Some(Reminder::parse(time, repeat, Utc::now())?)
+                };
+                notes
+                    .try_get_note_mut(&Uuid::parse_str(id)?)?
+                    .set_reminder(reminder);
+                Ok(())
+            }
+        }
+    }
+
+    async fn set_note_title(&mut self, id: &str, title: &str) -> anyhow::Result<()> {
+        match self {
+            Self::Service(proxy) => Ok(proxy.set_note_title(id, title).await?),
+            Self::Storage(notes, _) => {
+                notes
+                    .try_get_note_mut(&Uuid::parse_str(id)?)?
+                    .set_title(Some(title.to_string()));
+                Ok(())
+            }
+        }
+    }
+
+    async fn set_note_tags(&mut self, id: &str, tags: Vec<String>) -> anyhow::Result<()> {
+        match self {
+            Self::Service(proxy) => Ok(proxy.set_note_tags(id, tags).await?),
+            Self::Storage(notes, _) => {
+                notes
+                    .try_get_note_mut(&Uuid::parse_str(id)?)?
+                    .set_tags(tags);
+                Ok(())
+            }
+        }
+    }
+
+    async fn list_tags(&self) -> anyhow::Result<Vec<String>> {
+        match self {
+            Self::Service(proxy) => Ok(proxy.list_tags().await?),
+            Self::Storage(notes, storage) => {
+                Ok(notes.get_tags(storage.load_config().hashtags_as_tags))
+            }
+        }
+    }
+
+    async fn dismiss_note_reminder(&mut self, id: &str) -> anyhow::Result<()> {
+        match self {
+            Self::Service(proxy) => Ok(proxy.dismiss_note_reminder(id).await?),
+            Self::Storage(notes, _) => {
+                notes
+                    .try_get_note_mut(&Uuid::parse_str(id)?)?
+                    .dismiss_reminder(Utc::now());
+                Ok(())
+            }
+        }
+    }
+
+    async fn send_command(&mut self, command: Command) -> anyhow::Result<()> {
+        // commands on a particular note are the same as the actions
+        match command {
+            Command::NewNote(content, style) => {
+                return self.create_note(&content, &style).await.map(|_| ());
+            }
+            Command::ShowNote(note_id) => {
+                return self.set_note_visible(&note_id.to_string(), true).await;
+            }
+            Command::HideNote(note_id) => {
+                return self.set_note_visible(&note_id.to_string(), false).await;
+            }
+            Command::LockNote(note_id) => {
+                return self.set_note_locked(&note_id.to_string(), true).await;
+            }
+            _ => {}
+        }
+        match self {
+            Self::Service(proxy) => Ok(send_command(proxy, &command).await?),
+            Self::Storage(notes, storage) => match command {
+                Command::Ignored | Command::SaveNotes | Command::LoadNotes => Ok(()),
+                Command::ShowAllNotes | Command::HideAllNotes => {
+                    let on = command == Command::ShowAllNotes;
+                    notes.for_each_note_mut(|note| note.set_visibility(on));
+                    Ok(())
+                }
+                Command::LockAll => {
+                    notes.for_each_note_mut(|note| note.set_locking(true));
+                    Ok(())
+                }
+                Command::ShowTagged(tag) => {
+                    notes.show_only_tagged(&tag, storage.load_config().hashtags_as_tags);
+                    Ok(())
+                }
+                Command::ImportNotes(file_args) => {
+                    let (file, format) = file_args.unwrap_or_else(|| configured_file(storage));
+                    **notes = NotesCollection::try_import(&file, format).await?;
+                    // attached files are kept next to the file
+                    notes
+                        .try_copy_attachments(
+                            &file_attachments_dir(Path::new(&file)),
+                            storage.attachments_dir(),
+                        )
+                        .await?;
+                    Ok(())
+                }
+                Command::ExportNotes(file_args) => {
+                    let (file, format) = file_args.unwrap_or_else(|| configured_file(storage));
+                    NotesCollection::try_export(&file, notes.as_ref().clone(), format).await?;
+                    notes
+                        .try_copy_attachments(
+                            storage.attachments_dir(),
+                            &file_attachments_dir(Path::new(&file)),
+                        )
+                        .await?;
+                    Ok(())
+                }
+                _ => bail!("{command} requires running notes-service"),
+            },
+        }
+    }
+}
+
+fn configured_file(storage: &NotesStorage) -> (String, NotesFormat) {
+    (storage.load_config().import_file, NotesFormat::default())
+}
+
+#[test]
+fn parse_storage_options() {
+    let args = |line: &str| line.split(' ').map(ToString::to_string).collect::<Vec<_>>();
+
+    let (flags, rest) =
+        parse_options(args("--data-dir /tmp/notes --config-profile work list")).expect("valid");
+    assert_eq!(flags.data_dir, Some(PathBuf::from("/tmp/notes")));
+    assert_eq!(flags.config_profile.as_deref(), Some("work"));
+    assert_eq!(rest, ["list"]);
+    // options of commands are not taken
+    let (flags, rest) = parse_options(args("new text --style Green")).expect("valid");
+    assert_eq!(flags, ServiceFlags::default());
+    assert_eq!(rest.len(), 4);
+    assert!(parse_options(args("--data-dir")).is_err());
+    assert!(parse_options(args("--unknown value list")).is_err());
+}
+
+#[test]
+fn parse_command_line() {
+    let args = |line: &str| line.split(' ').map(ToString::to_string).collect::<Vec<_>>();
+
+    assert_eq!(parse_args(Vec::new()).ok(), Some(Action::Help));
+    assert_eq!(parse_args(args("list")).ok(), Some(Action::List));
+    assert_eq!(
+        parse_args(args("new")).ok(),
+        Some(Action::New(None, String::new()))
+    );
+    assert_eq!(
+        parse_args(args("new text --style Green")).ok(),
+        Some(Action::New(Some("text".to_string()), "Green".to_string()))
+    );
+    assert_eq!(
+        parse_args(args("append Todo done")).ok(),
+        Some(Action::Append("Todo".to_string(), Some("done".to_string())))
+    );
+    assert_eq!(
+        parse_args(args("command save")).ok(),
+        Some(Action::Command(Command::SaveNotes))
+    );
+    assert_eq!(
+        parse_args(args("command export notes.json json")).ok(),
+        Some(Action::Command(Command::ExportNotes(Some((
+            std::path::absolute("notes.json")
+                .expect("valid path")
+                .display()
+                .to_string(),
+            NotesFormat::Json
+        )))))
+    );
+    assert_eq!(
+        parse_args(args("remind Todo 09:30 --repeat mon,fri")).ok(),
+        Some(Action::Remind(
+            "Todo".to_string(),
+            Some("09:30".to_string()),
+            "mon,fri".to_string()
+        ))
+    );
+    assert_eq!(
+        parse_args(args("remind Todo off")).ok(),
+        Some(Action::Remind("Todo".to_string(), None, String::new()))
+    );
+    assert_eq!(
+        parse_args(args("tag Todo #Work home,work")).ok(),
+        Some(Action::Tag(
+            "Todo".to_string(),
+            vec!["home".to_string(), "work".to_string()]
+        ))
+    );
+    assert_eq!(
+        parse_args(args("tag Todo")).ok(),
+        Some(Action::Tag("Todo".to_string(), Vec::new()))
+    );
+    assert_eq!(parse_args(args("tags")).ok(), Some(Action::Tags));
+    assert_eq!(
+        parse_args(args("title Todo Groceries")).ok(),
+        Some(Action::Title(
+            "Todo".to_string(),
+            Some("Groceries".to_string())
+        ))
+    );
+    assert_eq!(
+        parse_args(args("title Todo")).ok(),
+        Some(Action::Title("Todo".to_string(), None))
+    );
+    assert_eq!(
+        parse_args(args("command tag work")).ok(),
+        Some(Action::Command(Command::ShowTagged("work".to_string())))
+    );
+    // missing or extra arguments
+    assert!(parse_args(args("remind Todo")).is_err());
+    assert!(parse_args(args("remind Todo 09:30 --repeat")).is_err());
+    assert!(parse_args(args("print")).is_err());
+    assert!(parse_args(args("style Todo")).is_err());
+    assert!(parse_args(args("lock Todo extra")).is_err());
+    assert!(parse_args(args("new one two")).is_err());
+    // unknown commands
+    assert!(parse_args(args("unknown")).is_err());
+    assert!(parse_args(args("command unknown")).is_err());
+    assert!(parse_args(args("command save now")).is_err());
+}
@@ -1,14 +1,21 @@
 // SPDX-License-Identifier: MPL-2.0
-use cosmic::{
-    cosmic_config::{self, CosmicConfigEntry, cosmic_config_derive::CosmicConfigEntry},
-    iced::Size,
-};
+#[cfg(feature = "gui")]
+use cosmic::iced::Size;
+use cosmic_config::{ConfigSet, CosmicConfigEntry, cosmic_config_derive::CosmicConfigEntry};
 
+#[cfg(feature = "gui")]
 use crate::app::to_f32;
+use crate::{
+    notes::{DEF_HISTORY_DAYS, DEF_HISTORY_REVISIONS, DEF_TITLE_CHARS, HistoryLimits},
+    notifications::Category,
+};
 
+pub const APP_ID: &str = "com.github.aae.sticky_notes";
 const DEF_DATA_FILE: &str = ".config/indicator-stickynotes";
 const DEF_SERVICE_BIN: &str = "/usr/bin/notes-service";
 const ICON_SIZE: u16 = 16;
+/// The date and time inserted into the note being edited by Ctrl+Shift+D
+pub const DEF_DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
 
 #[derive(Debug, Clone, CosmicConfigEntry, Eq, PartialEq)]
 #[version = 1]
@@ -18,6 +25,10 @@ pub struct Config {
     pub service_bin: String,
     pub restore_notes_width: usize,
     pub restore_notes_height: usize,
+    pub search_width: usize,
+    pub search_height: usize,
+    pub history_width: usize,
+    pub history_height: usize,
     pub edit_style_width: usize,
     pub edit_style_height: usize,
     pub about_width: usize,
@@ -26,8 +37,33 @@ pub struct Config {
     pub note_min_width: usize,
     pub note_min_height: usize,
     pub connect_service_pause_ms: u64,
+    // The longest pause between attempts to connect the service:
+    pub connect_service_max_pause_ms: u64,
+    // Period of testing the service is alive. If 0 the service is not tested:
+    pub health_check_period_ms: u64,
     // Test and save period. If 0 autosave is off:
     pub autosave_period_ms: u64,
+    // Period of testing reminders are due. If 0 reminders never ring:
+    pub reminder_check_period_ms: u64,
+    // Snoozed reminder rings again in minutes:
+    pub reminder_snooze_min: u32,
+    // The format of the date and time inserted into the note being edited:
+    pub date_format: String,
+    // Desktop notifications are turned on or off by categories:
+    pub notify_load_failed: bool,
+    pub notify_save_failed: bool,
+    pub notify_export_failed: bool,
+    pub notify_style_failed: bool,
+    pub notify_import_export_done: bool,
+    pub notify_reminders: bool,
+    // #hashtags of the content are tags of the note too:
+    pub hashtags_as_tags: bool,
+    // Notes without the title are titled by the number of first characters of the first line:
+    pub title_chars: usize,
+    // Previous versions of the content kept for every note, 0 turns the history off:
+    pub history_revisions: usize,
+    // The age in days of previous versions of the content kept. If 0 the age is no limit:
+    pub history_days: usize,
 }
 
 impl Default for Config {
@@ -44,6 +80,10 @@ impl Default for Config {
             service_bin: DEF_SERVICE_BIN.to_string(),
             restore_notes_width: 480,
             restore_notes_height: 400,
+            search_width: 480,
+            search_height: 560,
+            history_width: 560,
+            history_height: 640,
             edit_style_width: 480,
             edit_style_height: 800,
             about_width: 480,
@@ -52,11 +92,88 @@ impl Default for Config {
             note_min_height: 64,
             toolbar_icon_size: ICON_SIZE,
             connect_service_pause_ms: 1_000,
+            connect_service_max_pause_ms: 30_000,
+            health_check_period_ms: 5_000,
             autosave_period_ms: 30_000,
+            reminder_check_period_ms: 15_000,
+            reminder_snooze_min: 10,
+            date_format: DEF_DATE_FORMAT.to_string(),
+            notify_load_failed: true,
+            notify_save_failed: true,
+            notify_export_failed: true,
+            notify_style_failed: true,
+            notify_import_export_done: false,
+            notify_reminders: true,
+            hashtags_as_tags: true,
+            title_chars: DEF_TITLE_CHARS,
+            history_revisions: DEF_HISTORY_REVISIONS,
+            history_days: DEF_HISTORY_DAYS,
+        }
+    }
+}
+
+/// Returns the name of the config, every profile has its own config
+#[must_use]
+pub fn config_id(profile: Option<&str>) -> String {
+    match profile {
+        Some(profile) if !profile.is_empty() => format!("{APP_ID}.{profile}"),
+        _ => APP_ID.to_string(),
+    }
+}
+
+impl Config {
+    /// Loads the config named `config_id`, the erroneous values are replaced with defaults
+    #[must_use]
+    pub fn load(config_id: &str) -> Self {
+        cosmic_config::Config::new(config_id, Self::VERSION)
+            .map(|context| match Self::get_entry(&context) {
+                Ok(config) => config,
+                Err((errors, config)) => {
+                    for why in errors {
+                        tracing::error!("error loading app config: {why}");
+                    }
+                    config
+                }
+            })
+            .unwrap_or_default()
+    }
+
+    /// Stores serialized notes collection into the application config
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the config is not accessible or not writable
+    pub fn store_notes(config_id: &str, json: String) -> Result<(), cosmic_config::Error> {
+        let global_config = cosmic_config::Config::new(config_id, Self::VERSION)?;
+        let tx = global_config.transaction();
+        tx.set("notes", json)?;
+        tx.commit()
+    }
+
+    /// Returns the limits of previous versions of the content kept for every note
+    #[must_use]
+    pub fn get_history_limits(&self) -> HistoryLimits {
+        HistoryLimits {
+            revisions: self.history_revisions,
+            days: self.history_days,
+        }
+    }
+
+    #[must_use]
+    pub fn is_notification_enabled(&self, category: Category) -> bool {
+        match category {
+            Category::LoadFailed => self.notify_load_failed,
+            Category::SaveFailed => self.notify_save_failed,
+            Category::ExportFailed => self.notify_export_failed,
+            Category::StyleFailed => self.notify_style_failed,
+            Category::ImportExportDone => self.notify_import_export_done,
+            Category::Reminder => self.notify_reminders,
         }
     }
 }
 
+// Sizes of windows are needed only by the GUI
+#[cfg(feature = "gui")]
 impl Config {
     #[must_use]
     pub fn restore_notes_size(&self) -> Size {
@@ -66,6 +183,16 @@ impl Config {
         )
     }
 
+    #[must_use]
+    pub fn search_size(&self) -> Size {
+        Size::new(to_f32(self.search_width), to_f32(self.search_height))
+    }
+
+    #[must_use]
+    pub fn history_size(&self) -> Size {
+        Size::new(to_f32(self.history_width), to_f32(self.history_height))
+    }
+
     #[must_use]
     pub fn edit_style_size(&self) -> Size {
         Size::new(
@@ -0,0 +1,450 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The D-Bus notes API served by notes-service to control notes from other processes.
+
+use crate::notes::{Color, NoteData, NoteStyle};
+use palette::rgb::FromHexError;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use uuid::Uuid;
+use zbus::{proxy, zvariant::Type};
+
+/// The well-known bus name owned by notes-service
+pub const BUS_NAME: &str = "com.github.aae.sticky_notes.Notes";
+/// The object path the notes API is served at
+pub const OBJECT_PATH: &str = "/com/github/aae/sticky_notes/Notes";
+/// The name of the notes API interface
+pub const INTERFACE_NAME: &str = "com.github.aae.sticky_notes.Notes";
+/// The version of the notes API, notes-applet warns if notes-service has another one
+pub const PROTOCOL_VERSION: u32 = 3;
+
+/// The protocol version and commands notes-service supports, notes-applet asks for them
+/// when connected
+#[derive(Serialize, Deserialize, Type, Debug, Clone, PartialEq)]
+pub struct Capabilities {
+    pub protocol_version: u32,
+    pub commands: Vec<String>, // names of supported commands, e.g. SAVE
+    pub storage: String,       // the data file or the config notes are kept in
+}
+
+impl Capabilities {
+    #[must_use]
+    pub fn is_compatible(&self) -> bool {
+        self.protocol_version == PROTOCOL_VERSION
+    }
+
+    #[must_use]
+    pub fn supports(&self, command_name: &str) -> bool {
+        self.commands.iter().any(|name| name == command_name)
+    }
+}
+
+/// The note as it is transferred over D-Bus
+#[derive(Serialize, Deserialize, Type, Debug, Clone, PartialEq)]
+pub struct NoteInfo {
+    pub id: String,
+    pub title: String,
+    pub content: String,
+    pub style: String,    // the name of the note style
+    pub style_id: String, // names of styles might repeat, the ID is unique
+    pub modified: String, // RFC 3339, local time
+    pub is_locked: bool,
+    pub is_visible: bool,
+    pub left: u64,
+    pub top: u64,
+    pub width: u64,
+    pub height: u64,
+}
+
+impl NoteInfo {
+    #[must_use]
+    pub fn new(note_id: Uuid, note: &NoteData, title: &str, style_name: &str) -> Self {
+        Self {
+            id: note_id.to_string(),
+            title: title.to_string(),
+            content: note.get_content().to_string(),
+            style: style_name.to_string(),
+            style_id: note.style().to_string(),
+            modified: note.get_modified().to_rfc3339(),
+            is_locked: note.is_locked(),
+            is_visible: note.is_visible(),
+            left: note.left() as u64,
+            top: note.top() as u64,
+            width: note.width() as u64,
+            height: note.height() as u64,
+        }
+    }
+}
+
+/// The note style as it is transferred over D-Bus
+#[derive(Serialize, Deserialize, Type, Debug, Clone, PartialEq)]
+pub struct StyleInfo {
+    pub id: String,
+    pub name: String,
+    pub font_style: String, // Default, Light, Semibold, Bold or Monospace
+    pub font_size: u16,
+    pub background: String, // #rrggbb
+    pub is_default: bool,
+}
+
+impl StyleInfo {
+    #[must_use]
+    pub fn new(style_id: Uuid, style: &NoteStyle, is_default: bool) -> Self {
+        let font = style.get_font();
+        Self {
+            id: style_id.to_string(),
+            name: style.get_name().to_string(),
+            font_style: font.style.to_string(),
+            font_size: font.size,
+            background: color_to_hex(style.get_background_color()),
+            is_default,
+        }
+    }
+}
+
+/// Formats the color as #rrggbb
+#[must_use]
+pub fn color_to_hex(color: Color) -> String {
+    format!("#{:x}", color.into_format::<u8>())
+}
+
+/// Parses the color formatted as #rrggbb or #rgb
+///
+/// # Errors
+///
+/// Returns error if the text is not a hex color
+pub fn color_from_hex(hex: &str) -> Result<Color, FromHexError> {
+    palette::Srgb::<u8>::from_str(hex).map(palette::Srgb::into_format)
+}
+
+/// Tests if the call failed because nobody owns the notes API name, i.e. notes-service
+/// is not running, rather than because of any other D-Bus failure
+#[must_use]
+pub fn is_service_missing(error: &zbus::Error) -> bool {
+    match error {
+        zbus::Error::FDO(e) => matches!(
+            **e,
+            zbus::fdo::Error::ServiceUnknown(_) | zbus::fdo::Error::NameHasNoOwner(_)
+        ),
+        zbus::Error::MethodError(name, _, _) => matches!(
+            name.as_str(),
+            "org.freedesktop.DBus.Error.ServiceUnknown"
+                | "org.freedesktop.DBus.Error.NameHasNoOwner"
+        ),
+        _ => false,
+    }
+}
+
+/// Asks the bus if anybody owns the notes API name
+///
+/// # Errors
+///
+/// Returns error if the bus can't be asked
+pub async fn is_service_running(connection: &zbus::Connection) -> zbus::Result<bool> {
+    let bus = zbus::fdo::DBusProxy::new(connection).await?;
+    Ok(bus.name_has_owner(BUS_NAME.try_into()?).await?)
+}
+
+/// Pauses between attempts to connect notes-service, every next pause is twice as long
+/// as the previous one until it reaches the maximum
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    initial_ms: u64,
+    max_ms: u64,
+    attempts: u32,
+}
+
+impl Backoff {
+    #[must_use]
+    pub fn new(initial_ms: u64, max_ms: u64) -> Self {
+        Self {
+            initial_ms,
+            max_ms: max_ms.max(initial_ms),
+            attempts: 0,
+        }
+    }
+
+    /// Returns the pause before the next attempt
+    pub fn next_pause_ms(&mut self) -> u64 {
+        let pause = self
+            .initial_ms
+            .saturating_mul(2_u64.saturating_pow(self.attempts))
+            .min(self.max_ms);
+        self.attempts = self.attempts.saturating_add(1);
+        pause
+    }
+
+    /// Tests if pauses don't grow any more, i.e. many attempts have failed
+    #[must_use]
+    pub fn is_exhausted(&self) -> bool {
+        self.attempts > 0
+            && self
+                .initial_ms
+                .saturating_mul(2_u64.saturating_pow(self.attempts - 1))
+                >= self.max_ms
+    }
+
+    /// Starts over after the successful attempt
+    pub fn reset(&mut self) {
+        self.attempts = 0;
+    }
+}
+
+/// Client side of the notes API, `NotesProxy` is generated from it.
+///
+/// Notes are addressed by UUID, styles are addressed either by UUID or by name,
+/// the empty style stands for the default one.
+#[proxy(
+    interface = "com.github.aae.sticky_notes.Notes",
+    default_service = "com.github.aae.sticky_notes.Notes",
+    default_path = "/com/github/aae/sticky_notes/Notes"
+)]
+pub trait Notes {
+    /// Returns the protocol version, supported commands and where notes are kept
+    async fn get_capabilities(&self) -> zbus::Result<Capabilities>;
+
+    /// Returns all live notes
+    async fn list_notes(&self) -> zbus::Result<Vec<NoteInfo>>;
+
+    /// Returns the note
+    async fn get_note(&self, id: &str) -> zbus::Result<NoteInfo>;
+
+    /// Creates a new note, returns its UUID
+    async fn create_note(&self, content: &str, style: &str) -> zbus::Result<String>;
+
+    /// Replaces the content of the note
+    async fn update_note_content(&self, id: &str, content: &str) -> zbus::Result<()>;
+
+    /// Changes the style of the note
+    async fn set_note_style(&self, id: &str, style: &str) -> zbus::Result<()>;
+
+    /// Locks or unlocks the note
+    async fn set_note_locked(&self, id: &str, is_locked: bool) -> zbus::Result<()>;
+
+    /// Shows or hides the note
+    async fn set_note_visible(&self, id: &str, is_visible: bool) -> zbus::Result<()>;
+
+    /// Moves and resizes the note window
+    async fn set_note_geometry(
+        &self,
+        id: &str,
+        left: u64,
+        top: u64,
+        width: u64,
+        height: u64,
+    ) -> zbus::Result<()>;
+
+    /// Sets the reminder of the note, `time` is HH:MM, YYYY-MM-DD HH:MM or RFC 3339 and
+    /// `repeat` is once, daily, weekly or days like mon,fri; the empty time removes the reminder
+    async fn set_note_reminder(&self, id: &str, time: &str, repeat: &str) -> zbus::Result<()>;
+
+    /// Stops the ringing reminder of the note for `minutes`
+    async fn snooze_note_reminder(&self, id: &str, minutes: u32) -> zbus::Result<()>;
+
+    /// Stops the ringing reminder of the note, the recurring one is due the next time
+    async fn dismiss_note_reminder(&self, id: &str) -> zbus::Result<()>;
+
+    /// Sets the title of the note, the empty one titles the note by its first line
+    async fn set_note_title(&self, id: &str, title: &str) -> zbus::Result<()>;
+
+    /// Replaces tags of the note, the empty list removes them
+    async fn set_note_tags(&self, id: &str, tags: Vec<String>) -> zbus::Result<()>;
+
+    /// Returns tags of all notes, sorted
+    async fn list_tags(&self) -> zbus::Result<Vec<String>>;
+
+    /// Moves the note into recently deleted ones
+    async fn delete_note(&self, id: &str) -> zbus::Result<()>;
+
+    /// Brings the recently deleted note back
+    async fn restore_note(&self, id: &str) -> zbus::Result<()>;
+
+    /// Returns all styles
+    async fn list_styles(&self) -> zbus::Result<Vec<StyleInfo>>;
+
+    /// Creates a new style, returns its UUID; `background` is #rrggbb
+    async fn create_style(
+        &self,
+        name: &str,
+        font_style: &str,
+        font_size: u16,
+        background: &str,
+    ) -> zbus::Result<String>;
+
+    /// Tells the service notes-applet provides the main menu now
+    async fn connect_applet(&self) -> zbus::Result<()>;
+
+    /// Saves notes and stops the service
+    async fn quit(&self) -> zbus::Result<()>;
+
+    /// Reloads stored notes dropping unsaved changes
+    async fn load_notes(&self) -> zbus::Result<()>;
+
+    /// Stores notes
+    async fn save_notes(&self) -> zbus::Result<()>;
+
+    /// Replaces notes with imported ones, the empty path and format stand for configured ones
+    async fn import_notes(&self, path: &str, format: &str) -> zbus::Result<()>;
+
+    /// Exports notes, the empty path and format stand for configured ones
+    async fn export_notes(&self, path: &str, format: &str) -> zbus::Result<()>;
+
+    /// Shows or hides all notes
+    async fn set_all_notes_visible(&self, is_visible: bool) -> zbus::Result<()>;
+
+    /// Locks all notes
+    async fn lock_all_notes(&self) -> zbus::Result<()>;
+
+    /// Shows notes having the tag and hides the rest
+    async fn show_tagged_notes(&self, tag: &str) -> zbus::Result<()>;
+
+    /// Opens the window of recently deleted notes
+    async fn open_restore_window(&self) -> zbus::Result<()>;
+
+    /// Opens the window to search notes
+    async fn open_search_window(&self) -> zbus::Result<()>;
+
+    /// Opens the settings window
+    async fn open_settings_window(&self) -> zbus::Result<()>;
+
+    /// Opens the about window
+    async fn open_about_window(&self) -> zbus::Result<()>;
+
+    /// Emitted after the note is created
+    #[zbus(signal)]
+    fn note_created(&self, note: NoteInfo) -> zbus::Result<()>;
+
+    /// Emitted after the content of the note is changed
+    #[zbus(signal)]
+    fn note_content_changed(&self, id: &str, title: &str, content: &str) -> zbus::Result<()>;
+
+    /// Emitted after the note is moved or resized
+    #[zbus(signal)]
+    fn note_geometry_changed(
+        &self,
+        id: &str,
+        left: u64,
+        top: u64,
+        width: u64,
+        height: u64,
+    ) -> zbus::Result<()>;
+
+    /// Emitted after the note is moved into recently deleted ones
+    #[zbus(signal)]
+    fn note_deleted(&self, id: &str) -> zbus::Result<()>;
+
+    /// Emitted after the recently deleted note is brought back
+    #[zbus(signal)]
+    fn note_restored(&self, note: NoteInfo) -> zbus::Result<()>;
+
+    /// Emitted after the style is created or changed, including the change of the default style
+    #[zbus(signal)]
+    fn style_changed(&self, style: StyleInfo) -> zbus::Result<()>;
+
+    /// Emitted after the style is deleted
+    #[zbus(signal)]
+    fn style_deleted(&self, id: &str) -> zbus::Result<()>;
+
+    /// Emitted after all changes are stored
+    #[zbus(signal)]
+    fn collection_saved(&self) -> zbus::Result<()>;
+}
+
+#[test]
+fn color_to_hex_and_back() {
+    let color = color_from_hex("#ff8000").expect("valid color");
+    assert_eq!(color_to_hex(color), "#ff8000");
+    assert_eq!(color_from_hex("#fff").ok(), Some(crate::notes::WHITE));
+    assert!(color_from_hex("orange").is_err());
+}
+
+#[test]
+fn style_info_from_style() {
+    let style_id = Uuid::new_v4();
+    let style = NoteStyle::new(
+        "Green".to_string(),
+        crate::notes::Font {
+            style: crate::notes::FontStyle::Monospace,
+            size: 14,
+        },
+        Color::new(0.0, 1.0, 0.0),
+    );
+    let info = StyleInfo::new(style_id, &style, true);
+    assert_eq!(info.id, style_id.to_string());
+    assert_eq!(info.name, "Green");
+    assert_eq!(
+        crate::notes::FontStyle::from_str(&info.font_style).ok(),
+        Some(crate::notes::FontStyle::Monospace)
+    );
+    assert_eq!(info.font_size, 14);
+    assert_eq!(info.background, "#00ff00");
+    assert!(info.is_default);
+}
+
+#[test]
+fn note_info_refers_style_by_id() {
+    let style_id = Uuid::new_v4();
+    let mut note = NoteData::new(style_id);
+    note.set_content("buy milk".to_string());
+    let info = NoteInfo::new(Uuid::new_v4(), &note, "buy milk", "White");
+    // styles of the same name are told apart by IDs
+    assert_eq!(info.style, "White");
+    assert_eq!(info.style_id, style_id.to_string());
+    assert_eq!(info.content, "buy milk");
+}
+
+#[test]
+fn capabilities_of_service() {
+    let capabilities = Capabilities {
+        protocol_version: PROTOCOL_VERSION,
+        commands: vec!["SAVE".to_string(), "LOAD".to_string()],
+        storage: "com.github.aae.sticky_notes".to_string(),
+    };
+    assert!(capabilities.is_compatible());
+    assert!(capabilities.supports("SAVE"));
+    assert!(!capabilities.supports("ABOUT"));
+    let previous = Capabilities {
+        protocol_version: PROTOCOL_VERSION - 1,
+        ..capabilities
+    };
+    assert!(!previous.is_compatible());
+}
+
+#[test]
+fn missing_service_errors() {
+    let missing = zbus::Error::FDO(Box::new(zbus::fdo::Error::ServiceUnknown(
+        "The name is not activatable".to_string(),
+    )));
+    assert!(is_service_missing(&missing));
+    let no_owner = zbus::Error::FDO(Box::new(zbus::fdo::Error::NameHasNoOwner(
+        BUS_NAME.to_string(),
+    )));
+    assert!(is_service_missing(&no_owner));
+    let unknown_method = zbus::Error::FDO(Box::new(zbus::fdo::Error::UnknownMethod(
+        "GetCapabilities".to_string(),
+    )));
+    assert!(!is_service_missing(&unknown_method));
+    assert!(!is_service_missing(&zbus::Error::Failure(
+        "timeout".to_string()
+    )));
+}
+
+#[test]
+fn backoff_doubles_pauses_up_to_maximum() {
+    let mut backoff = Backoff::new(1_000, 5_000);
+    assert!(!backoff.is_exhausted());
+    assert_eq!(backoff.next_pause_ms(), 1_000);
+    assert_eq!(backoff.next_pause_ms(), 2_000);
+    assert_eq!(backoff.next_pause_ms(), 4_000);
+    assert!(!backoff.is_exhausted());
+    assert_eq!(backoff.next_pause_ms(), 5_000);
+    assert!(backoff.is_exhausted());
+    assert_eq!(backoff.next_pause_ms(), 5_000);
+    backoff.reset();
+    assert!(!backoff.is_exhausted());
+    assert_eq!(backoff.next_pause_ms(), 1_000);
+    // the maximum never is less than the initial pause
+    let mut fixed = Backoff::new(2_000, 0);
+    assert_eq!(fixed.next_pause_ms(), 2_000);
+    assert!(fixed.is_exhausted());
+}
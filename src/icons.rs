@@ -25,6 +25,13 @@ pub mod inner {
         include_bytes!("../resources/icons/mono/scalable/checkbox-checked-symbolic.svg");
     const ICON_MENU: &[u8] =
         include_bytes!("../resources/icons/mono/scalable/open-menu-symbolic.svg");
+    const ICON_CONNECTING: &[u8] =
+        include_bytes!("../resources/icons/mono/scalable/content-loading-symbolic.svg");
+    const ICON_FAILED: &[u8] =
+        include_bytes!("../resources/icons/mono/scalable/dialog-error-symbolic.svg");
+    const ICON_TAG: &[u8] = include_bytes!("../resources/icons/mono/scalable/tag-symbolic.svg");
+    const ICON_HISTORY: &[u8] =
+        include_bytes!("../resources/icons/mono/scalable/document-open-recent-symbolic.svg");
 
     pub struct IconSet {
         pub notes: Handle,
@@ -37,6 +44,10 @@ pub mod inner {
         pub undo: Handle,
         pub checked: Handle,
         pub menu: Handle,
+        pub connecting: Handle,
+        pub failed: Handle,
+        pub tag: Handle,
+        pub history: Handle,
     }
 
     impl Default for IconSet {
@@ -59,6 +70,10 @@ pub mod inner {
                 undo: icon::from_svg_bytes(ICON_UNDO),
                 checked: icon::from_svg_bytes(ICON_CHECKED),
                 menu: icon::from_svg_bytes(ICON_MENU),
+                connecting: icon::from_svg_bytes(ICON_CONNECTING),
+                failed: icon::from_svg_bytes(ICON_FAILED),
+                tag: icon::from_svg_bytes(ICON_TAG),
+                history: icon::from_svg_bytes(ICON_HISTORY),
             }
         }
     }
@@ -78,6 +93,10 @@ mod inner {
     pub const XDG_UNDO: &str = "edit-undo-symbolic";
     pub const XDG_CHECKED: &str = "checkbox-checked-symbolic";
     pub const XDG_MENU: &str = "open-menu-symbolic";
+    pub const XDG_CONNECTING: &str = "content-loading-symbolic";
+    pub const XDG_FAILED: &str = "dialog-error-symbolic";
+    pub const XDG_TAG: &str = "tag-symbolic";
+    pub const XDG_HISTORY: &str = "document-open-recent-symbolic";
 
     pub struct IconSet {
         pub notes: Handle,
@@ -90,6 +109,10 @@ mod inner {
         pub undo: Handle,
         pub checked: Handle,
         pub menu: Handle,
+        pub connecting: Handle,
+        pub failed: Handle,
+        pub tag: Handle,
+        pub history: Handle,
     }
 
     impl Default for IconSet {
@@ -112,6 +135,10 @@ mod inner {
                 undo: icon::from_name(XDG_UNDO).into(),
                 checked: icon::from_name(XDG_CHECKED).into(),
                 menu: icon::from_name(XDG_MENU).into(),
+                connecting: icon::from_name(XDG_CONNECTING).into(),
+                failed: icon::from_name(XDG_FAILED).into(),
+                tag: icon::from_name(XDG_TAG).into(),
+                history: icon::from_name(XDG_HISTORY).into(),
             }
         }
     }
@@ -174,4 +201,20 @@ impl IconSet {
     pub fn menu(&self) -> Handle {
         self.inner.menu.clone()
     }
+
+    pub fn connecting(&self) -> Handle {
+        self.inner.connecting.clone()
+    }
+
+    pub fn failed(&self) -> Handle {
+        self.inner.failed.clone()
+    }
+
+    pub fn tag(&self) -> Handle {
+        self.inner.tag.clone()
+    }
+
+    pub fn history(&self) -> Handle {
+        self.inner.history.clone()
+    }
 }
@@ -1,8 +1,21 @@
 // SPDX-License-Identifier: MPL-2.0
 
+#[cfg(feature = "gui")]
 pub mod app;
+#[cfg(feature = "cli")]
+pub mod cli;
+#[cfg(feature = "cli")]
 pub mod config;
+#[cfg(feature = "model")]
+pub mod dbus;
+#[cfg(feature = "gui")]
 pub mod i18n;
+#[cfg(feature = "gui")]
 pub mod icons;
+#[cfg(feature = "model")]
 #[allow(dead_code)]
 pub mod notes;
+#[cfg(feature = "model")]
+pub mod notifications;
+#[cfg(feature = "cli")]
+pub mod service;
@@ -1,17 +1,61 @@
-pub use collection::NotesCollection;
+pub use attachment::{
+    Attachment, collect_garbage, config_attachments_dir, file_attachments_dir, parse_file_list,
+    try_attach_data, try_attach_file,
+};
+pub use changes::{ChangeTracker, NotesChange};
+pub use checklist::{
+    ChecklistPart, clear_done_tasks, move_done_tasks_down, split_tasks, task_progress, toggle_task,
+};
+pub use code_language::Language;
+pub use collection::{NotesCollection, NotesCollectionError};
+pub use draft::Draft;
+pub use editing::{
+    ListContinuation, continue_list, format_date_stamp, indent_line, outdent_line, toggle_line_task,
+};
+pub use history::{
+    DiffLine, HistoryLimits, Revision, config_history_file, diff_lines, file_history_file,
+};
+pub use links::{find_links, link_target};
 pub use note_data::NoteData;
-pub use note_style::{Font, FontStyle, NoteStyle};
+pub use note_style::{Color, Font, FontStyle, NoteStyle, UnknownFontStyleError, WHITE};
+pub use notes_format::{NotesFormat, UnknownFormatError};
+pub use reminder::{Reminder, ReminderError, Repeat};
+pub use search::{SearchMatch, find_all, replace_all};
+pub use tags::{format_tags, normalize_tag, parse_hashtags, parse_tags};
 
+use changes::bump_generation;
+
+mod attachment;
+mod changes;
+mod checklist;
+mod code_language;
 mod collection;
+mod draft;
+mod editing;
+mod history;
 mod indicator_stickynotes;
+mod links;
 mod note_data;
 mod note_style;
+mod notes_format;
+mod reminder;
+mod search;
+mod tags;
 
 const DEF_NOTE_STYLE_NAME: &str = "White";
 const DEF_NOTE_FONT_SIZE: u16 = 12;
 const EMPTY_TITLE: &str = "<Empty>";
 const NO_TITLE: &str = "Untitled";
 const NO_CONTENT: &str = "click inside to begin edit the content";
-const MAX_TITLE_CHARS: usize = 12;
+// notes without the title are titled by the first characters of the first line
+pub const DEF_TITLE_CHARS: usize = 12;
+pub const MIN_TITLE_CHARS: usize = 4;
+pub const MAX_TITLE_CHARS: usize = 80;
+// previous versions of the content are kept up to the number and the age, 0 is no limit
+// by the age, while no revisions are kept at all if their number is 0
+pub const DEF_HISTORY_REVISIONS: usize = 20;
+pub const MAX_HISTORY_REVISIONS: usize = 100;
+pub const DEF_HISTORY_DAYS: usize = 30;
+pub const MAX_HISTORY_DAYS: usize = 365;
 const DEF_NOTE_WIDTH: usize = 400;
 const DEF_NOTE_HEIGHT: usize = 300;
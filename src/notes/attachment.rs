@@ -0,0 +1,223 @@
+//! Files attached to notes, they are kept in the attachments directory next to the notes
+//! rather than inside the collection, notes refer to them by the name of the stored file
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+use uuid::Uuid;
+
+// the directory of notes kept in the config
+const ATTACHMENTS_DIR: &str = "attachments";
+// the directory next to the notes file: notes.json -> notes-attachments
+const ATTACHMENTS_DIR_SUFFIX: &str = "-attachments";
+const DEF_ATTACHMENT_NAME: &str = "attachment";
+const FILE_URI_PREFIX: &str = "file://";
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp", "webp", "tif", "tiff"];
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub struct Attachment {
+    // unique name of the stored file in the attachments directory
+    file: String,
+    // name of the original file
+    name: String,
+}
+
+impl Attachment {
+    /// Creates the attachment of the file named `name`, the stored file gets the unique name
+    /// with the same extension
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        let file = match Path::new(name).extension() {
+            Some(extension) => format!("{}.{}", Uuid::new_v4(), extension.to_string_lossy()),
+            None => Uuid::new_v4().to_string(),
+        };
+        Self {
+            file,
+            name: name.to_string(),
+        }
+    }
+
+    /// Returns the name of the stored file in the attachments directory
+    #[must_use]
+    pub fn get_file(&self) -> &str {
+        &self.file
+    }
+
+    /// Returns the name of the original file
+    #[must_use]
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Images are shown as thumbnails, other files are shown by name
+    #[must_use]
+    pub fn is_image(&self) -> bool {
+        Path::new(&self.file).extension().is_some_and(|extension| {
+            IMAGE_EXTENSIONS
+                .iter()
+                .any(|image| extension.eq_ignore_ascii_case(image))
+        })
+    }
+}
+
+/// Returns the attachments directory of notes kept in the config `config_id`
+#[must_use]
+pub fn config_attachments_dir(config_id: &str) -> PathBuf {
+    dirs_next::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join(config_id)
+        .join(ATTACHMENTS_DIR)
+}
+
+/// Returns the attachments directory of notes kept in the file, it is next to the file
+#[must_use]
+pub fn file_attachments_dir(data_file: &Path) -> PathBuf {
+    let stem = data_file
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    data_file.with_file_name(format!("{stem}{ATTACHMENTS_DIR_SUFFIX}"))
+}
+
+/// Copies the file into the attachments directory, returns the attachment to add to the note
+#[allow(clippy::missing_errors_doc)]
+pub fn try_attach_file(attachments_dir: &Path, source: &Path) -> std::io::Result<Attachment> {
+    let name = source
+        .file_name()
+        .map_or(DEF_ATTACHMENT_NAME.to_string(), |name| {
+            name.to_string_lossy().to_string()
+        });
+    let attachment = Attachment::new(&name);
+    std::fs::create_dir_all(attachments_dir)?;
+    std::fs::copy(source, attachments_dir.join(&attachment.file))?;
+    Ok(attachment)
+}
+
+/// Stores the data like the image pasted from the clipboard as the file named `name`,
+/// returns the attachment to add to the note
+#[allow(clippy::missing_errors_doc)]
+pub fn try_attach_data(
+    attachments_dir: &Path,
+    name: &str,
+    data: &[u8],
+) -> std::io::Result<Attachment> {
+    let attachment = Attachment::new(name);
+    std::fs::create_dir_all(attachments_dir)?;
+    std::fs::write(attachments_dir.join(&attachment.file), data)?;
+    Ok(attachment)
+}
+
+/// Removes files of the attachments directory which are not in `files`,
+/// returns the number of removed files
+#[allow(clippy::missing_errors_doc)]
+pub fn collect_garbage(attachments_dir: &Path, files: &HashSet<&str>) -> std::io::Result<usize> {
+    let entries = match std::fs::read_dir(attachments_dir) {
+        Ok(entries) => entries,
+        // nothing was attached yet
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+    let mut removed = 0;
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_type()?.is_file()
+            && !files.contains(entry.file_name().to_string_lossy().as_ref())
+        {
+            tracing::debug!("remove unused attachment {}", entry.path().display());
+            std::fs::remove_file(entry.path())?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Parses the list of files as it is copied by file managers: paths or `file://` URIs
+/// one per line, returns absolute paths only
+#[must_use]
+pub fn parse_file_list(text: &str) -> Vec<PathBuf> {
+    text.lines()
+        .map(str::trim)
+        // comments are allowed in text/uri-list
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| match line.strip_prefix(FILE_URI_PREFIX) {
+            Some(path) => percent_decode(path),
+            None => line.to_string(),
+        })
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .collect()
+}
+
+// decodes %XX escapes of the URI path, broken escapes are kept as they are
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = (bytes[index] == b'%')
+            .then(|| text.get(index + 1..index + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+#[test]
+fn attach_files_and_collect_garbage() {
+    let dir = std::env::temp_dir().join(format!("sticky-notes-{}", Uuid::new_v4()));
+    let source = std::env::temp_dir().join(format!("sticky-notes-{}.png", Uuid::new_v4()));
+    std::fs::write(&source, b"image").expect("source file is written");
+
+    let kept = try_attach_file(&dir, &source).expect("file is attached");
+    let dropped = try_attach_file(&dir, &source).expect("file is attached twice");
+    let _ = std::fs::remove_file(&source);
+    assert_ne!(kept.get_file(), dropped.get_file());
+    assert_eq!(
+        kept.get_name(),
+        source.file_name().unwrap().to_string_lossy()
+    );
+    assert!(kept.is_image());
+    assert!(!Attachment::new("notes.txt").is_image());
+    let pasted = try_attach_data(&dir, "image.png", b"pasted").expect("data is attached");
+    assert!(pasted.is_image());
+    assert_eq!(
+        std::fs::read(dir.join(pasted.get_file())).ok().as_deref(),
+        Some(b"pasted".as_slice())
+    );
+
+    let removed = collect_garbage(&dir, &HashSet::from([kept.get_file()]));
+    let is_kept = dir.join(kept.get_file()).exists();
+    let is_dropped = dir.join(dropped.get_file()).exists();
+    let _ = std::fs::remove_dir_all(&dir);
+    assert_eq!(removed.ok(), Some(2));
+    assert!(is_kept);
+    assert!(!is_dropped);
+    // the directory is not created until something is attached
+    assert_eq!(collect_garbage(&dir, &HashSet::new()).ok(), Some(0));
+}
+
+#[test]
+fn parse_copied_files() {
+    assert_eq!(
+        parse_file_list("# comment\r\nfile:///home/me/My%20Pictures/a.png\r\n/tmp/b.txt\nb.txt\n"),
+        [
+            PathBuf::from("/home/me/My Pictures/a.png"),
+            PathBuf::from("/tmp/b.txt")
+        ]
+    );
+    assert_eq!(percent_decode("100%"), "100%");
+    assert_eq!(
+        file_attachments_dir(Path::new("/data/notes.json")),
+        PathBuf::from("/data/notes-attachments")
+    );
+}
@@ -0,0 +1,288 @@
+use super::{Color, Font, NoteData, NoteStyle, NotesCollection};
+use std::{
+    collections::{HashMap, HashSet},
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::atomic::{AtomicU64, Ordering},
+};
+use uuid::Uuid;
+
+// Notes, styles and collections bump the generation next to marking themselves unsaved,
+// a collection is compared with the snapshot only if the generation has changed since then
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+pub(super) fn bump_generation() {
+    GENERATION.fetch_add(1, Ordering::Relaxed);
+}
+
+/// The change of the collection to notify about, see [`ChangeTracker::take_changes`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NotesChange {
+    NoteCreated(Uuid),
+    NoteContentChanged(Uuid),
+    NoteGeometryChanged(Uuid),
+    NoteDeleted(Uuid),
+    NoteRestored(Uuid),
+    StyleChanged(Uuid),
+    StyleDeleted(Uuid),
+    CollectionSaved,
+}
+
+/// Finds changes of the notes collection by comparing it with the snapshot of the previous call.
+/// The tracker is kept by notes-service apart from the collection, so changes of a note
+/// are coalesced until they are taken and nothing piles up while nobody takes them.
+#[derive(Debug, Default)]
+pub struct ChangeTracker {
+    // the generation of the snapshot, `None` until the first one is taken
+    generation: Option<u64>,
+    notes: HashMap<Uuid, NoteSnapshot>,
+    deleted_notes: HashSet<Uuid>,
+    styles: HashMap<Uuid, StyleSnapshot>,
+    is_saved: bool,
+}
+
+#[derive(Debug, PartialEq)]
+struct NoteSnapshot {
+    // the hash of the title, the content, tags, the reminder and the code language,
+    // they are reported together
+    content: u64,
+    geometry: (usize, usize, usize, usize),
+}
+
+impl NoteSnapshot {
+    fn new(note: &NoteData) -> Self {
+        let mut hasher = DefaultHasher::new();
+        note.get_explicit_title().hash(&mut hasher);
+        note.get_content().hash(&mut hasher);
+        note.get_tags().hash(&mut hasher);
+        note.get_reminder().hash(&mut hasher);
+        note.get_code_language().hash(&mut hasher);
+        Self {
+            content: hasher.finish(),
+            geometry: (note.left(), note.top(), note.width(), note.height()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct StyleSnapshot {
+    name: String,
+    font: Font,
+    color: Color,
+    is_markdown: bool,
+    is_default: bool,
+}
+
+impl StyleSnapshot {
+    fn new(style: &NoteStyle, is_default: bool) -> Self {
+        Self {
+            name: style.get_name().to_string(),
+            font: style.get_font().clone(),
+            color: style.get_background_color(),
+            is_markdown: style.is_markdown(),
+            is_default,
+        }
+    }
+}
+
+impl ChangeTracker {
+    /// Starts tracking changes of the collection as it is now
+    #[must_use]
+    pub fn new(notes: &NotesCollection) -> Self {
+        let mut tracker = Self::default();
+        tracker.take_changes(notes);
+        tracker
+    }
+
+    /// The collection was saved, it is reported after other changes
+    pub fn mark_saved(&mut self) {
+        self.is_saved = true;
+    }
+
+    /// Returns changes of the collection made since the previous call,
+    /// notes-service emits them as D-Bus signals.
+    /// The whole replaced collection is reported as deleted and created notes and styles.
+    pub fn take_changes(&mut self, notes: &NotesCollection) -> Vec<NotesChange> {
+        let generation = GENERATION.load(Ordering::Relaxed);
+        let mut changes = if self.generation == Some(generation) {
+            // nothing has been changed since the snapshot
+            Vec::new()
+        } else {
+            self.generation = Some(generation);
+            self.compare(notes)
+        };
+        if std::mem::take(&mut self.is_saved) {
+            changes.push(NotesChange::CollectionSaved);
+        }
+        changes
+    }
+
+    // compares the collection with the snapshot and takes the new one
+    fn compare(&mut self, notes: &NotesCollection) -> Vec<NotesChange> {
+        let mut changes: Vec<NotesChange> = self
+            .notes
+            .keys()
+            .filter(|note_id| notes.try_get_note(note_id).is_err())
+            .map(|note_id| NotesChange::NoteDeleted(*note_id))
+            .collect();
+        let snapshots: HashMap<Uuid, NoteSnapshot> = notes
+            .iter_notes()
+            .map(|(note_id, note)| (*note_id, NoteSnapshot::new(note)))
+            .collect();
+        for (note_id, snapshot) in &snapshots {
+            match self.notes.get(note_id) {
+                None if self.deleted_notes.contains(note_id) => {
+                    changes.push(NotesChange::NoteRestored(*note_id));
+                }
+                None => changes.push(NotesChange::NoteCreated(*note_id)),
+                Some(previous) => {
+                    if previous.content != snapshot.content {
+                        changes.push(NotesChange::NoteContentChanged(*note_id));
+                    }
+                    if previous.geometry != snapshot.geometry {
+                        changes.push(NotesChange::NoteGeometryChanged(*note_id));
+                    }
+                }
+            }
+        }
+        self.notes = snapshots;
+        self.deleted_notes = notes
+            .iter_deleted_notes()
+            .map(|(note_id, _)| *note_id)
+            .collect();
+
+        let default_style = notes.get_default_style_id();
+        changes.extend(
+            self.styles
+                .keys()
+                .filter(|style_id| notes.try_get_style(style_id).is_err())
+                .map(|style_id| NotesChange::StyleDeleted(*style_id)),
+        );
+        let styles: HashMap<Uuid, StyleSnapshot> = notes
+            .iter_styles()
+            .map(|(style_id, style)| {
+                let snapshot = StyleSnapshot::new(style, *style_id == default_style);
+                (*style_id, snapshot)
+            })
+            .collect();
+        changes.extend(
+            styles
+                .iter()
+                .filter(|(style_id, snapshot)| self.styles.get(style_id) != Some(snapshot))
+                .map(|(style_id, _)| NotesChange::StyleChanged(*style_id)),
+        );
+        self.styles = styles;
+        changes
+    }
+}
+
+#[test]
+fn take_coalesced_changes() {
+    use super::{Reminder, Repeat};
+    let mut notes = NotesCollection::default();
+    let default_style = notes.get_default_style_id();
+    let mut tracker = ChangeTracker::new(&notes);
+    assert!(tracker.take_changes(&notes).is_empty());
+
+    // the new note is reported once with all its changes
+    let note_id = notes.new_note();
+    let note = notes
+        .try_get_note_mut(&note_id)
+        .expect("new note must exist");
+    note.set_content("changed".to_string());
+    note.set_position(10, 20);
+    assert_eq!(
+        tracker.take_changes(&notes),
+        [NotesChange::NoteCreated(note_id)]
+    );
+
+    let note = notes.try_get_note_mut(&note_id).expect("note must exist");
+    note.set_content("changed again".to_string());
+    note.set_content("changed twice".to_string());
+    note.set_size(100, 200);
+    // locking is not reported
+    note.set_locking(true);
+    tracker.mark_saved();
+    assert_eq!(
+        tracker.take_changes(&notes),
+        [
+            NotesChange::NoteContentChanged(note_id),
+            NotesChange::NoteGeometryChanged(note_id),
+            NotesChange::CollectionSaved,
+        ]
+    );
+
+    // tags are reported as changed content
+    let note = notes.try_get_note_mut(&note_id).expect("note must exist");
+    note.set_tags(vec!["work".to_string()]);
+    assert_eq!(
+        tracker.take_changes(&notes),
+        [NotesChange::NoteContentChanged(note_id)]
+    );
+
+    // the reminder is reported as changed content when it is set, rings or is dismissed
+    let now = chrono::Utc::now();
+    let note = notes.try_get_note_mut(&note_id).expect("note must exist");
+    note.set_reminder(Some(Reminder::new(now, Repeat::Daily)));
+    assert_eq!(
+        tracker.take_changes(&notes),
+        [NotesChange::NoteContentChanged(note_id)]
+    );
+    notes.ring_due_reminders(now);
+    assert_eq!(
+        tracker.take_changes(&notes),
+        [NotesChange::NoteContentChanged(note_id)]
+    );
+    let note = notes.try_get_note_mut(&note_id).expect("note must exist");
+    note.snooze_reminder(now + chrono::Duration::minutes(10));
+    note.dismiss_reminder(now);
+    assert_eq!(
+        tracker.take_changes(&notes),
+        [NotesChange::NoteContentChanged(note_id)]
+    );
+    // changes are reported once
+    assert!(tracker.take_changes(&notes).is_empty());
+
+    // the note turned into code is shown another way
+    notes
+        .try_get_note_mut(&note_id)
+        .expect("note must exist")
+        .set_code_language(Some(super::Language::Shell));
+    assert_eq!(
+        tracker.take_changes(&notes),
+        [NotesChange::NoteContentChanged(note_id)]
+    );
+
+    notes
+        .try_get_style_mut(&default_style)
+        .expect("default style must exist")
+        .set_name("Renamed");
+    notes.delete_note(note_id);
+    assert_eq!(
+        tracker.take_changes(&notes),
+        [
+            NotesChange::NoteDeleted(note_id),
+            NotesChange::StyleChanged(default_style),
+        ]
+    );
+    notes
+        .try_restore_deleted_note(note_id)
+        .expect("deleted note must be restored");
+    assert_eq!(
+        tracker.take_changes(&notes),
+        [NotesChange::NoteRestored(note_id)]
+    );
+    // the note deleted and restored in between is not reported
+    notes.delete_note(note_id);
+    notes
+        .try_restore_deleted_note(note_id)
+        .expect("deleted note must be restored");
+    assert!(tracker.take_changes(&notes).is_empty());
+
+    // the loaded collection replaces all notes and styles
+    notes = NotesCollection::default();
+    let changes = tracker.take_changes(&notes);
+    assert_eq!(changes.len(), 5);
+    assert!(changes.contains(&NotesChange::NoteDeleted(note_id)));
+    assert!(changes.contains(&NotesChange::StyleDeleted(default_style)));
+    assert!(changes.contains(&NotesChange::StyleChanged(notes.get_default_style_id())));
+}
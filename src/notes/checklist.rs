@@ -0,0 +1,253 @@
+//! Tasks of checklists like `- [ ] milk` and `- [x] bread`, tasks inside code blocks are text
+use super::editing::is_rule;
+use std::collections::HashMap;
+
+pub(super) const OPEN_TASK: &str = "[ ]";
+pub(super) const DONE_TASK: &str = "[x]";
+const LINE_END: char = '\n';
+const CODE_FENCE: &str = "```";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ListMarker {
+    Bullet,
+    Number,
+    Task(bool), // is done
+}
+
+/// The text split by tasks, tasks are shown as checkboxes between the rest of the text
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChecklistPart {
+    Text(String),
+    Task {
+        line: usize,   // the number of the line in the text starting from 0
+        indent: usize, // leading spaces, a tab is 4 spaces
+        is_done: bool,
+        text: String,
+    },
+}
+
+/// Splits the text by tasks, the lines between them are joined
+#[must_use]
+pub fn split_tasks(text: &str) -> Vec<ChecklistPart> {
+    let items = list_items(text);
+    let mut parts = Vec::new();
+    let mut lines: Vec<&str> = Vec::new();
+    for (number, line) in text.split(LINE_END).enumerate() {
+        let Some((indent, ListMarker::Task(is_done))) = items.get(&number).copied() else {
+            lines.push(line);
+            continue;
+        };
+        if !lines.is_empty() {
+            parts.push(ChecklistPart::Text(lines.join(&LINE_END.to_string())));
+            lines.clear();
+        }
+        let text = parse_list_marker(line.trim_start()).map_or("", |(_, text)| text);
+        parts.push(ChecklistPart::Task {
+            line: number,
+            indent,
+            is_done,
+            text: text.to_string(),
+        });
+    }
+    if !lines.is_empty() {
+        parts.push(ChecklistPart::Text(lines.join(&LINE_END.to_string())));
+    }
+    parts
+}
+
+/// Returns the number of done tasks and of all tasks in the text
+#[must_use]
+pub fn task_progress(text: &str) -> (usize, usize) {
+    list_items(text)
+        .values()
+        .fold((0, 0), |(done, all), (_, marker)| match marker {
+            ListMarker::Task(true) => (done + 1, all + 1),
+            ListMarker::Task(false) => (done, all + 1),
+            _ => (done, all),
+        })
+}
+
+/// Marks the open task on the line as done and vice versa, returns None if there is no task
+#[must_use]
+pub fn toggle_task(text: &str, line: usize) -> Option<String> {
+    let Some((_, ListMarker::Task(is_done))) = list_items(text).get(&line).copied() else {
+        return None;
+    };
+    let mut lines: Vec<String> = text.split(LINE_END).map(ToString::to_string).collect();
+    let task = lines.get_mut(line)?;
+    // the mark follows the bullet and the space after it
+    let start = task.len() - task.trim_start().len() + 2;
+    let mark = if is_done { OPEN_TASK } else { DONE_TASK };
+    task.replace_range(start..start + mark.len(), mark);
+    Some(lines.join(&LINE_END.to_string()))
+}
+
+/// Moves done tasks below open ones in every list, nested items move with their parent
+#[must_use]
+pub fn move_done_tasks_down(text: &str) -> String {
+    rearrange_lists(text, true)
+}
+
+/// Removes done tasks with their nested items
+#[must_use]
+pub fn clear_done_tasks(text: &str) -> String {
+    rearrange_lists(text, false)
+}
+
+// list items outside of code blocks by the line number: (indent, marker),
+// the unterminated code block lasts until the end of the text
+fn list_items(text: &str) -> HashMap<usize, (usize, ListMarker)> {
+    let mut items = HashMap::new();
+    let mut is_code = false;
+    for (number, line) in text.split(LINE_END).enumerate() {
+        let trimmed = line.trim();
+        if is_code {
+            is_code = trimmed != CODE_FENCE;
+        } else if trimmed.starts_with(CODE_FENCE) {
+            is_code = true;
+        } else if !is_rule(trimmed)
+            && let Some((marker, _)) = parse_list_marker(trimmed)
+        {
+            items.insert(number, (line_indent(line), marker));
+        }
+    }
+    items
+}
+
+fn line_indent(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum()
+}
+
+// returns the marker of the list item and the text after it
+pub(super) fn parse_list_marker(line: &str) -> Option<(ListMarker, &str)> {
+    if let Some(text) = ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|bullet| line.strip_prefix(bullet))
+    {
+        let task = [("[ ] ", false), ("[x] ", true), ("[X] ", true)]
+            .iter()
+            .find_map(|(mark, is_done)| text.strip_prefix(mark).map(|text| (*is_done, text)));
+        // the task without the text is still a task
+        let task = task.or_else(|| match text.trim_end() {
+            "[ ]" => Some((false, "")),
+            "[x]" | "[X]" => Some((true, "")),
+            _ => None,
+        });
+        return Some(match task {
+            Some((is_done, text)) => (ListMarker::Task(is_done), text),
+            None => (ListMarker::Bullet, text),
+        });
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits == 0 {
+        return None;
+    }
+    let text = line[digits..]
+        .strip_prefix(". ")
+        .or_else(|| line[digits..].strip_prefix(") "))?;
+    Some((ListMarker::Number, text))
+}
+
+fn rearrange_lists(text: &str, keep_done: bool) -> String {
+    let items = list_items(text);
+    let lines: Vec<&str> = text.split(LINE_END).collect();
+    let mut result: Vec<&str> = Vec::with_capacity(lines.len());
+    let mut number = 0;
+    while number < lines.len() {
+        if !items.contains_key(&number) {
+            result.push(lines[number]);
+            number += 1;
+            continue;
+        }
+        // the list is the run of list items
+        let start = number;
+        while items.contains_key(&number) {
+            number += 1;
+        }
+        let min_indent = (start..number)
+            .filter_map(|line| items.get(&line).map(|(indent, _)| *indent))
+            .min()
+            .unwrap_or_default();
+        // the item of the least indent starts the unit, the rest are nested into it
+        let mut units: Vec<(bool, Vec<&str>)> = Vec::new();
+        for line in start..number {
+            let (indent, marker) = items[&line];
+            match units.last_mut() {
+                Some((_, unit)) if indent > min_indent => unit.push(lines[line]),
+                _ => units.push((marker == ListMarker::Task(true), vec![lines[line]])),
+            }
+        }
+        let (done, open): (Vec<_>, Vec<_>) = units.into_iter().partition(|(is_done, _)| *is_done);
+        result.extend(open.into_iter().flat_map(|(_, unit)| unit));
+        if keep_done {
+            result.extend(done.into_iter().flat_map(|(_, unit)| unit));
+        }
+    }
+    result.join(&LINE_END.to_string())
+}
+
+#[test]
+fn toggle_tasks_and_count_progress() {
+    let note = "Todo\n- [ ] milk\n  - [x] bread\n```\n- [ ] code\n```\n";
+    assert_eq!(task_progress(note), (1, 2));
+    let toggled = toggle_task(note, 1).expect("task is on the line");
+    assert_eq!(
+        toggled,
+        "Todo\n- [x] milk\n  - [x] bread\n```\n- [ ] code\n```\n"
+    );
+    assert_eq!(task_progress(&toggled), (2, 2));
+    assert_eq!(
+        toggle_task(&toggled, 2).as_deref(),
+        Some("Todo\n- [x] milk\n  - [ ] bread\n```\n- [ ] code\n```\n")
+    );
+    // no tasks there
+    assert_eq!(toggle_task(note, 0), None);
+    assert_eq!(toggle_task(note, 4), None);
+    assert_eq!(toggle_task(note, 42), None);
+}
+
+#[test]
+fn split_text_by_tasks() {
+    let note = "Todo\n- [ ] milk\n  - [x] bread\n---\n```\n- [ ] code\n```";
+    assert_eq!(
+        split_tasks(note),
+        [
+            ChecklistPart::Text("Todo".to_string()),
+            ChecklistPart::Task {
+                line: 1,
+                indent: 0,
+                is_done: false,
+                text: "milk".to_string()
+            },
+            ChecklistPart::Task {
+                line: 2,
+                indent: 2,
+                is_done: true,
+                text: "bread".to_string()
+            },
+            ChecklistPart::Text("---\n```\n- [ ] code\n```".to_string()),
+        ]
+    );
+    assert_eq!(
+        split_tasks("no tasks"),
+        [ChecklistPart::Text("no tasks".to_string())]
+    );
+}
+
+#[test]
+fn move_down_and_clear_done_tasks() {
+    let note =
+        "Todo\n- [x] milk\n  - 2 liters\n- [ ] bread\n- [X] eggs\n- [ ] tea\n\nDone\n- [x] jam";
+    assert_eq!(
+        move_done_tasks_down(note),
+        "Todo\n- [ ] bread\n- [ ] tea\n- [x] milk\n  - 2 liters\n- [X] eggs\n\nDone\n- [x] jam"
+    );
+    assert_eq!(
+        clear_done_tasks(note),
+        "Todo\n- [ ] bread\n- [ ] tea\n\nDone"
+    );
+    assert_eq!(clear_done_tasks("no tasks\n"), "no tasks\n");
+}
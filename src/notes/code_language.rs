@@ -0,0 +1,56 @@
+//! The language of notes which are code as a whole, the code is highlighted
+//! by the syntax of the language
+
+/// The language of the code, it is chosen for the note
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    Shell,
+    Rust,
+    Python,
+    JavaScript,
+    C,
+    Json,
+    Toml,
+}
+
+impl Language {
+    pub const ALL: [Language; 7] = [
+        Language::Shell,
+        Language::Rust,
+        Language::Python,
+        Language::JavaScript,
+        Language::C,
+        Language::Json,
+        Language::Toml,
+    ];
+
+    /// Returns the name of the syntax like the file extension, the highlighter finds
+    /// the syntax by it
+    #[must_use]
+    pub fn get_syntax(self) -> &'static str {
+        match self {
+            Language::Shell => "sh",
+            Language::Rust => "rs",
+            Language::Python => "py",
+            Language::JavaScript => "js",
+            Language::C => "cpp",
+            Language::Json => "json",
+            Language::Toml => "toml",
+        }
+    }
+}
+
+impl std::fmt::Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Language::Shell => write!(f, "Shell"),
+            Language::Rust => write!(f, "Rust"),
+            Language::Python => write!(f, "Python"),
+            Language::JavaScript => write!(f, "JavaScript"),
+            Language::C => write!(f, "C/C++"),
+            Language::Json => write!(f, "JSON"),
+            Language::Toml => write!(f, "TOML/INI"),
+        }
+    }
+}
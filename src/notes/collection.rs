@@ -1,13 +1,14 @@
 use std::{
     collections::{
-        HashMap,
+        HashMap, HashSet,
         hash_map::{Iter, IterMut},
     },
     path::Path,
 };
 
 use super::{
-    Font, NoteData, NoteStyle,
+    Attachment, Color, DEF_TITLE_CHARS, Font, HistoryLimits, NoteData, NoteStyle, NotesFormat,
+    WHITE, bump_generation,
     indicator_stickynotes::{
         CategoryProperties as StickyNotesCategoryProperties,
         GlobalProperties as StickyNotesGlobalProperties,
@@ -17,10 +18,8 @@ use super::{
         try_import_indicator_stickynotes,
     },
 };
-use cosmic::{
-    cosmic_theme::palette::{Hsv, Srgb, convert::FromColorUnclamped as _, rgb::Rgb},
-    iced::Color,
-};
+use chrono::{DateTime, Utc};
+use palette::{Hsv, convert::FromColorUnclamped as _, rgb::Rgb};
 use thiserror::Error;
 use uuid::Uuid;
 
@@ -35,6 +34,9 @@ pub enum NotesCollectionError {
     // Failed parsing input text
     #[error("Failed parsing notes: {0}")]
     Json(serde_json::Error),
+    // Failed reading or writing file
+    #[error("Failed accessing notes file: {0}")]
+    Io(std::io::Error),
     // must not delete the last (and default) style
     #[error("Cannot delete the last style")]
     DeleteLastStyle,
@@ -42,6 +44,8 @@ pub enum NotesCollectionError {
     StyleNotFound(Uuid),
     #[error("Style index {0} not found")]
     StyleIndexNotFound(usize),
+    #[error("Style named {0} is not found")]
+    StyleNameNotFound(String),
     #[error("Note {0} is not found")]
     NoteNotFound(Uuid),
 }
@@ -84,14 +88,14 @@ impl From<StickyNotesDatabase> for NotesCollection {
                     _ => Hsv::default(),
                 };
                 let rgb = Rgb::from_color_unclamped(hsv).into_components();
-                (
-                    id,
-                    NoteStyle::new(
-                        cat.name,
-                        parse_font(&cat.font),
-                        Color::from_rgb(rgb.0, rgb.1, rgb.2),
-                    ),
-                )
+                let mut style = NoteStyle::new(
+                    cat.name,
+                    parse_font(&cat.font),
+                    Color::new(rgb.0, rgb.1, rgb.2),
+                );
+                style.set_markdown(!cat.plain_text);
+                style.commit();
+                (id, style)
             })
             .collect();
         // finalize notes collection
@@ -102,6 +106,7 @@ impl From<StickyNotesDatabase> for NotesCollection {
             is_dirty: true,                // not saved yet
             deleted_notes: HashMap::new(), // no deleted yet
         };
+        bump_generation();
         // ensure default_style is correct
         instance.ensure_default_style();
         instance
@@ -123,19 +128,25 @@ impl From<NotesCollection> for StickyNotesDatabase {
                     locked: note.is_locked(),
                 },
                 cat: note.style(),
+                reminder: note.get_reminder().cloned(),
+                tags: note.get_tags().to_vec(),
+                attachments: note.get_attachments().to_vec(),
+                title: note.get_explicit_title().map(ToString::to_string),
+                code_language: note.get_code_language(),
             })
             .collect();
         let categories = value
             .styles
             .into_iter()
             .map(|(style_id, style)| {
-                let hsv = Hsv::from_color_unclamped(Srgb::from(style.get_background_color()));
+                let hsv = Hsv::from_color_unclamped(style.get_background_color());
                 (
                     style_id,
                     StickyNotesCategoryProperties {
                         name: style.get_name().to_string(),
                         font: serialize_font(style.get_font()),
                         bgcolor_hsv: vec![hsv.hue.into(), hsv.saturation, hsv.value],
+                        plain_text: !style.is_markdown(),
                     },
                 )
             })
@@ -158,24 +169,73 @@ impl NotesCollection {
 
     pub async fn try_import<P: AsRef<Path> + std::fmt::Debug>(
         data_file: P,
+        format: NotesFormat,
     ) -> Result<Self, NotesCollectionError> {
-        try_import_indicator_stickynotes(data_file)
-            .await
-            .map(Into::into)
-            .map_err(NotesCollectionError::Import)
+        match format {
+            NotesFormat::IndicatorStickyNotes => try_import_indicator_stickynotes(data_file)
+                .await
+                .map(Into::into)
+                .map_err(NotesCollectionError::Import),
+            NotesFormat::Json => {
+                let content = tokio::fs::read_to_string(data_file)
+                    .await
+                    .map_err(NotesCollectionError::Io)?;
+                let mut instance = Self::try_read(&content)?;
+                instance.is_dirty = true; // not saved yet
+                Ok(instance)
+            }
+        }
     }
 
     pub async fn try_export<P: AsRef<Path> + std::fmt::Debug>(
         data_file: P,
         notes: NotesCollection,
+        format: NotesFormat,
     ) -> Result<(), NotesCollectionError> {
-        try_export_indicator_stickynotes(data_file, notes.into())
-            .await
-            .map_err(NotesCollectionError::Export)
+        match format {
+            NotesFormat::IndicatorStickyNotes => {
+                try_export_indicator_stickynotes(data_file, notes.into())
+                    .await
+                    .map_err(NotesCollectionError::Export)
+            }
+            NotesFormat::Json => tokio::fs::write(data_file, notes.try_write()?)
+                .await
+                .map_err(NotesCollectionError::Io),
+        }
+    }
+
+    /// Copies files attached to notes from the attachments directory `from_dir` to `to_dir`,
+    /// missing files are skipped, returns the number of copied files
+    pub async fn try_copy_attachments(
+        &self,
+        from_dir: &Path,
+        to_dir: &Path,
+    ) -> Result<usize, NotesCollectionError> {
+        let mut copied = 0;
+        for file in self.get_attachment_files() {
+            let source = from_dir.join(file);
+            if !tokio::fs::try_exists(&source).await.unwrap_or_default() {
+                tracing::warn!("attachment {} is missing", source.display());
+                continue;
+            }
+            if copied == 0 {
+                tokio::fs::create_dir_all(to_dir)
+                    .await
+                    .map_err(NotesCollectionError::Io)?;
+            }
+            tokio::fs::copy(&source, to_dir.join(file))
+                .await
+                .map_err(NotesCollectionError::Io)?;
+            copied += 1;
+        }
+        Ok(copied)
     }
 
     pub fn try_read(input: &str) -> Result<Self, NotesCollectionError> {
-        serde_json::from_str(input).map_err(NotesCollectionError::Json)
+        let instance = serde_json::from_str(input).map_err(NotesCollectionError::Json)?;
+        // the read collection replaces the current one
+        bump_generation();
+        Ok(instance)
     }
 
     pub fn try_write(&self) -> Result<String, NotesCollectionError> {
@@ -203,6 +263,12 @@ impl NotesCollection {
         tracing::debug!("saved collection: no changes for now");
     }
 
+    // the change is saved and notified later
+    fn set_dirty(&mut self) {
+        self.is_dirty = true;
+        bump_generation();
+    }
+
     // operations with notes
 
     #[must_use]
@@ -249,6 +315,7 @@ impl NotesCollection {
     pub fn new_note(&mut self) -> Uuid {
         let id = Uuid::new_v4();
         self.notes.insert(id, NoteData::new(self.default_style));
+        bump_generation();
         id
     }
 
@@ -256,9 +323,9 @@ impl NotesCollection {
         if let Some((id, note)) = self.notes.remove_entry(&note_id) {
             tracing::debug!(
                 "(*) unsaved collection: deleted note {note_id} ({})",
-                note.get_title()
+                note.get_title(DEF_TITLE_CHARS)
             );
-            self.is_dirty = true;
+            self.set_dirty();
             self.deleted_notes.insert(id, note);
         }
     }
@@ -270,9 +337,9 @@ impl NotesCollection {
         if let Some((id, note)) = self.deleted_notes.remove_entry(&note_id) {
             tracing::debug!(
                 "(*) unsaved collection: restored note {note_id} ({})",
-                note.get_title()
+                note.get_title(DEF_TITLE_CHARS)
             );
-            self.is_dirty = true;
+            self.set_dirty();
             self.notes.insert(id, note);
             self.notes
                 .get(&id)
@@ -282,6 +349,62 @@ impl NotesCollection {
         }
     }
 
+    /// Returns stored files attached to both live and deleted notes
+    #[must_use]
+    pub fn get_attachment_files(&self) -> HashSet<&str> {
+        self.notes
+            .values()
+            .chain(self.deleted_notes.values())
+            .flat_map(NoteData::get_attachments)
+            .map(Attachment::get_file)
+            .collect()
+    }
+
+    /// Rings reminders of notes which are due, returns IDs of notes which have begun ringing
+    pub fn ring_due_reminders(&mut self, now: DateTime<Utc>) -> Vec<Uuid> {
+        self.notes
+            .iter_mut()
+            .filter_map(|(note_id, note)| note.ring_reminder_if_due(now).then_some(*note_id))
+            .collect()
+    }
+
+    // operations with revisions
+
+    /// Drops revisions of both live and deleted notes which are beyond the limits
+    pub fn prune_revisions(&mut self, limits: HistoryLimits, now: DateTime<Utc>) {
+        self.notes
+            .values_mut()
+            .chain(self.deleted_notes.values_mut())
+            .for_each(|note| note.prune_revisions(limits, now));
+    }
+
+    // operations with tags
+
+    /// Returns tags of all live notes, sorted and unique.
+    /// #hashtags of the content are tags too if `with_hashtags` is set.
+    #[must_use]
+    pub fn get_tags(&self, with_hashtags: bool) -> Vec<String> {
+        let mut tags: Vec<String> = self
+            .notes
+            .values()
+            .flat_map(|note| note.get_all_tags(with_hashtags))
+            .collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    /// Shows notes having the tag and hides the rest, returns the number of shown notes
+    pub fn show_only_tagged(&mut self, tag: &str, with_hashtags: bool) -> usize {
+        let mut count = 0;
+        for note in self.notes.values_mut() {
+            let is_tagged = note.get_all_tags(with_hashtags).iter().any(|t| t == tag);
+            note.set_visibility(is_tagged);
+            count += usize::from(is_tagged);
+        }
+        count
+    }
+
     // operations with styles
 
     #[must_use]
@@ -302,6 +425,11 @@ impl NotesCollection {
             .collect()
     }
 
+    #[must_use]
+    pub fn get_default_style_id(&self) -> Uuid {
+        self.default_style
+    }
+
     pub fn try_get_default_style(&self) -> Result<&NoteStyle, NotesCollectionError> {
         self.styles
             .get(&self.default_style)
@@ -320,21 +448,20 @@ impl NotesCollection {
         &mut self,
         style_index: usize,
     ) -> Result<(), NotesCollectionError> {
-        self.styles
+        let id = *self
+            .styles
             .keys()
             .nth(style_index)
-            .map(|id| {
-                if self.default_style != *id {
-                    tracing::debug!(
-                        "(*) unsaved collection: replaced default style {} with {}",
-                        self.default_style,
-                        *id
-                    );
-                    self.default_style = *id;
-                    self.is_dirty = true;
-                }
-            })
-            .ok_or(NotesCollectionError::StyleIndexNotFound(style_index))
+            .ok_or(NotesCollectionError::StyleIndexNotFound(style_index))?;
+        if self.default_style != id {
+            tracing::debug!(
+                "(*) unsaved collection: replaced default style {} with {id}",
+                self.default_style
+            );
+            self.default_style = id;
+            self.set_dirty();
+        }
+        Ok(())
     }
 
     pub fn try_get_style(&self, style_id: &Uuid) -> Result<&NoteStyle, NotesCollectionError> {
@@ -352,6 +479,20 @@ impl NotesCollection {
             .ok_or(NotesCollectionError::StyleNotFound(*style_id))
     }
 
+    // search style by UUID or by name, the empty string stands for the default style
+    pub fn try_find_style(&self, id_or_name: &str) -> Result<Uuid, NotesCollectionError> {
+        if id_or_name.is_empty() {
+            Ok(self.default_style)
+        } else if let Ok(style_id) = Uuid::parse_str(id_or_name) {
+            self.try_get_style(&style_id).map(|_| style_id)
+        } else {
+            self.styles
+                .iter()
+                .find_map(|(style_id, style)| (style.get_name() == id_or_name).then_some(*style_id))
+                .ok_or_else(|| NotesCollectionError::StyleNameNotFound(id_or_name.to_string()))
+        }
+    }
+
     pub fn for_each_style_mut<F>(&mut self, f: F)
     where
         F: Fn(&mut NoteStyle),
@@ -368,9 +509,10 @@ impl NotesCollection {
                 source.get_background_color(),
             )
         } else {
-            NoteStyle::new(name, Font::default(), Color::WHITE)
+            NoteStyle::new(name, Font::default(), WHITE)
         };
         self.styles.insert(id, new_style);
+        bump_generation();
         id
     }
 
@@ -379,7 +521,7 @@ impl NotesCollection {
             Err(NotesCollectionError::DeleteLastStyle)
         } else if self.styles.remove(&style_id).is_some() {
             tracing::debug!("(*) unsaved collection: deleted style {style_id}");
-            self.is_dirty = true;
+            self.set_dirty();
             // if default style is being deleted select another one as default
             if style_id == self.default_style {
                 self.default_style = self.styles.keys().next().copied().unwrap_or_default();
@@ -471,6 +613,7 @@ impl Default for NotesCollection {
         let styles = HashMap::from_iter([(default_style, NoteStyle::default())]);
         // create note with default style
         let notes = HashMap::from_iter([(Uuid::new_v4(), NoteData::new(default_style))]);
+        bump_generation();
         Self {
             notes,
             styles,
@@ -579,3 +722,382 @@ fn for_each_note_mut() {
     // test all of notes are hidden
     assert!(!collection.iter_notes().any(|(_, note)| note.is_visible()));
 }
+
+#[test]
+fn find_style_by_id_or_name() {
+    let mut collection = NotesCollection::default();
+    let default_style = collection.default_style;
+    let style_id = collection.new_style("Yellow".to_string());
+
+    // empty string means default style
+    assert_eq!(collection.try_find_style("").ok(), Some(default_style));
+    // by UUID
+    assert_eq!(
+        collection.try_find_style(&style_id.to_string()).ok(),
+        Some(style_id)
+    );
+    // by name
+    assert_eq!(collection.try_find_style("Yellow").ok(), Some(style_id));
+    // unknown name and unknown UUID
+    assert!(collection.try_find_style("Magenta").is_err());
+    assert!(
+        collection
+            .try_find_style(&Uuid::new_v4().to_string())
+            .is_err()
+    );
+}
+
+// exports notes into the temporary file and imports them back
+#[cfg(test)]
+async fn export_import(notes: &NotesCollection, format: NotesFormat) -> NotesCollection {
+    let data_file = std::env::temp_dir().join(format!("sticky-notes-{}.json", Uuid::new_v4()));
+    let exported = NotesCollection::try_export(&data_file, notes.clone(), format).await;
+    let imported = NotesCollection::try_import(&data_file, format).await;
+    let _ = std::fs::remove_file(&data_file);
+    exported.expect("export must succeed");
+    imported.expect("import must succeed")
+}
+
+#[tokio::test]
+async fn export_and_import_json_file() {
+    let mut expected = NotesCollection::default();
+    let note_id = expected.new_note();
+    expected
+        .try_get_note_mut(&note_id)
+        .expect("new note must exist")
+        .set_content("exported text".to_string());
+
+    let result = export_import(&expected, NotesFormat::Json).await;
+
+    // imported collection is not saved yet
+    assert!(result.is_unsaved());
+    assert_eq!(result.get_notes_count(), expected.get_notes_count());
+    assert_eq!(
+        result
+            .try_get_note(&note_id)
+            .map(NoteData::get_content)
+            .ok(),
+        Some("exported text")
+    );
+}
+
+#[tokio::test]
+async fn ring_reminders_and_keep_them_in_export() {
+    use super::{Reminder, Repeat};
+
+    let now = Utc::now();
+
+    let mut collection = NotesCollection::default();
+    let due_id = collection.new_note();
+    let later_id = collection.new_note();
+    collection
+        .try_get_note_mut(&due_id)
+        .expect("new note must exist")
+        .set_reminder(Some(Reminder::new(now, Repeat::Daily)));
+    collection
+        .try_get_note_mut(&later_id)
+        .expect("new note must exist")
+        .set_reminder(Some(Reminder::new(
+            now + chrono::Duration::hours(1),
+            Repeat::Once,
+        )));
+    assert_eq!(collection.ring_due_reminders(now), vec![due_id]);
+    // ringing reminder is not reported again
+    assert!(collection.ring_due_reminders(now).is_empty());
+
+    let result = export_import(&collection, NotesFormat::IndicatorStickyNotes).await;
+    let reminder = result
+        .try_get_note(&due_id)
+        .ok()
+        .and_then(NoteData::get_reminder)
+        .expect("reminder is imported");
+    assert!(reminder.is_ringing());
+    assert_eq!(reminder.get_repeat(), &Repeat::Daily);
+}
+
+#[test]
+fn tags_and_filter_by_tag() {
+    let mut collection = NotesCollection::default();
+    let tagged_id = collection.new_note();
+    let hashtag_id = collection.new_note();
+    let note = collection
+        .try_get_note_mut(&tagged_id)
+        .expect("new note must exist");
+    note.set_tags(vec!["work".to_string(), "home".to_string()]);
+    assert_eq!(note.get_tags(), ["home", "work"]);
+    collection
+        .try_get_note_mut(&hashtag_id)
+        .expect("new note must exist")
+        .set_content("buy #milk at #home".to_string());
+    assert_eq!(collection.get_tags(true), ["home", "milk", "work"]);
+
+    assert_eq!(collection.show_only_tagged("home", true), 2);
+    assert_eq!(collection.show_only_tagged("work", true), 1);
+    let visible: Vec<Uuid> = collection
+        .iter_notes()
+        .filter_map(|(note_id, note)| note.is_visible().then_some(*note_id))
+        .collect();
+    assert_eq!(visible, [tagged_id]);
+
+    // hashtags might be not tags
+    assert_eq!(collection.get_tags(false), ["home", "work"]);
+    assert_eq!(collection.show_only_tagged("milk", false), 0);
+}
+
+#[tokio::test]
+async fn keep_plain_text_styles_in_export() {
+    let mut collection = NotesCollection::default();
+    let plain_id = collection.new_style("Plain".to_string());
+    collection
+        .try_get_style_mut(&plain_id)
+        .expect("new style must exist")
+        .set_markdown(false);
+    let default_id = collection.get_default_style_id();
+
+    let result = export_import(&collection, NotesFormat::IndicatorStickyNotes).await;
+    let is_markdown = |style_id| {
+        result
+            .try_get_style(&style_id)
+            .map(NoteStyle::is_markdown)
+            .expect("style is imported")
+    };
+    assert!(!is_markdown(plain_id));
+    assert!(is_markdown(default_id));
+}
+
+#[tokio::test]
+async fn export_attachments_with_notes() {
+    let temp_dir = std::env::temp_dir();
+    let data_file = temp_dir.join(format!("sticky-notes-{}.json", Uuid::new_v4()));
+    let source = temp_dir.join(format!("sticky-notes-{}.txt", Uuid::new_v4()));
+    let storage_dir = temp_dir.join(format!("sticky-notes-{}", Uuid::new_v4()));
+    let export_dir = super::file_attachments_dir(&data_file);
+    std::fs::write(&source, "attached").expect("source file is written");
+
+    let mut collection = NotesCollection::default();
+    let note_id = collection.new_note();
+    let attachment = super::try_attach_file(&storage_dir, &source).expect("file is attached");
+    let file = attachment.get_file().to_string();
+    collection
+        .try_get_note_mut(&note_id)
+        .expect("new note must exist")
+        .add_attachment(attachment);
+    // the deleted note may be restored, so its attachment is kept
+    collection.delete_note(note_id);
+    assert_eq!(
+        collection.get_attachment_files(),
+        HashSet::from([file.as_str()])
+    );
+    collection
+        .try_restore_deleted_note(note_id)
+        .expect("note is restored");
+
+    NotesCollection::try_export(
+        &data_file,
+        collection.clone(),
+        NotesFormat::IndicatorStickyNotes,
+    )
+    .await
+    .expect("export must succeed");
+    let copied = collection
+        .try_copy_attachments(&storage_dir, &export_dir)
+        .await;
+    let result = NotesCollection::try_import(&data_file, NotesFormat::IndicatorStickyNotes).await;
+    let exported = std::fs::read_to_string(export_dir.join(&file));
+    let _ = std::fs::remove_file(&data_file);
+    let _ = std::fs::remove_file(&source);
+    let _ = std::fs::remove_dir_all(&storage_dir);
+    let _ = std::fs::remove_dir_all(&export_dir);
+
+    assert_eq!(copied.ok(), Some(1));
+    assert_eq!(exported.ok().as_deref(), Some("attached"));
+    let result = result.expect("import must succeed");
+    assert_eq!(
+        result
+            .try_get_note(&note_id)
+            .map(|note| note.get_attachments().to_vec())
+            .ok(),
+        collection
+            .try_get_note(&note_id)
+            .map(|note| note.get_attachments().to_vec())
+            .ok()
+    );
+}
+
+#[tokio::test]
+async fn explicit_and_fallback_titles() {
+    let mut collection = NotesCollection::default();
+    let note_id = collection.new_note();
+    let note = collection
+        .try_get_note_mut(&note_id)
+        .expect("new note must exist");
+    note.set_content("Buy milk, bread and eggs\nin the shop".to_string());
+    let title = |collection: &NotesCollection, chars: usize| {
+        collection
+            .try_get_note(&note_id)
+            .map(|note| note.get_title(chars).to_string())
+            .expect("note exists")
+    };
+    assert_eq!(title(&collection, DEF_TITLE_CHARS), "Buy milk, br");
+    assert_eq!(title(&collection, 8), "Buy milk");
+
+    let note = collection.try_get_note_mut(&note_id).expect("note exists");
+    note.set_title(Some("  Groceries ".to_string()));
+    assert_eq!(title(&collection, 8), "Groceries");
+
+    let result = export_import(&collection, NotesFormat::IndicatorStickyNotes).await;
+    assert_eq!(
+        result
+            .try_get_note(&note_id)
+            .map(NoteData::get_explicit_title)
+            .ok()
+            .flatten(),
+        Some("Groceries")
+    );
+
+    // the blank title titles the note by the first line again
+    let note = collection.try_get_note_mut(&note_id).expect("note exists");
+    note.set_title(Some(" ".to_string()));
+    assert_eq!(note.get_explicit_title(), None);
+    assert_eq!(title(&collection, 4), "Buy ");
+}
+
+#[test]
+fn record_and_prune_revisions() {
+    let limits = HistoryLimits {
+        revisions: 20,
+        days: 30,
+    };
+    let mut collection = NotesCollection::default();
+    let note_id = collection.new_note();
+    let note = collection
+        .try_get_note_mut(&note_id)
+        .expect("new note must exist");
+    // the empty content of the new note is not worth a revision
+    note.set_edited_content("first".to_string(), limits);
+    note.set_edited_content("second".to_string(), limits);
+    note.set_edited_content("second".to_string(), limits);
+    // only finished edits are recorded
+    note.set_content("third".to_string());
+    note.set_edited_content("fourth".to_string(), limits);
+    let revisions = |collection: &NotesCollection| {
+        collection
+            .try_get_note(&note_id)
+            .map(|note| {
+                note.get_revisions()
+                    .iter()
+                    .map(|revision| revision.get_content().to_string())
+                    .collect::<Vec<_>>()
+            })
+            .expect("note exists")
+    };
+    assert_eq!(revisions(&collection), ["first", "third"]);
+
+    // revisions are kept apart from notes
+    let json = collection.try_write().expect("collection is written");
+    let history = collection.try_write_history().expect("history is written");
+    let mut collection = NotesCollection::try_read(&json).expect("collection is read");
+    assert!(revisions(&collection).is_empty());
+    collection
+        .try_read_history(&history)
+        .expect("history is read");
+    assert_eq!(revisions(&collection), ["first", "third"]);
+
+    let now = Utc::now();
+    collection.prune_revisions(
+        HistoryLimits {
+            revisions: 1,
+            ..limits
+        },
+        now,
+    );
+    assert_eq!(revisions(&collection), ["third"]);
+    collection.prune_revisions(limits, now + chrono::Duration::days(29));
+    assert_eq!(revisions(&collection), ["third"]);
+    collection.prune_revisions(limits, now + chrono::Duration::days(31));
+    assert!(revisions(&collection).is_empty());
+
+    // no revisions are kept at all if their number is 0
+    let note = collection.try_get_note_mut(&note_id).expect("note exists");
+    note.set_edited_content(
+        "fifth".to_string(),
+        HistoryLimits {
+            revisions: 0,
+            ..limits
+        },
+    );
+    assert!(revisions(&collection).is_empty());
+}
+
+#[test]
+fn rollback_keeps_current_content() {
+    let limits = HistoryLimits {
+        revisions: 20,
+        days: 30,
+    };
+    let mut collection = NotesCollection::default();
+    let note_id = collection.new_note();
+    let note = collection
+        .try_get_note_mut(&note_id)
+        .expect("new note must exist");
+    note.set_edited_content("first".to_string(), limits);
+    note.set_edited_content("second".to_string(), limits);
+    assert!(!note.rollback(1, limits));
+
+    assert!(note.rollback(0, limits));
+    assert_eq!(note.get_content(), "first");
+    let revisions: Vec<&str> = note
+        .get_revisions()
+        .iter()
+        .map(super::Revision::get_content)
+        .collect();
+    // the rollback might be rolled back too
+    assert_eq!(revisions, ["first", "second"]);
+}
+
+#[test]
+fn keep_draft_until_edit_finished() {
+    let mut collection = NotesCollection::default();
+    let note_id = collection.new_note();
+    let note = collection
+        .try_get_note_mut(&note_id)
+        .expect("new note must exist");
+    note.set_content("saved".to_string());
+    note.commit();
+    let draft = super::Draft::new("saved and typed".to_string(), "Title".to_string(), (0, 9));
+    note.set_draft(Some(draft.clone()));
+    assert!(note.is_changed());
+    assert_eq!(note.get_content(), "saved");
+
+    let json = collection.try_write().expect("collection is written");
+    let mut collection = NotesCollection::try_read(&json).expect("collection is read");
+    let note = collection.try_get_note_mut(&note_id).expect("note exists");
+    assert_eq!(note.get_draft(), Some(&draft));
+
+    note.set_content(draft.get_content().to_string());
+    note.set_draft(None);
+    assert_eq!(note.get_draft(), None);
+    assert_eq!(note.get_content(), "saved and typed");
+}
+
+#[tokio::test]
+async fn keep_code_language() {
+    let mut collection = NotesCollection::default();
+    let note_id = collection.new_note();
+    let note = collection
+        .try_get_note_mut(&note_id)
+        .expect("new note must exist");
+    note.commit();
+    note.set_code_language(Some(super::Language::Shell));
+    assert!(note.is_changed());
+
+    let json = collection.try_write().expect("collection is written");
+    assert!(json.contains(r#""code_language":"shell""#));
+    let collection = NotesCollection::try_read(&json).expect("collection is read");
+    let note = collection.try_get_note(&note_id).expect("note exists");
+    assert_eq!(note.get_code_language(), Some(super::Language::Shell));
+
+    let result = export_import(&collection, NotesFormat::IndicatorStickyNotes).await;
+    let note = result.try_get_note(&note_id).expect("note is imported");
+    assert_eq!(note.get_code_language(), Some(super::Language::Shell));
+}
@@ -0,0 +1,37 @@
+//! The text of the note being edited, it is kept with the note until the edit is finished
+//! so typing survives the restart of notes-service
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Default, PartialEq)]
+pub struct Draft {
+    content: String,
+    // the empty title titles the note by the first line
+    title: String,
+    // (line, column) of the editor cursor
+    cursor: (usize, usize),
+}
+
+impl Draft {
+    #[must_use]
+    pub fn new(content: String, title: String, cursor: (usize, usize)) -> Self {
+        Self {
+            content,
+            title,
+            cursor,
+        }
+    }
+
+    #[must_use]
+    pub fn get_content(&self) -> &str {
+        &self.content
+    }
+
+    #[must_use]
+    pub fn get_title(&self) -> &str {
+        &self.title
+    }
+
+    #[must_use]
+    pub fn get_cursor(&self) -> (usize, usize) {
+        self.cursor
+    }
+}
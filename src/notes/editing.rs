@@ -0,0 +1,168 @@
+//! Helpers of the note editor working on the line under the cursor: list items go on,
+//! lines are indented, tasks are toggled and the current time is stamped
+use super::checklist::{DONE_TASK, ListMarker, OPEN_TASK, parse_list_marker};
+use chrono::{DateTime, TimeZone};
+use std::fmt::{Display, Write as _};
+
+// nested list items are indented by 2 spaces
+const INDENT: &str = "  ";
+// the bullet and the space after it
+const BULLET_LEN: usize = 2;
+
+/// What Enter does at the end of the list item
+#[derive(Debug, Clone, PartialEq)]
+pub enum ListContinuation {
+    // the next line begins with this marker
+    Continue(String),
+    // the item without the text ends the list, its marker is removed
+    End,
+}
+
+/// Returns how the list goes on after the line or None if the line is not a list item,
+/// the task goes on as the open one and the number is incremented
+#[must_use]
+pub fn continue_list(line: &str) -> Option<ListContinuation> {
+    let (indent, rest) = split_indent(line);
+    if is_rule(rest) {
+        return None;
+    }
+    let (marker, text) = parse_list_marker(rest)?;
+    let marker = match marker {
+        ListMarker::Bullet => format!("{indent}{}", &rest[..BULLET_LEN]),
+        ListMarker::Task(_) => format!("{indent}{}{OPEN_TASK} ", &rest[..BULLET_LEN]),
+        ListMarker::Number => {
+            let digits = rest.chars().take_while(char::is_ascii_digit).count();
+            let number: u32 = rest[..digits].parse().ok()?;
+            // the largest number is not continued
+            let next = number.checked_add(1)?;
+            // the separator is '.' or ')'
+            format!("{indent}{next}{} ", &rest[digits..=digits])
+        }
+    };
+    Some(if text.trim().is_empty() {
+        ListContinuation::End
+    } else {
+        ListContinuation::Continue(marker)
+    })
+}
+
+// three or more of the same '-', '*' or '_' with optional spaces between them
+pub(super) fn is_rule(line: &str) -> bool {
+    let mut marks = line.chars().filter(|c| !c.is_whitespace());
+    let Some(first) = marks.next() else {
+        return false;
+    };
+    matches!(first, '-' | '*' | '_') && {
+        let rest: Vec<char> = marks.collect();
+        rest.len() >= 2 && rest.iter().all(|c| *c == first)
+    }
+}
+
+/// Returns the line indented by one level
+#[must_use]
+pub fn indent_line(line: &str) -> String {
+    format!("{INDENT}{line}")
+}
+
+/// Returns the line outdented by one level and the number of removed bytes
+#[must_use]
+pub fn outdent_line(line: &str) -> (String, usize) {
+    let removed = if line.starts_with('\t') {
+        1
+    } else {
+        line.chars()
+            .take(INDENT.len())
+            .take_while(|c| *c == ' ')
+            .count()
+    };
+    (line[removed..].to_string(), removed)
+}
+
+/// Marks the open task on the line as done and vice versa, any other line becomes
+/// the open task: the bullet gets the mark, the text gets the bullet with the mark
+#[must_use]
+pub fn toggle_line_task(line: &str) -> String {
+    let (indent, rest) = split_indent(line);
+    let (bullet, text) = rest.split_at(BULLET_LEN.min(rest.len()));
+    match parse_list_marker(rest) {
+        // the mark follows the bullet
+        Some((ListMarker::Task(is_done), _)) => {
+            let mark = if is_done { OPEN_TASK } else { DONE_TASK };
+            format!("{indent}{bullet}{mark}{}", &text[mark.len()..])
+        }
+        Some((ListMarker::Bullet, _)) => format!("{indent}{bullet}{OPEN_TASK} {text}"),
+        _ => format!("{indent}- {OPEN_TASK} {rest}"),
+    }
+}
+
+/// Formats the time with the strftime-like `format`, returns None if the format is broken
+#[must_use]
+pub fn format_date_stamp<Tz>(time: &DateTime<Tz>, format: &str) -> Option<String>
+where
+    Tz: TimeZone,
+    Tz::Offset: Display,
+{
+    let mut stamp = String::new();
+    // the broken format is the error of writing rather than the panic of to_string()
+    write!(stamp, "{}", time.format(format)).ok()?;
+    Some(stamp)
+}
+
+fn split_indent(line: &str) -> (&str, &str) {
+    let rest = line.trim_start();
+    (&line[..line.len() - rest.len()], rest)
+}
+
+#[test]
+fn continue_lists() {
+    let next = |line| match continue_list(line) {
+        Some(ListContinuation::Continue(marker)) => Some(marker),
+        _ => None,
+    };
+    assert_eq!(next("- milk").as_deref(), Some("- "));
+    assert_eq!(next("  * nested").as_deref(), Some("  * "));
+    assert_eq!(next("- [x] done task").as_deref(), Some("- [ ] "));
+    assert_eq!(next("9. ninth").as_deref(), Some("10. "));
+    assert_eq!(next("1) first").as_deref(), Some("2) "));
+    assert_eq!(continue_list("- "), Some(ListContinuation::End));
+    assert_eq!(continue_list("  - [ ] "), Some(ListContinuation::End));
+    assert_eq!(continue_list("2. "), Some(ListContinuation::End));
+    assert_eq!(continue_list("plain text"), None);
+    assert_eq!(continue_list("-not a list"), None);
+    assert_eq!(continue_list("- - -"), None);
+    assert_eq!(next("- [x]milk").as_deref(), Some("- "));
+    assert_eq!(continue_list("2024.10"), None);
+    assert_eq!(continue_list("4294967295. the last"), None);
+    assert_eq!(continue_list("4294967296. too large"), None);
+}
+
+#[test]
+fn indent_and_toggle_lines() {
+    assert_eq!(indent_line("- milk"), "  - milk");
+    assert_eq!(outdent_line("   - milk"), (" - milk".to_string(), 2));
+    assert_eq!(outdent_line("\t- milk"), ("- milk".to_string(), 1));
+    assert_eq!(outdent_line("- milk"), ("- milk".to_string(), 0));
+
+    assert_eq!(toggle_line_task("  - [ ] milk"), "  - [x] milk");
+    assert_eq!(toggle_line_task("- [X] milk"), "- [ ] milk");
+    assert_eq!(toggle_line_task("* milk"), "* [ ] milk");
+    assert_eq!(toggle_line_task("- [ ]"), "- [x]");
+    // the mark must be followed by the space like in checklists, so this is the plain item
+    assert_eq!(toggle_line_task("- [x]milk"), "- [ ] [x]milk");
+    assert_eq!(toggle_line_task("1. first"), "- [ ] 1. first");
+    assert_eq!(toggle_line_task("  milk"), "  - [ ] milk");
+    assert_eq!(toggle_line_task(""), "- [ ] ");
+}
+
+#[test]
+fn stamp_date() {
+    let time = chrono::Utc
+        .with_ymd_and_hms(2024, 3, 9, 7, 5, 0)
+        .single()
+        .expect("time is valid");
+    assert_eq!(
+        format_date_stamp(&time, "%Y-%m-%d %H:%M").as_deref(),
+        Some("2024-03-09 07:05")
+    );
+    assert_eq!(format_date_stamp(&time, "%Q"), None);
+}
@@ -0,0 +1,173 @@
+//! Previous versions of the note content and the line diff between versions.
+//! Revisions are kept in the history file next to the notes rather than inside the collection.
+use super::{NotesCollection, NotesCollectionError};
+use chrono::{DateTime, Days, Local, Utc};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+use uuid::Uuid;
+
+// the history file of notes kept in the config
+const HISTORY_FILE: &str = "history.json";
+// the history file next to the notes file: notes.json -> notes-history.json
+const HISTORY_FILE_SUFFIX: &str = "-history.json";
+
+/// Previous versions of the content are kept up to the number and the age in days,
+/// no revisions are kept at all if their number is 0 while the age of 0 is no limit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HistoryLimits {
+    pub revisions: usize,
+    pub days: usize,
+}
+
+impl HistoryLimits {
+    /// Returns the time revisions written before are dropped, if they are dropped by the age
+    pub(super) fn get_oldest(self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        (self.days > 0)
+            .then(|| now.checked_sub_days(Days::new(self.days as u64)))
+            .flatten()
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub struct Revision {
+    // when the content was written, not when it was replaced
+    time: DateTime<Utc>,
+    content: String,
+}
+
+impl Revision {
+    #[must_use]
+    pub fn new(time: DateTime<Utc>, content: String) -> Self {
+        Self { time, content }
+    }
+
+    #[must_use]
+    pub fn get_time(&self) -> DateTime<Local> {
+        self.time.into()
+    }
+
+    #[must_use]
+    pub fn get_content(&self) -> &str {
+        &self.content
+    }
+
+    pub(super) fn is_older(&self, time: DateTime<Utc>) -> bool {
+        self.time < time
+    }
+}
+
+impl NotesCollection {
+    /// Writes revisions of live notes to keep them in the history file
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if revisions are not serialized
+    pub fn try_write_history(&self) -> Result<String, NotesCollectionError> {
+        let history: HashMap<&Uuid, &[Revision]> = self
+            .iter_notes()
+            .filter(|(_, note)| !note.get_revisions().is_empty())
+            .map(|(note_id, note)| (note_id, note.get_revisions()))
+            .collect();
+        serde_json::to_string(&history).map_err(NotesCollectionError::Json)
+    }
+
+    /// Reads revisions written by [`Self::try_write_history`] into notes,
+    /// revisions of missing notes are skipped
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the history is broken
+    pub fn try_read_history(&mut self, input: &str) -> Result<(), NotesCollectionError> {
+        let history: HashMap<Uuid, Vec<Revision>> =
+            serde_json::from_str(input).map_err(NotesCollectionError::Json)?;
+        for (note_id, revisions) in history {
+            if let Ok(note) = self.try_get_note_mut(&note_id) {
+                note.set_revisions(revisions);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the history file of notes kept in the config
+#[must_use]
+pub fn config_history_file(config_id: &str) -> PathBuf {
+    dirs_next::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join(config_id)
+        .join(HISTORY_FILE)
+}
+
+/// Returns the history file of notes kept in the file, it is next to the file
+#[must_use]
+pub fn file_history_file(data_file: &Path) -> PathBuf {
+    let stem = data_file
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    data_file.with_file_name(format!("{stem}{HISTORY_FILE_SUFFIX}"))
+}
+
+/// The line of the diff between the old and the new text
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffLine<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Compares texts line by line, lines removed from the old text go before lines added
+/// in their place
+#[must_use]
+pub fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    // common[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+    let mut diff = Vec::with_capacity(old.len().max(new.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            diff.push(DiffLine::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if common[i + 1][j] >= common[i][j + 1] {
+            diff.push(DiffLine::Removed(old[i]));
+            i += 1;
+        } else {
+            diff.push(DiffLine::Added(new[j]));
+            j += 1;
+        }
+    }
+    diff.extend(old[i..].iter().map(|line| DiffLine::Removed(line)));
+    diff.extend(new[j..].iter().map(|line| DiffLine::Added(line)));
+    diff
+}
+
+#[test]
+fn diff_changed_lines() {
+    assert_eq!(
+        diff_lines("milk\nbread\neggs", "milk\nbutter\neggs\ntea"),
+        [
+            DiffLine::Same("milk"),
+            DiffLine::Removed("bread"),
+            DiffLine::Added("butter"),
+            DiffLine::Same("eggs"),
+            DiffLine::Added("tea"),
+        ]
+    );
+    assert_eq!(diff_lines("", "new"), [DiffLine::Added("new")]);
+    assert_eq!(diff_lines("old", ""), [DiffLine::Removed("old")]);
+    assert!(diff_lines("", "").is_empty());
+}
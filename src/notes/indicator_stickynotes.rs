@@ -1,4 +1,4 @@
-use super::{Font, FontStyle};
+use super::{Attachment, Font, FontStyle, Language, Reminder};
 use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
 use serde::{Deserialize, Deserializer, Serializer, de::Error};
 use serde_json_fmt::JsonSyntaxError;
@@ -57,6 +57,17 @@ pub struct Note {
     pub last_modified: DateTime<Local>,
     pub properties: NoteProperties,
     pub cat: Uuid,
+    // unknown to indicator-stickynotes, these are kept to import notes back
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reminder: Option<Reminder>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code_language: Option<Language>,
 }
 
 const IMPORT_DATETIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
@@ -100,6 +111,9 @@ pub struct CategoryProperties {
     pub name: String,
     pub bgcolor_hsv: Vec<f32>,
     pub font: String,
+    // unknown to indicator-stickynotes, it is kept to import styles back
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub plain_text: bool,
 }
 
 #[derive(serde::Deserialize, serde::Serialize, PartialEq, Debug)]
@@ -0,0 +1,159 @@
+//! Links in the plain text of notes: URLs, e-mail addresses and file paths
+use std::ops::Range;
+
+const MAILTO: &str = "mailto:";
+const WEB_PREFIX: &str = "www.";
+const HOME_PREFIX: &str = "~/";
+const MARKDOWN_LINK: &str = "](";
+// punctuation around the link which is a part of the sentence rather than of the link
+const LEADING_PUNCTUATION: &[char] = &['(', '[', '<', '"', '\''];
+const TRAILING_PUNCTUATION: &[char] = &['.', ',', ';', ':', '!', '?', ')', ']', '>', '"', '\''];
+
+/// Returns what to open for the text if it is a link: the URL as it is, `mailto:` for
+/// e-mail addresses, `https://` for `www.` addresses and the file path with the home expanded
+#[must_use]
+pub fn link_target(text: &str) -> Option<String> {
+    if let Some((scheme, rest)) = text.split_once("://") {
+        let is_scheme = !scheme.is_empty()
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+        return (is_scheme && !rest.is_empty()).then(|| text.to_string());
+    }
+    if let Some(address) = text.strip_prefix(MAILTO) {
+        return is_email(address).then(|| text.to_string());
+    }
+    if text
+        .strip_prefix(WEB_PREFIX)
+        .is_some_and(|host| host.contains('.'))
+    {
+        return Some(format!("https://{text}"));
+    }
+    if is_email(text) {
+        return Some(format!("{MAILTO}{text}"));
+    }
+    if let Some(path) = text.strip_prefix(HOME_PREFIX) {
+        let home = dirs_next::home_dir()?;
+        return Some(home.join(path).to_string_lossy().to_string());
+    }
+    // absolute paths like /etc/hosts, but not a fraction like 1/2 or a comment like //
+    let mut chars = text.chars();
+    (chars.next() == Some('/')
+        && chars
+            .next()
+            .is_some_and(|c| c.is_alphanumeric() || matches!(c, '.' | '_')))
+    .then(|| text.to_string())
+}
+
+fn is_email(text: &str) -> bool {
+    let Some((local, domain)) = text.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && local
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '.' | '_' | '%' | '+' | '-'))
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && domain
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '.' | '-'))
+}
+
+/// Finds links in the text, returns their byte ranges and targets (see [`link_target`])
+#[must_use]
+pub fn find_links(text: &str) -> Vec<(Range<usize>, String)> {
+    let mut links = Vec::new();
+    let mut start = None;
+    // the whitespace at the end flushes the last word
+    for (index, c) in text
+        .char_indices()
+        .chain(std::iter::once((text.len(), ' ')))
+    {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some(index),
+            (true, Some(mut word_start)) => {
+                start = None;
+                let mut word = &text[word_start..index];
+                // the target of the Markdown link like [text](target)
+                if let Some(at) = word.find(MARKDOWN_LINK) {
+                    word_start += at + MARKDOWN_LINK.len();
+                    word = &word[at + MARKDOWN_LINK.len()..];
+                }
+                let trimmed = word.trim_start_matches(LEADING_PUNCTUATION);
+                let word_start = word_start + word.len() - trimmed.len();
+                let trimmed = trimmed.trim_end_matches(TRAILING_PUNCTUATION);
+                if let Some(target) = link_target(trimmed) {
+                    links.push((word_start..word_start + trimmed.len(), target));
+                }
+            }
+            _ => {}
+        }
+    }
+    links
+}
+
+#[test]
+fn detect_link_targets() {
+    assert_eq!(
+        link_target("https://example.com/a?b=c").as_deref(),
+        Some("https://example.com/a?b=c")
+    );
+    assert_eq!(
+        link_target("www.example.com").as_deref(),
+        Some("https://www.example.com")
+    );
+    assert_eq!(
+        link_target("me@example.com").as_deref(),
+        Some("mailto:me@example.com")
+    );
+    assert_eq!(
+        link_target("mailto:me@example.com").as_deref(),
+        Some("mailto:me@example.com")
+    );
+    assert_eq!(link_target("/etc/hosts").as_deref(), Some("/etc/hosts"));
+    if let Some(home) = dirs_next::home_dir() {
+        assert_eq!(
+            link_target("~/notes.json"),
+            Some(home.join("notes.json").to_string_lossy().to_string())
+        );
+    }
+    for text in [
+        "1/2",
+        "//",
+        "/",
+        "://x",
+        "www.",
+        "@home",
+        "a@b",
+        "me@example.",
+        "text",
+    ] {
+        assert_eq!(link_target(text), None, "{text}");
+    }
+}
+
+#[test]
+fn find_links_in_text() {
+    let text = "see (https://example.com), mail me@example.com. or /tmp/x, [my docs](https://example.org/docs)";
+    let links = find_links(text);
+    let found: Vec<&str> = links
+        .iter()
+        .map(|(range, _)| &text[range.clone()])
+        .collect();
+    assert_eq!(
+        found,
+        [
+            "https://example.com",
+            "me@example.com",
+            "/tmp/x",
+            "https://example.org/docs"
+        ]
+    );
+    assert_eq!(
+        links.get(1).map(|(_, target)| target.as_str()),
+        Some("mailto:me@example.com")
+    );
+    assert!(find_links("no links").is_empty());
+}
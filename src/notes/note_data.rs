@@ -1,12 +1,15 @@
 use super::{
-    DEF_NOTE_HEIGHT, DEF_NOTE_WIDTH, EMPTY_TITLE, MAX_TITLE_CHARS, NO_TITLE,
-    indicator_stickynotes as import,
+    Attachment, DEF_NOTE_HEIGHT, DEF_NOTE_WIDTH, Draft, EMPTY_TITLE, HistoryLimits, Language,
+    NO_TITLE, Reminder, Revision, bump_generation, indicator_stickynotes as import, parse_hashtags,
 };
 use chrono::{DateTime, Local, Utc};
 use uuid::Uuid;
 
 #[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Default, PartialEq)]
 pub struct NoteData {
+    // set by user, otherwise the note is titled by the first line
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
     content: String,
     modified: DateTime<Utc>,
     style_id: Uuid,
@@ -14,6 +17,23 @@ pub struct NoteData {
     size: (usize, usize),
     is_locked: bool,
     is_visible: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reminder: Option<Reminder>,
+    // normalized, sorted and unique
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    // files are kept in the attachments directory, see [`super::try_attach_file`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    attachments: Vec<Attachment>,
+    // previous versions of the content, the oldest first, they are kept in the history file
+    #[serde(skip)]
+    revisions: Vec<Revision>,
+    // the unfinished edit, the note is opened in edit mode with it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    draft: Option<Draft>,
+    // the whole note is code highlighted by the language instead of Markdown or plain text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    code_language: Option<Language>,
     #[serde(skip)]
     is_dirty: bool,
 }
@@ -22,6 +42,7 @@ impl NoteData {
     #[must_use]
     pub fn new(style: Uuid) -> Self {
         Self {
+            title: None,
             content: String::new(),
             modified: Utc::now(),
             position: (0, 0),
@@ -29,6 +50,12 @@ impl NoteData {
             style_id: style,
             is_locked: false,
             is_visible: true,
+            reminder: None,
+            tags: Vec::new(),
+            attachments: Vec::new(),
+            revisions: Vec::new(),
+            draft: None,
+            code_language: None,
             is_dirty: false,
         }
     }
@@ -46,6 +73,7 @@ impl NoteData {
             _ => (1, 1),
         };
         Self {
+            title: src.title,
             content: src.body,
             modified: src.last_modified.into(),
             style_id: src.cat,
@@ -53,17 +81,27 @@ impl NoteData {
             size,
             is_locked: src.properties.locked,
             is_visible,
+            reminder: src.reminder,
+            tags: src.tags,
+            attachments: src.attachments,
+            revisions: Vec::new(),
+            draft: None,
+            code_language: src.code_language,
             is_dirty: false,
         }
     }
 
+    /// Returns the title set by user or, without it, up to `fallback_chars` first characters
+    /// of the first line
     #[must_use]
-    pub fn get_title(&self) -> &str {
-        if self.content.is_empty() {
+    pub fn get_title(&self, fallback_chars: usize) -> &str {
+        if let Some(title) = &self.title {
+            title
+        } else if self.content.is_empty() {
             EMPTY_TITLE
         } else {
             self.content.lines().next().map_or(NO_TITLE, |line| {
-                match line.char_indices().nth(MAX_TITLE_CHARS) {
+                match line.char_indices().nth(fallback_chars) {
                     None => line,
                     Some((byte_index, _)) => &line[..byte_index],
                 }
@@ -71,6 +109,24 @@ impl NoteData {
         }
     }
 
+    /// Returns the title set by user
+    #[must_use]
+    pub fn get_explicit_title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Sets the title, the blank one is removed to title the note by the first line
+    pub fn set_title(&mut self, title: Option<String>) {
+        let title = title
+            .map(|title| title.trim().to_string())
+            .filter(|title| !title.is_empty());
+        if self.title != title {
+            tracing::debug!("(*) unsaved note: title changed");
+            self.title = title;
+            self.set_dirty();
+        }
+    }
+
     #[must_use]
     pub fn get_content(&self) -> &str {
         self.content.as_str()
@@ -80,7 +136,29 @@ impl NoteData {
         tracing::debug!("(*) unsaved note: content changed");
         self.content = content;
         self.modified = Utc::now();
-        self.is_dirty = true;
+        self.set_dirty();
+    }
+
+    /// Replaces the content with the finished edit, the previous one is kept as the revision
+    /// if it differs, revisions beyond the limits are dropped
+    pub fn set_edited_content(&mut self, content: String, limits: HistoryLimits) {
+        if content != self.content && !self.content.is_empty() {
+            let previous = std::mem::take(&mut self.content);
+            self.revisions.push(Revision::new(self.modified, previous));
+        }
+        self.set_content(content);
+        self.prune_revisions(limits, Utc::now());
+    }
+
+    /// Replaces the content with the revision by index, the current content is kept
+    /// as a revision like the finished edit does. Returns false if there is no such revision.
+    pub fn rollback(&mut self, index: usize, limits: HistoryLimits) -> bool {
+        let Some(revision) = self.revisions.get(index) else {
+            return false;
+        };
+        let content = revision.get_content().to_string();
+        self.set_edited_content(content, limits);
+        true
     }
 
     #[must_use]
@@ -88,6 +166,67 @@ impl NoteData {
         self.modified.into()
     }
 
+    #[must_use]
+    pub fn get_draft(&self) -> Option<&Draft> {
+        self.draft.as_ref()
+    }
+
+    /// Keeps the unfinished edit with the note, `None` drops it when the edit is finished
+    pub fn set_draft(&mut self, draft: Option<Draft>) {
+        if self.draft != draft {
+            if !self.is_dirty {
+                // report only once
+                tracing::debug!("(*) unsaved note: draft changed");
+            }
+            self.draft = draft;
+            self.set_dirty();
+        }
+    }
+
+    #[must_use]
+    pub fn get_code_language(&self) -> Option<Language> {
+        self.code_language
+    }
+
+    /// Makes the whole note code of the language, `None` turns it back to text
+    pub fn set_code_language(&mut self, language: Option<Language>) {
+        if self.code_language != language {
+            if !self.is_dirty {
+                // report only once
+                tracing::debug!("(*) unsaved note: code language changed");
+            }
+            self.code_language = language;
+            self.set_dirty();
+        }
+    }
+
+    /// Returns previous versions of the content, the oldest first
+    #[must_use]
+    pub fn get_revisions(&self) -> &[Revision] {
+        &self.revisions
+    }
+
+    pub(super) fn set_revisions(&mut self, revisions: Vec<Revision>) {
+        self.revisions = revisions;
+    }
+
+    /// Drops revisions older than the limit and then the oldest ones above the number
+    pub(super) fn prune_revisions(&mut self, limits: HistoryLimits, now: DateTime<Utc>) {
+        let before = self.revisions.len();
+        if let Some(oldest) = limits.get_oldest(now) {
+            self.revisions.retain(|revision| !revision.is_older(oldest));
+        }
+        let excess = self.revisions.len().saturating_sub(limits.revisions);
+        self.revisions.drain(..excess);
+        if self.revisions.len() != before {
+            tracing::debug!(
+                "(*) unsaved note: {} revisions pruned",
+                before - self.revisions.len()
+            );
+            self.set_dirty();
+        }
+    }
+
     #[must_use]
     pub fn left(&self) -> usize {
         self.position.0
@@ -120,7 +259,7 @@ impl NoteData {
                 tracing::debug!("(*) unsaved note: position changed");
             }
             self.position = (left, top);
-            self.is_dirty = true;
+            self.set_dirty();
         }
     }
 
@@ -131,7 +270,7 @@ impl NoteData {
                 tracing::debug!("(*) unsaved note: size changed");
             }
             self.size = (width, height);
-            self.is_dirty = true;
+            self.set_dirty();
         }
     }
 
@@ -147,7 +286,7 @@ impl NoteData {
                 tracing::debug!("(*) unsaved note: lock changed");
             }
             self.is_locked = on;
-            self.is_dirty = true;
+            self.set_dirty();
         }
     }
 
@@ -163,7 +302,102 @@ impl NoteData {
                 tracing::debug!("(*) unsaved note: visibility changed");
             }
             self.is_visible = on;
-            self.is_dirty = true;
+            self.set_dirty();
+        }
+    }
+
+    /// Returns tags set explicitly
+    #[must_use]
+    pub fn get_tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Returns tags set explicitly and, optionally, #hashtags of the content
+    #[must_use]
+    pub fn get_all_tags(&self, with_hashtags: bool) -> Vec<String> {
+        let mut tags = self.tags.clone();
+        if with_hashtags {
+            tags.extend(parse_hashtags(&self.content));
+            tags.sort();
+            tags.dedup();
+        }
+        tags
+    }
+
+    /// Replaces tags, they must be normalized (see [`super::parse_tags`])
+    pub fn set_tags(&mut self, mut tags: Vec<String>) {
+        tags.sort();
+        tags.dedup();
+        if self.tags != tags {
+            tracing::debug!("(*) unsaved note: tags changed");
+            self.tags = tags;
+            self.set_dirty();
+        }
+    }
+
+    #[must_use]
+    pub fn get_attachments(&self) -> &[Attachment] {
+        &self.attachments
+    }
+
+    pub fn add_attachment(&mut self, attachment: Attachment) {
+        tracing::debug!("(*) unsaved note: attached {}", attachment.get_name());
+        self.attachments.push(attachment);
+        self.set_dirty();
+    }
+
+    /// Removes the attachment by the name of the stored file, the file itself is removed
+    /// on saving the collection when no deleted note refers to it
+    pub fn remove_attachment(&mut self, file: &str) -> Option<Attachment> {
+        let index = self
+            .attachments
+            .iter()
+            .position(|attachment| attachment.get_file() == file)?;
+        tracing::debug!("(*) unsaved note: attachment removed");
+        self.set_dirty();
+        Some(self.attachments.remove(index))
+    }
+
+    #[must_use]
+    pub fn get_reminder(&self) -> Option<&Reminder> {
+        self.reminder.as_ref()
+    }
+
+    pub fn set_reminder(&mut self, reminder: Option<Reminder>) {
+        if self.reminder != reminder {
+            if !self.is_dirty {
+                // report only once
+                tracing::debug!("(*) unsaved note: reminder changed");
+            }
+            self.reminder = reminder;
+            self.set_dirty();
+        }
+    }
+
+    /// Rings the reminder if it is due, returns whether it has begun ringing
+    pub fn ring_reminder_if_due(&mut self, now: DateTime<Utc>) -> bool {
+        match &mut self.reminder {
+            Some(reminder) if reminder.is_due(now) => {
+                reminder.ring();
+                self.set_dirty();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn snooze_reminder(&mut self, until: DateTime<Utc>) {
+        if let Some(reminder) = &mut self.reminder {
+            reminder.snooze(until);
+            self.set_dirty();
+        }
+    }
+
+    /// Stops ringing, the recurring reminder is due the next time, the one-off is removed
+    pub fn dismiss_reminder(&mut self, now: DateTime<Utc>) {
+        if let Some(reminder) = self.reminder.take() {
+            self.reminder = reminder.dismiss(now);
+            self.set_dirty();
         }
     }
 
@@ -174,7 +408,7 @@ impl NoteData {
                 tracing::debug!("(*) unsaved note: style changed");
             }
             self.style_id = style_id;
-            self.is_dirty = true;
+            self.set_dirty();
         }
     }
 
@@ -183,6 +417,12 @@ impl NoteData {
         self.is_dirty
     }
 
+    // the change is saved and notified later
+    fn set_dirty(&mut self) {
+        self.is_dirty = true;
+        bump_generation();
+    }
+
     pub fn commit(&mut self) {
         self.is_dirty = false;
     }
@@ -1,6 +1,8 @@
-use super::{DEF_NOTE_FONT_SIZE, DEF_NOTE_STYLE_NAME};
-use cosmic::{cosmic_theme::palette::Srgb, iced::Color};
+use super::{DEF_NOTE_FONT_SIZE, DEF_NOTE_STYLE_NAME, bump_generation};
+use palette::Srgb;
 use serde::{Deserialize, Deserializer, Serializer, ser::SerializeTuple};
+use std::str::FromStr;
+use thiserror::Error;
 
 /// The style defines how to adjust font to display a text
 #[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, Default, PartialEq)]
@@ -25,6 +27,25 @@ impl std::fmt::Display for FontStyle {
     }
 }
 
+#[derive(Debug, Error)]
+#[error("Unknown font style {0}")]
+pub struct UnknownFontStyleError(String);
+
+impl FromStr for FontStyle {
+    type Err = UnknownFontStyleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "default" => Ok(FontStyle::Default),
+            "light" => Ok(FontStyle::Light),
+            "semibold" => Ok(FontStyle::Semibold),
+            "bold" => Ok(FontStyle::Bold),
+            "monospace" => Ok(FontStyle::Monospace),
+            _ => Err(UnknownFontStyleError(s.to_string())),
+        }
+    }
+}
+
 /// The set of font parameters to display a text
 #[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
 pub struct Font {
@@ -41,6 +62,12 @@ impl Default for Font {
     }
 }
 
+/// The color of a note background, is converted into GUI colors by the GUI itself
+pub type Color = Srgb;
+
+/// The default background of a sticky note
+pub const WHITE: Color = Color::new(1.0, 1.0, 1.0);
+
 /// The style to use when display a sticky note
 #[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
 pub struct NoteStyle {
@@ -49,6 +76,9 @@ pub struct NoteStyle {
     font: Font,
     #[serde(deserialize_with = "color_from_str", serialize_with = "color_to_str")]
     bgcolor: Color,
+    // notes are rendered as Markdown in view mode, otherwise as plain text
+    #[serde(default = "default_markdown")]
+    is_markdown: bool,
     #[serde(skip)]
     is_dirty: bool,
 }
@@ -73,7 +103,7 @@ fn color_to_str<S>(value: &Color, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
 {
-    let rgb: [f32; 3] = Srgb::from(*value).into();
+    let rgb: [f32; 3] = (*value).into();
     let mut serialize_array = serializer.serialize_tuple(3)?;
     for v in rgb {
         serialize_array.serialize_element(&v)?;
@@ -81,12 +111,17 @@ where
     serialize_array.end()
 }
 
+fn default_markdown() -> bool {
+    true
+}
+
 impl Default for NoteStyle {
     fn default() -> Self {
         Self {
             name: DEF_NOTE_STYLE_NAME.to_string(),
             font: Font::default(),
-            bgcolor: Color::WHITE,
+            bgcolor: WHITE,
+            is_markdown: default_markdown(),
             is_dirty: false,
         }
     }
@@ -99,6 +134,7 @@ impl NoteStyle {
             name,
             font,
             bgcolor,
+            is_markdown: default_markdown(),
             is_dirty: false,
         }
     }
@@ -122,7 +158,7 @@ impl NoteStyle {
         if self.name != name {
             tracing::debug!("(*) unsaved style: renamed {} into {name}", self.name);
             self.name = name.to_string();
-            self.is_dirty = true;
+            self.set_dirty();
         }
     }
 
@@ -130,7 +166,7 @@ impl NoteStyle {
         if self.font != font {
             tracing::debug!("(*) unsaved style: font changed");
             self.font = font;
-            self.is_dirty = true;
+            self.set_dirty();
         }
     }
 
@@ -138,7 +174,20 @@ impl NoteStyle {
         if self.bgcolor != color {
             tracing::debug!("(*) unsaved style: color changed");
             self.bgcolor = color;
-            self.is_dirty = true;
+            self.set_dirty();
+        }
+    }
+
+    #[must_use]
+    pub fn is_markdown(&self) -> bool {
+        self.is_markdown
+    }
+
+    pub fn set_markdown(&mut self, on: bool) {
+        if self.is_markdown != on {
+            tracing::debug!("(*) unsaved style: markdown rendering changed");
+            self.is_markdown = on;
+            self.set_dirty();
         }
     }
 
@@ -147,6 +196,12 @@ impl NoteStyle {
         self.is_dirty
     }
 
+    // the change is saved and notified later
+    fn set_dirty(&mut self) {
+        self.is_dirty = true;
+        bump_generation();
+    }
+
     pub fn commit(&mut self) {
         self.is_dirty = false;
     }
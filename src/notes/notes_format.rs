@@ -0,0 +1,64 @@
+use std::str::FromStr;
+use thiserror::Error;
+
+const INDICATOR_STICKYNOTES: &str = "indicator-stickynotes";
+const JSON: &str = "json";
+
+/// The file format to import notes from or to export notes into
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NotesFormat {
+    /// The database of indicator-stickynotes
+    #[default]
+    IndicatorStickyNotes,
+    /// The own notes collection as it is saved by notes-service
+    Json,
+}
+
+#[derive(Debug, Error)]
+#[error("Unknown notes format {0}")]
+pub struct UnknownFormatError(String);
+
+impl NotesFormat {
+    #[must_use]
+    pub const fn all() -> &'static [NotesFormat] {
+        &[NotesFormat::IndicatorStickyNotes, NotesFormat::Json]
+    }
+
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            NotesFormat::IndicatorStickyNotes => INDICATOR_STICKYNOTES,
+            NotesFormat::Json => JSON,
+        }
+    }
+}
+
+impl std::fmt::Display for NotesFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for NotesFormat {
+    type Err = UnknownFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            INDICATOR_STICKYNOTES => Ok(NotesFormat::IndicatorStickyNotes),
+            JSON => Ok(NotesFormat::Json),
+            _ => Err(UnknownFormatError(s.to_string())),
+        }
+    }
+}
+
+#[test]
+fn format_to_string_and_back() {
+    for format in NotesFormat::all() {
+        assert_eq!(
+            NotesFormat::from_str(&format.to_string()).ok(),
+            Some(*format)
+        );
+    }
+    assert_eq!(NotesFormat::from_str("JSON").ok(), Some(NotesFormat::Json));
+    assert!(NotesFormat::from_str("markdown").is_err());
+}
@@ -0,0 +1,276 @@
+use chrono::{DateTime, Datelike, Days, Local, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday};
+use std::str::FromStr;
+use thiserror::Error;
+
+const TIME_FORMAT: &str = "%H:%M";
+const DATE_TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+const DAYS_SEPARATOR: char = ',';
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ReminderError {
+    #[error("Invalid reminder time {0}, expected HH:MM, YYYY-MM-DD HH:MM or RFC 3339")]
+    InvalidTime(String),
+    #[error("Invalid reminder repeat {0}, expected once, daily, weekly or days like mon,fri")]
+    InvalidRepeat(String),
+}
+
+/// How the reminder repeats after it is dismissed, the time of day is kept in local time
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum Repeat {
+    #[default]
+    Once,
+    Daily,
+    Weekly,
+    Weekdays(Vec<Weekday>), // like cron's day of week, e.g. mon,tue,wed,thu,fri
+}
+
+impl Repeat {
+    fn matches(&self, weekday: Weekday) -> bool {
+        match self {
+            // no days at all is the same as every day
+            Repeat::Weekdays(days) => days.is_empty() || days.contains(&weekday),
+            _ => true,
+        }
+    }
+
+    fn step(&self) -> Option<Days> {
+        match self {
+            Repeat::Once => None,
+            Repeat::Daily | Repeat::Weekdays(_) => Some(Days::new(1)),
+            Repeat::Weekly => Some(Days::new(7)),
+        }
+    }
+}
+
+impl std::fmt::Display for Repeat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Repeat::Once => write!(f, "once"),
+            Repeat::Daily => write!(f, "daily"),
+            Repeat::Weekly => write!(f, "weekly"),
+            Repeat::Weekdays(days) => {
+                let days: Vec<String> = days
+                    .iter()
+                    .map(|day| day.to_string().to_lowercase())
+                    .collect();
+                write!(f, "{}", days.join(&DAYS_SEPARATOR.to_string()))
+            }
+        }
+    }
+}
+
+impl FromStr for Repeat {
+    type Err = ReminderError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "" | "once" => Ok(Repeat::Once),
+            "daily" => Ok(Repeat::Daily),
+            "weekly" => Ok(Repeat::Weekly),
+            days => days
+                .split(DAYS_SEPARATOR)
+                .map(|day| Weekday::from_str(day.trim()))
+                .collect::<Result<Vec<_>, _>>()
+                .map(Repeat::Weekdays)
+                .map_err(|_| ReminderError::InvalidRepeat(s.to_string())),
+        }
+    }
+}
+
+/// The time the note reminds about itself. The reminder rings when it is due until it is
+/// snoozed or dismissed, the dismissed recurring reminder is due the next time.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq, Hash)]
+pub struct Reminder {
+    due: DateTime<Utc>,
+    #[serde(default)]
+    repeat: Repeat,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    snoozed_until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    is_ringing: bool,
+}
+
+impl Reminder {
+    #[must_use]
+    pub fn new(due: DateTime<Utc>, repeat: Repeat) -> Self {
+        let mut reminder = Self {
+            due,
+            repeat,
+            snoozed_until: None,
+            is_ringing: false,
+        };
+        // the reminder on particular days starts on the first of them
+        if !reminder.repeat.matches(local_weekday(due))
+            && let Some(due) = reminder.next_due(due)
+        {
+            reminder.due = due;
+        }
+        reminder
+    }
+
+    /// Parses the time like HH:MM (the nearest one), YYYY-MM-DD HH:MM (local time) or RFC 3339
+    /// and the repeat like once, daily, weekly or mon,wed,fri
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the time or the repeat is invalid
+    pub fn parse(time: &str, repeat: &str, now: DateTime<Utc>) -> Result<Self, ReminderError> {
+        let invalid_time = || ReminderError::InvalidTime(time.to_string());
+        let time = time.trim();
+        let due = if let Ok(due) = DateTime::parse_from_rfc3339(time) {
+            due.with_timezone(&Utc)
+        } else if let Ok(due) = NaiveDateTime::parse_from_str(time, DATE_TIME_FORMAT) {
+            from_local(due).ok_or_else(invalid_time)?
+        } else {
+            let time_of_day =
+                NaiveTime::parse_from_str(time, TIME_FORMAT).map_err(|_| invalid_time())?;
+            let today = now.with_timezone(&Local).date_naive().and_time(time_of_day);
+            let due = from_local(today).ok_or_else(invalid_time)?;
+            if due > now {
+                due
+            } else {
+                today
+                    .checked_add_days(Days::new(1))
+                    .and_then(from_local)
+                    .ok_or_else(invalid_time)?
+            }
+        };
+        Ok(Self::new(due, Repeat::from_str(repeat)?))
+    }
+
+    /// Returns the time the reminder rings, i.e. the snoozed time if it is snoozed
+    #[must_use]
+    pub fn get_due(&self) -> DateTime<Local> {
+        self.snoozed_until.unwrap_or(self.due).into()
+    }
+
+    #[must_use]
+    pub fn get_repeat(&self) -> &Repeat {
+        &self.repeat
+    }
+
+    #[must_use]
+    pub fn is_ringing(&self) -> bool {
+        self.is_ringing
+    }
+
+    #[must_use]
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        !self.is_ringing && self.snoozed_until.unwrap_or(self.due) <= now
+    }
+
+    pub fn ring(&mut self) {
+        self.is_ringing = true;
+    }
+
+    pub fn snooze(&mut self, until: DateTime<Utc>) {
+        self.snoozed_until = Some(until);
+        self.is_ringing = false;
+    }
+
+    /// Stops ringing, returns the reminder of the next time or None if it does not repeat
+    #[must_use]
+    pub fn dismiss(self, now: DateTime<Utc>) -> Option<Self> {
+        let due = self.next_due(now)?;
+        Some(Self {
+            due,
+            repeat: self.repeat,
+            snoozed_until: None,
+            is_ringing: false,
+        })
+    }
+
+    // the first time after `after` following the due time by the repeat
+    fn next_due(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let step = self.repeat.step()?;
+        let mut local = self.due.with_timezone(&Local).naive_local();
+        loop {
+            local = local.checked_add_days(step)?;
+            if !self.repeat.matches(local.weekday()) {
+                continue;
+            }
+            // the time skipped by the daylight saving is the next day
+            if let Some(due) = from_local(local)
+                && due > after
+            {
+                return Some(due);
+            }
+        }
+    }
+}
+
+fn from_local(local: NaiveDateTime) -> Option<DateTime<Utc>> {
+    Local
+        .from_local_datetime(&local)
+        .earliest()
+        .map(|due| due.with_timezone(&Utc))
+}
+
+fn local_weekday(time: DateTime<Utc>) -> Weekday {
+    time.with_timezone(&Local).weekday()
+}
+
+#[test]
+fn parse_reminder() {
+    let now = Utc::now();
+    let reminder = Reminder::parse("2030-01-02 15:00", "daily", now).expect("valid reminder");
+    assert_eq!(
+        reminder.get_due().naive_local(),
+        NaiveDateTime::parse_from_str("2030-01-02 15:00", DATE_TIME_FORMAT).expect("valid time")
+    );
+    assert_eq!(reminder.get_repeat(), &Repeat::Daily);
+
+    // the nearest time of day is within a day
+    let reminder = Reminder::parse("15:00", "", now).expect("valid reminder");
+    assert!(reminder.get_due() > now);
+    assert!(reminder.get_due() <= now + Days::new(1));
+
+    let reminder = Reminder::parse("2030-01-02T15:00:00Z", "mon,fri", now).expect("valid reminder");
+    assert_eq!(
+        reminder.get_repeat(),
+        &Repeat::Weekdays(vec![Weekday::Mon, Weekday::Fri])
+    );
+    assert!(matches!(
+        reminder.get_due().weekday(),
+        Weekday::Mon | Weekday::Fri
+    ));
+    assert_eq!(reminder.get_repeat().to_string(), "mon,fri");
+
+    assert_eq!(
+        Reminder::parse("tomorrow", "once", now),
+        Err(ReminderError::InvalidTime("tomorrow".to_string()))
+    );
+    assert_eq!(
+        Reminder::parse("15:00", "hourly", now),
+        Err(ReminderError::InvalidRepeat("hourly".to_string()))
+    );
+}
+
+#[test]
+fn ring_snooze_and_dismiss() {
+    let now = Utc::now();
+    let due = now - chrono::Duration::minutes(1);
+    let mut reminder = Reminder::new(due, Repeat::Once);
+    assert!(reminder.is_due(now));
+    reminder.ring();
+    assert!(reminder.is_ringing());
+    // does not ring again until snoozed
+    assert!(!reminder.is_due(now));
+    reminder.snooze(now + chrono::Duration::minutes(10));
+    assert!(!reminder.is_ringing());
+    assert!(!reminder.is_due(now));
+    assert!(reminder.is_due(now + chrono::Duration::minutes(10)));
+    // one-off reminder is over when dismissed
+    assert_eq!(reminder.dismiss(now), None);
+
+    let mut weekly = Reminder::new(due, Repeat::Weekly);
+    weekly.ring();
+    let next = weekly.dismiss(now).expect("repeated");
+    assert!(!next.is_ringing());
+    assert!(!next.is_due(now));
+    assert_eq!(
+        next.get_due().weekday(),
+        DateTime::<Local>::from(due).weekday()
+    );
+    assert!(next.get_due() <= now + Days::new(7));
+}
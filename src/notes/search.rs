@@ -0,0 +1,224 @@
+use super::{NoteData, NotesCollection};
+use uuid::Uuid;
+
+// the number of characters shown around the match
+const SNIPPET_CONTEXT: usize = 32;
+const ELLIPSIS: &str = "…";
+
+/// The note containing the searched text, the snippet is split to highlight the match
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchMatch {
+    pub note_id: Uuid,
+    pub style_id: Uuid,
+    pub title: String,
+    pub is_deleted: bool,
+    pub before: String,
+    pub matched: String,
+    pub after: String,
+}
+
+impl NotesCollection {
+    /// Finds case-insensitively notes containing `query`, both live and recently deleted,
+    /// live notes go first. The empty query finds nothing.
+    /// Notes without the title are titled by up to `title_chars` characters of the first line.
+    #[must_use]
+    pub fn search(&self, query: &str, title_chars: usize) -> Vec<SearchMatch> {
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let live = self.iter_notes().filter_map(|(note_id, note)| {
+            search_note(*note_id, note, note.get_title(title_chars), query, false)
+        });
+        let deleted = self.iter_deleted_notes().filter_map(|(note_id, note)| {
+            search_note(*note_id, note, note.get_title(title_chars), query, true)
+        });
+        let mut found: Vec<SearchMatch> = live.chain(deleted).collect();
+        found.sort_by(|a, b| {
+            a.is_deleted
+                .cmp(&b.is_deleted)
+                .then_with(|| a.title.cmp(&b.title))
+        });
+        found
+    }
+}
+
+fn search_note(
+    note_id: Uuid,
+    note: &NoteData,
+    title: &str,
+    query: &str,
+    is_deleted: bool,
+) -> Option<SearchMatch> {
+    // the note titled by user is found by the title too, the title is the snippet then
+    let content = note.get_content();
+    let (content, (start, end)) = match find_ignore_case(content, query) {
+        Some(range) => (content, range),
+        None => {
+            let title = note.get_explicit_title()?;
+            (title, find_ignore_case(title, query)?)
+        }
+    };
+    let before = &content[..start];
+    let after = &content[end..];
+    let skip = before.chars().count().saturating_sub(SNIPPET_CONTEXT);
+    let mut snippet_before: String = before.chars().skip(skip).collect();
+    if skip > 0 {
+        snippet_before.insert_str(0, ELLIPSIS);
+    }
+    let mut snippet_after: String = after.chars().take(SNIPPET_CONTEXT).collect();
+    if after.chars().nth(SNIPPET_CONTEXT).is_some() {
+        snippet_after.push_str(ELLIPSIS);
+    }
+    Some(SearchMatch {
+        note_id,
+        style_id: note.style(),
+        title: title.to_string(),
+        is_deleted,
+        before: single_line(&snippet_before),
+        matched: single_line(&content[start..end]),
+        after: single_line(&snippet_after),
+    })
+}
+
+// returns the byte range of the first match, characters are compared by their lowercase
+fn find_ignore_case(text: &str, query: &str) -> Option<(usize, usize)> {
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    text.char_indices().find_map(|(start, _)| {
+        let mut expected = query.iter();
+        for (index, c) in text[start..].char_indices() {
+            let mut lower = c.to_lowercase();
+            if !lower.all(|c| expected.next() == Some(&c)) {
+                return None;
+            }
+            if expected.len() == 0 {
+                return Some((start, start + index + c.len_utf8()));
+            }
+        }
+        None
+    })
+}
+
+/// Returns byte ranges of all matches of `query` in `text` which don't overlap,
+/// characters are compared by their lowercase unless `match_case` is set
+#[must_use]
+pub fn find_all(text: &str, query: &str, match_case: bool) -> Vec<(usize, usize)> {
+    let mut found = Vec::new();
+    if query.is_empty() {
+        return found;
+    }
+    let mut offset = 0;
+    while let Some((start, end)) = if match_case {
+        text[offset..]
+            .find(query)
+            .map(|start| (start, start + query.len()))
+    } else {
+        find_ignore_case(&text[offset..], query)
+    } {
+        found.push((offset + start, offset + end));
+        offset += end;
+    }
+    found
+}
+
+/// Replaces all matches of `query` in `text`, returns the text and the number of replaced
+#[must_use]
+pub fn replace_all(
+    text: &str,
+    query: &str,
+    replacement: &str,
+    match_case: bool,
+) -> (String, usize) {
+    let found = find_all(text, query, match_case);
+    let mut replaced = String::with_capacity(text.len());
+    let mut offset = 0;
+    for (start, end) in &found {
+        replaced.push_str(&text[offset..*start]);
+        replaced.push_str(replacement);
+        offset = *end;
+    }
+    replaced.push_str(&text[offset..]);
+    (replaced, found.len())
+}
+
+fn single_line(text: &str) -> String {
+    text.replace(['\n', '\r', '\t'], " ")
+}
+
+#[test]
+fn find_text_ignoring_case() {
+    assert_eq!(find_ignore_case("Buy Milk", "milk"), Some((4, 8)));
+    assert_eq!(find_ignore_case("Купить МОЛОКО", "молоко"), Some((13, 25)));
+    assert_eq!(find_ignore_case("buy milk", "bread"), None);
+    assert_eq!(find_ignore_case("mil", "milk"), None);
+}
+
+#[test]
+fn find_and_replace_all_matches() {
+    assert_eq!(
+        find_all("Milk, milk and MILK", "milk", false),
+        [(0, 4), (6, 10), (15, 19)]
+    );
+    assert_eq!(find_all("Milk, milk and MILK", "milk", true), [(6, 10)]);
+    assert_eq!(find_all("aaaa", "aa", true), [(0, 2), (2, 4)]);
+    assert!(find_all("milk", "", false).is_empty());
+    assert_eq!(
+        replace_all("Молоко и молоко", "МОЛОКО", "кефир", false),
+        ("кефир и кефир".to_string(), 2)
+    );
+    assert_eq!(
+        replace_all("tea", "milk", "kefir", false),
+        ("tea".to_string(), 0)
+    );
+}
+
+#[test]
+fn search_live_and_deleted_notes() {
+    use super::DEF_TITLE_CHARS;
+
+    let mut collection = NotesCollection::default();
+    let live_id = collection.new_note();
+    let deleted_id = collection.new_note();
+    let long_text = format!("{}\nsay HELLO to Bob\n{}", "a".repeat(40), "b".repeat(40));
+    for (note_id, content) in [(live_id, long_text.as_str()), (deleted_id, "Hello")] {
+        collection
+            .try_get_note_mut(&note_id)
+            .expect("new note must exist")
+            .set_content(content.to_string());
+    }
+    collection.delete_note(deleted_id);
+
+    let found = collection.search("hello", DEF_TITLE_CHARS);
+    assert_eq!(found.len(), 2);
+    assert_eq!(found[0].note_id, live_id);
+    assert!(!found[0].is_deleted);
+    assert_eq!(found[0].matched, "HELLO");
+    assert_eq!(
+        found[0].before,
+        format!("{ELLIPSIS}{} say ", "a".repeat(27))
+    );
+    assert_eq!(
+        found[0].after,
+        format!(" to Bob {}{ELLIPSIS}", "b".repeat(24))
+    );
+    assert_eq!(found[1].note_id, deleted_id);
+    assert!(found[1].is_deleted);
+    assert_eq!(
+        (found[1].before.as_str(), found[1].after.as_str()),
+        ("", "")
+    );
+
+    let titled_id = collection.new_note();
+    collection
+        .try_get_note_mut(&titled_id)
+        .expect("new note must exist")
+        .set_title(Some("Greetings".to_string()));
+    let found = collection.search("greet", DEF_TITLE_CHARS);
+    assert_eq!(found.len(), 1);
+    assert_eq!(
+        (found[0].title.as_str(), found[0].matched.as_str()),
+        ("Greetings", "Greet")
+    );
+
+    assert!(collection.search("", DEF_TITLE_CHARS).is_empty());
+    assert!(collection.search("goodbye", DEF_TITLE_CHARS).is_empty());
+}
@@ -0,0 +1,71 @@
+const HASH: char = '#';
+const TAGS_SEPARATOR: char = ',';
+
+/// Brings the tag to the form it is kept in: lowercase without the leading hash,
+/// None if nothing is left
+#[must_use]
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag.trim().trim_start_matches(HASH).trim();
+    (!tag.is_empty() && !tag.contains(char::is_whitespace)).then(|| tag.to_lowercase())
+}
+
+/// Parses tags typed by the user: separated by commas or spaces, hashes are optional
+#[must_use]
+pub fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags: Vec<String> = input
+        .split(|c: char| c == TAGS_SEPARATOR || c.is_whitespace())
+        .filter_map(normalize_tag)
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+/// Finds #hashtags in the text: the hash begins a word and is followed by letters,
+/// digits, '_' or '-', so Markdown headings like "# Title" are not tags
+#[must_use]
+pub fn parse_hashtags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = text
+        .split_whitespace()
+        .filter_map(|word| {
+            let tag: String = word
+                .strip_prefix(HASH)?
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '-')
+                .collect();
+            normalize_tag(&tag)
+        })
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+/// Formats tags the way they are typed, e.g. "#home #todo"
+#[must_use]
+pub fn format_tags(tags: &[String]) -> String {
+    tags.iter()
+        .map(|tag| format!("{HASH}{tag}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[test]
+fn parse_and_format_tags() {
+    assert_eq!(normalize_tag(" #Home "), Some("home".to_string()));
+    assert_eq!(normalize_tag("#"), None);
+    assert_eq!(normalize_tag("two words"), None);
+    assert_eq!(
+        parse_tags("work, #Home todo,,work"),
+        ["home", "todo", "work"]
+    );
+    assert!(parse_tags(" , ").is_empty());
+    assert_eq!(
+        parse_hashtags("# Title\nbuy #milk, #Bread and #milk\nissue#1 #1 ##x"),
+        ["1", "bread", "milk"]
+    );
+    assert_eq!(
+        format_tags(&["home".to_string(), "todo".to_string()]),
+        "#home #todo"
+    );
+}
@@ -0,0 +1,244 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use futures_util::{Stream, StreamExt};
+use std::collections::HashMap;
+use zbus::{proxy, zvariant::Value};
+
+const APP_NAME: &str = "Sticky Notes";
+const APP_ICON: &str = "com.github.aae.sticky_notes";
+// the notification server decides how long the notification is shown
+const EXPIRE_DEFAULT: i32 = -1;
+const URGENCY_NORMAL: u8 = 1;
+const URGENCY_CRITICAL: u8 = 2;
+
+/// Client side of the freedesktop notifications, `NotificationsProxy` is generated from it
+#[proxy(
+    interface = "org.freedesktop.Notifications",
+    default_service = "org.freedesktop.Notifications",
+    default_path = "/org/freedesktop/Notifications"
+)]
+pub trait Notifications {
+    /// Shows the notification, returns its ID
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        app_name: &str,
+        replaces_id: u32,
+        app_icon: &str,
+        summary: &str,
+        body: &str,
+        actions: &[&str],
+        hints: &HashMap<&str, &Value<'_>>,
+        expire_timeout: i32,
+    ) -> zbus::Result<u32>;
+
+    /// The action of the notification is invoked, notifications of every application are signaled
+    #[zbus(signal)]
+    fn action_invoked(&self, id: u32, action_key: String) -> zbus::Result<()>;
+}
+
+/// Kinds of events the user is notified about, each kind is turned on or off in the config
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    LoadFailed,       // loading or importing notes failed
+    SaveFailed,       // saving notes failed, changes might be lost
+    ExportFailed,     // exporting notes failed
+    StyleFailed,      // changing styles failed
+    ImportExportDone, // notes were imported or exported successfully
+    Reminder,         // the reminder of the note is due
+}
+
+impl Category {
+    #[must_use]
+    pub fn is_failure(self) -> bool {
+        !matches!(self, Category::ImportExportDone | Category::Reminder)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub category: Category,
+    pub summary: String,
+    pub body: String,
+    pub actions: Vec<(String, String)>, // (key, label), the key is signaled when invoked
+}
+
+impl Notification {
+    #[must_use]
+    pub fn new(category: Category, summary: impl Into<String>, body: impl Into<String>) -> Self {
+        Self {
+            category,
+            summary: summary.into(),
+            body: body.into(),
+            actions: Vec::new(),
+        }
+    }
+
+    /// Adds the button to the notification, see [`receive_actions`]
+    #[must_use]
+    pub fn with_action(mut self, key: impl Into<String>, label: impl Into<String>) -> Self {
+        self.actions.push((key.into(), label.into()));
+        self
+    }
+
+    #[must_use]
+    pub fn urgency(&self) -> u8 {
+        if self.category.is_failure() {
+            URGENCY_CRITICAL
+        } else {
+            URGENCY_NORMAL
+        }
+    }
+}
+
+/// Sends the notification to the notification server of the bus
+///
+/// # Errors
+///
+/// Returns error if there is no notification server or it refused the notification
+pub async fn notify(
+    connection: &zbus::Connection,
+    notification: &Notification,
+) -> zbus::Result<u32> {
+    let proxy = NotificationsProxy::new(connection).await?;
+    let urgency = Value::from(notification.urgency());
+    let hints = HashMap::from([("urgency", &urgency)]);
+    // actions are sent as the flat list of keys and labels
+    let actions: Vec<&str> = notification
+        .actions
+        .iter()
+        .flat_map(|(key, label)| [key.as_str(), label.as_str()])
+        .collect();
+    proxy
+        .notify(
+            APP_NAME,
+            0,
+            APP_ICON,
+            &notification.summary,
+            &notification.body,
+            &actions,
+            &hints,
+            EXPIRE_DEFAULT,
+        )
+        .await
+}
+
+/// Receives keys of actions invoked on notifications of any application,
+/// keys added by [`Notification::with_action`] tell which actions are ours
+///
+/// # Errors
+///
+/// Returns error if the signal can't be subscribed on the bus
+pub async fn receive_actions(
+    connection: &zbus::Connection,
+) -> zbus::Result<impl Stream<Item = String> + Send + Unpin + 'static> {
+    let proxy = NotificationsProxy::new(connection).await?;
+    let signals = proxy.receive_action_invoked().await?;
+    Ok(signals
+        .filter_map(|signal| std::future::ready(signal.args().ok().map(|args| args.action_key))))
+}
+
+#[cfg(test)]
+type SentNotifications = std::sync::Arc<std::sync::Mutex<Vec<(String, String, u8, Vec<String>)>>>;
+
+// notification server keeping (summary, body, urgency, actions) of notifications
+#[cfg(test)]
+struct MockNotifications {
+    sent: SentNotifications,
+}
+
+#[cfg(test)]
+#[zbus::interface(name = "org.freedesktop.Notifications")]
+impl MockNotifications {
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        _app_name: &str,
+        _replaces_id: u32,
+        _app_icon: &str,
+        summary: &str,
+        body: &str,
+        actions: Vec<String>,
+        hints: HashMap<String, zbus::zvariant::OwnedValue>,
+        _expire_timeout: i32,
+    ) -> u32 {
+        let urgency = hints
+            .get("urgency")
+            .and_then(|urgency| u8::try_from(urgency).ok())
+            .unwrap_or_default();
+        let mut sent = self.sent.lock().expect("not poisoned");
+        sent.push((summary.to_string(), body.to_string(), urgency, actions));
+        u32::try_from(sent.len()).unwrap_or_default()
+    }
+}
+
+// the private bus keeps tests away from the desktop session
+#[cfg(test)]
+struct PrivateBus(std::process::Child);
+
+#[cfg(test)]
+impl Drop for PrivateBus {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+#[tokio::test]
+#[ignore = "runs dbus-daemon for a private session bus, see `cargo test -- --ignored`"]
+async fn notify_mock_server() {
+    use std::io::BufRead;
+    let mut daemon = std::process::Command::new("dbus-daemon")
+        .args(["--session", "--nofork", "--print-address"])
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .expect("dbus-daemon must be installed to test notifications");
+    let mut address = String::new();
+    std::io::BufReader::new(daemon.stdout.take().expect("piped stdout"))
+        .read_line(&mut address)
+        .expect("bus address");
+    let _bus = PrivateBus(daemon);
+    let bus_connection =
+        || zbus::connection::Builder::address(address.trim()).expect("valid bus address");
+
+    let sent = SentNotifications::default();
+    let _server = bus_connection()
+        .name("org.freedesktop.Notifications")
+        .and_then(|builder| {
+            builder.serve_at(
+                "/org/freedesktop/Notifications",
+                MockNotifications { sent: sent.clone() },
+            )
+        })
+        .expect("mock server")
+        .build()
+        .await
+        .expect("mock server is connected");
+    let client = bus_connection().build().await.expect("client is connected");
+
+    let failure = Notification::new(Category::SaveFailed, "Notes are not saved", "disk is full");
+    assert_eq!(notify(&client, &failure).await.ok(), Some(1));
+    let reminder = Notification::new(Category::Reminder, "Call", "")
+        .with_action("snooze", "Snooze")
+        .with_action("dismiss", "Dismiss");
+    assert_eq!(notify(&client, &reminder).await.ok(), Some(2));
+    assert_eq!(
+        *sent.lock().expect("not poisoned"),
+        vec![
+            (
+                "Notes are not saved".to_string(),
+                "disk is full".to_string(),
+                URGENCY_CRITICAL,
+                Vec::new()
+            ),
+            (
+                "Call".to_string(),
+                String::new(),
+                URGENCY_NORMAL,
+                ["snooze", "Snooze", "dismiss", "Dismiss"]
+                    .map(String::from)
+                    .to_vec()
+            ),
+        ]
+    );
+}
@@ -0,0 +1,50 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Parts of notes-service shared with its command line client: commands, command line
+//! arguments and the place where notes are kept. They don't need libcosmic.
+
+use crate::dbus::NotesProxy;
+pub use {
+    command::{Command, NotesAppError},
+    flags::{ServiceFlags, ServiceFlagsError, USAGE},
+    storage::NotesStorage,
+};
+
+mod command;
+mod flags;
+mod storage;
+
+/// Sends the command to notes-service over the notes API
+///
+/// # Errors
+///
+/// Returns D-Bus error, e.g. if notes-service is not running
+pub async fn send_command(proxy: &NotesProxy<'_>, command: &Command) -> zbus::Result<()> {
+    match command {
+        Command::Ignored => Ok(()),
+        Command::Connect => proxy.connect_applet().await,
+        Command::Quit => proxy.quit().await,
+        Command::NewNote(content, style) => proxy.create_note(content, style).await.map(|_| ()),
+        Command::LoadNotes => proxy.load_notes().await,
+        Command::SaveNotes => proxy.save_notes().await,
+        Command::ImportNotes(Some((file, format))) => {
+            proxy.import_notes(file, format.as_str()).await
+        }
+        Command::ImportNotes(None) => proxy.import_notes("", "").await,
+        Command::ExportNotes(Some((file, format))) => {
+            proxy.export_notes(file, format.as_str()).await
+        }
+        Command::ExportNotes(None) => proxy.export_notes("", "").await,
+        Command::ShowAllNotes => proxy.set_all_notes_visible(true).await,
+        Command::HideAllNotes => proxy.set_all_notes_visible(false).await,
+        Command::LockAll => proxy.lock_all_notes().await,
+        Command::ShowNote(note_id) => proxy.set_note_visible(&note_id.to_string(), true).await,
+        Command::HideNote(note_id) => proxy.set_note_visible(&note_id.to_string(), false).await,
+        Command::LockNote(note_id) => proxy.set_note_locked(&note_id.to_string(), true).await,
+        Command::ShowTagged(tag) => proxy.show_tagged_notes(tag).await,
+        Command::RestoreNotes => proxy.open_restore_window().await,
+        Command::SearchNotes => proxy.open_search_window().await,
+        Command::OpenSettings => proxy.open_settings_window().await,
+        Command::OpenAbout => proxy.open_about_window().await,
+    }
+}
@@ -0,0 +1,399 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::notes::{NotesFormat, normalize_tag};
+use std::str::FromStr;
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Commands of the main menu, some of them take arguments.
+///
+/// The text form is the name followed by arguments separated by spaces, an argument having
+/// spaces or quotes is double-quoted: `NEW "buy milk" Green`, `SHOW <uuid>`, `IMPORT <path> json`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Ignored, // dummy command
+    Connect,
+    Quit,
+    NewNote(String, String), // (content, style), the empty style stands for the default one
+    LoadNotes,
+    SaveNotes,
+    ImportNotes(Option<(String, NotesFormat)>), // (file, format), the configured file if None
+    ExportNotes(Option<(String, NotesFormat)>), // (file, format), the configured file if None
+    ShowAllNotes,
+    HideAllNotes,
+    LockAll,
+    ShowNote(Uuid),
+    HideNote(Uuid),
+    LockNote(Uuid),
+    ShowTagged(String), // shows notes having the tag and hides the rest
+    RestoreNotes,
+    SearchNotes,
+    OpenSettings,
+    OpenAbout,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum NotesAppError {
+    // Failed reading source file
+    #[error("Failed parsing command: {0}")]
+    ParseError(String),
+    #[error("Command {0} got unexpected argument {1}")]
+    UnexpectedArgument(String, String),
+    #[error("Invalid argument {1} of command {0}: {2}")]
+    InvalidArgument(String, String, String),
+}
+
+const CONNECT: &str = "CONNECT";
+const QUIT: &str = "QUIT";
+const NEW: &str = "NEW";
+const LOAD: &str = "LOAD";
+const SAVE: &str = "SAVE";
+const IMPORT: &str = "IMPORT";
+const EXPORT: &str = "EXPORT";
+const SHOW: &str = "SHOW";
+const HIDE: &str = "HIDE";
+const LOCK: &str = "LOCK";
+const TAG: &str = "TAG";
+const RESTORE: &str = "RESTORE";
+const SEARCH: &str = "SEARCH";
+const SETTINGS: &str = "SETTINGS";
+const ABOUT: &str = "ABOUT";
+const IGNORED: &str = "IGNORED";
+
+impl Command {
+    /// Names of all commands but the dummy one
+    pub const NAMES: &[&str] = &[
+        CONNECT, QUIT, NEW, LOAD, SAVE, IMPORT, EXPORT, SHOW, HIDE, LOCK, TAG, RESTORE, SEARCH,
+        SETTINGS, ABOUT,
+    ];
+
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::Ignored => IGNORED,
+            Command::Connect => CONNECT,
+            Command::Quit => QUIT,
+            Command::NewNote(..) => NEW,
+            Command::LoadNotes => LOAD,
+            Command::SaveNotes => SAVE,
+            Command::ImportNotes(_) => IMPORT,
+            Command::ExportNotes(_) => EXPORT,
+            Command::ShowAllNotes | Command::ShowNote(_) => SHOW,
+            Command::HideAllNotes | Command::HideNote(_) => HIDE,
+            Command::LockAll | Command::LockNote(_) => LOCK,
+            Command::ShowTagged(_) => TAG,
+            Command::RestoreNotes => RESTORE,
+            Command::SearchNotes => SEARCH,
+            Command::OpenSettings => SETTINGS,
+            Command::OpenAbout => ABOUT,
+        }
+    }
+
+    #[must_use]
+    pub fn args(&self) -> Vec<String> {
+        match self {
+            Command::NewNote(content, style) if style.is_empty() => vec![content.clone()],
+            Command::NewNote(content, style) => vec![content.clone(), style.clone()],
+            Command::ImportNotes(Some((file, format)))
+            | Command::ExportNotes(Some((file, format))) => {
+                vec![file.clone(), format.to_string()]
+            }
+            Command::ShowNote(note_id)
+            | Command::HideNote(note_id)
+            | Command::LockNote(note_id) => {
+                vec![note_id.to_string()]
+            }
+            Command::ShowTagged(tag) => vec![tag.clone()],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Builds the command from its name and arguments
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the name is unknown or arguments don't suit the command
+    pub fn try_from_args<S: AsRef<str>>(name: &str, args: &[S]) -> Result<Self, NotesAppError> {
+        let arg = |index: usize| args.get(index).map(AsRef::as_ref);
+        let note_id = || {
+            arg(0)
+                .map(|id| {
+                    Uuid::parse_str(id).map_err(|e| {
+                        NotesAppError::InvalidArgument(
+                            name.to_string(),
+                            id.to_string(),
+                            e.to_string(),
+                        )
+                    })
+                })
+                .transpose()
+        };
+        let command = match name {
+            IGNORED => Command::Ignored,
+            CONNECT => Command::Connect,
+            QUIT => Command::Quit,
+            NEW => Command::NewNote(
+                arg(0).unwrap_or_default().to_string(),
+                arg(1).unwrap_or_default().to_string(),
+            ),
+            LOAD => Command::LoadNotes,
+            SAVE => Command::SaveNotes,
+            IMPORT => Command::ImportNotes(file_args(name, arg(0), arg(1))?),
+            EXPORT => Command::ExportNotes(file_args(name, arg(0), arg(1))?),
+            SHOW => note_id()?.map_or(Command::ShowAllNotes, Command::ShowNote),
+            HIDE => note_id()?.map_or(Command::HideAllNotes, Command::HideNote),
+            LOCK => note_id()?.map_or(Command::LockAll, Command::LockNote),
+            TAG => Command::ShowTagged(
+                arg(0)
+                    .and_then(normalize_tag)
+                    .ok_or_else(|| NotesAppError::ParseError(name.to_string()))?,
+            ),
+            RESTORE => Command::RestoreNotes,
+            SEARCH => Command::SearchNotes,
+            SETTINGS => Command::OpenSettings,
+            ABOUT => Command::OpenAbout,
+            _ => return Err(NotesAppError::ParseError(name.to_string())),
+        };
+        let max_args = match command {
+            Command::NewNote(..) | Command::ImportNotes(_) | Command::ExportNotes(_) => 2,
+            Command::ShowNote(_)
+            | Command::HideNote(_)
+            | Command::LockNote(_)
+            | Command::ShowTagged(_) => 1,
+            _ => 0,
+        };
+        if let Some(extra) = arg(max_args) {
+            return Err(NotesAppError::UnexpectedArgument(
+                name.to_string(),
+                extra.to_string(),
+            ));
+        }
+        Ok(command)
+    }
+
+    /// Parses the action of D-Bus activation, arguments are either a part of the action
+    /// like `SHOW <uuid>` or are given separately
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the action is not a command
+    pub fn from_action(action: &str, args: &[String]) -> Result<Self, NotesAppError> {
+        let mut words = split_words(action)?;
+        words.extend_from_slice(args);
+        match words.split_first() {
+            Some((name, args)) => Self::try_from_args(name, args),
+            None => Err(NotesAppError::ParseError(action.to_string())),
+        }
+    }
+
+    /// Makes the file to import or export absolute,
+    /// the running notes-service might have another working directory
+    #[must_use]
+    pub fn with_absolute_file(self) -> Self {
+        match self {
+            Command::ImportNotes(Some((file, format))) => {
+                Command::ImportNotes(Some((absolute(file), format)))
+            }
+            Command::ExportNotes(Some((file, format))) => {
+                Command::ExportNotes(Some((absolute(file), format)))
+            }
+            command => command,
+        }
+    }
+}
+
+impl std::fmt::Display for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())?;
+        for arg in self.args() {
+            write!(f, " {}", quote(&arg))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Command {
+    type Err = NotesAppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_action(s, &[])
+    }
+}
+
+pub(super) fn absolute(path: String) -> String {
+    std::path::absolute(&path).map_or(path, |path| path.display().to_string())
+}
+
+fn file_args(
+    name: &str,
+    file: Option<&str>,
+    format: Option<&str>,
+) -> Result<Option<(String, NotesFormat)>, NotesAppError> {
+    let format = format
+        .map(|format| {
+            NotesFormat::from_str(format).map_err(|e| {
+                NotesAppError::InvalidArgument(name.to_string(), format.to_string(), e.to_string())
+            })
+        })
+        .transpose()?
+        .unwrap_or_default();
+    Ok(file.map(|file| (file.to_string(), format)))
+}
+
+// the argument is double-quoted if it is empty or has spaces, quotes or backslashes
+fn quote(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains(|c: char| c.is_whitespace() || c == '"' || c == '\\') {
+        return arg.to_string();
+    }
+    let mut quoted = String::with_capacity(arg.len() + 2);
+    quoted.push('"');
+    for c in arg.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn split_words(line: &str) -> Result<Vec<String>, NotesAppError> {
+    let unterminated = || NotesAppError::ParseError(line.to_string());
+    let mut words = Vec::new();
+    let mut chars = line.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let Some(first) = chars.next() else {
+            return Ok(words);
+        };
+        let mut word = String::new();
+        if first == '"' {
+            loop {
+                match chars.next().ok_or_else(unterminated)? {
+                    '"' => break,
+                    '\\' => word.push(chars.next().ok_or_else(unterminated)?),
+                    c => word.push(c),
+                }
+            }
+            // the closing quote ends the word
+            if chars.peek().is_some_and(|c| !c.is_whitespace()) {
+                return Err(unterminated());
+            }
+        } else {
+            word.push(first);
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                word.push(c);
+            }
+        }
+        words.push(word);
+    }
+}
+
+#[test]
+fn command_to_string_and_back() {
+    let note_id = Uuid::new_v4();
+    let commands = [
+        Command::Ignored,
+        Command::Connect,
+        Command::Quit,
+        Command::NewNote(String::new(), String::new()),
+        Command::NewNote("buy milk".to_string(), String::new()),
+        Command::NewNote("say \"hi\"\nC:\\".to_string(), "Green".to_string()),
+        Command::LoadNotes,
+        Command::SaveNotes,
+        Command::ImportNotes(None),
+        Command::ImportNotes(Some(("/tmp/my notes".to_string(), NotesFormat::Json))),
+        Command::ExportNotes(None),
+        Command::ExportNotes(Some((
+            "stickynotes".to_string(),
+            NotesFormat::IndicatorStickyNotes,
+        ))),
+        Command::ShowAllNotes,
+        Command::HideAllNotes,
+        Command::LockAll,
+        Command::ShowNote(note_id),
+        Command::HideNote(note_id),
+        Command::LockNote(note_id),
+        Command::ShowTagged("work".to_string()),
+        Command::RestoreNotes,
+        Command::SearchNotes,
+        Command::OpenSettings,
+        Command::OpenAbout,
+    ];
+    for command in commands {
+        assert!(
+            command == Command::Ignored || Command::NAMES.contains(&command.name()),
+            "{command}"
+        );
+        let text = command.to_string();
+        assert_eq!(Command::from_str(&text), Ok(command.clone()), "{text}");
+        // D-Bus activation might give arguments separately
+        assert_eq!(
+            Command::from_action(command.name(), &command.args()),
+            Ok(command)
+        );
+    }
+}
+
+#[test]
+fn parse_command_arguments() {
+    let note_id = Uuid::new_v4();
+    assert_eq!(
+        Command::from_str(&format!("SHOW {note_id}")),
+        Ok(Command::ShowNote(note_id))
+    );
+    assert_eq!(
+        Command::from_str(r#"NEW "buy milk" Green"#),
+        Ok(Command::NewNote(
+            "buy milk".to_string(),
+            "Green".to_string()
+        ))
+    );
+    assert_eq!(
+        Command::from_str("IMPORT stickynotes"),
+        Ok(Command::ImportNotes(Some((
+            "stickynotes".to_string(),
+            NotesFormat::IndicatorStickyNotes
+        ))))
+    );
+    assert_eq!(
+        Command::from_action("NEW", &["text".to_string()]),
+        Ok(Command::NewNote("text".to_string(), String::new()))
+    );
+    assert_eq!(
+        Command::from_str("TAG #Work"),
+        Ok(Command::ShowTagged("work".to_string()))
+    );
+    assert!(matches!(
+        Command::from_str("TAG"),
+        Err(NotesAppError::ParseError(_))
+    ));
+    assert!(matches!(
+        Command::from_str("LOCK note"),
+        Err(NotesAppError::InvalidArgument(..))
+    ));
+    assert!(matches!(
+        Command::from_str("IMPORT notes xml"),
+        Err(NotesAppError::InvalidArgument(..))
+    ));
+    assert!(matches!(
+        Command::from_str("SAVE now"),
+        Err(NotesAppError::UnexpectedArgument(..))
+    ));
+    assert!(matches!(
+        Command::from_str(&format!("HIDE {note_id} {note_id}")),
+        Err(NotesAppError::UnexpectedArgument(..))
+    ));
+    assert!(matches!(
+        Command::from_str(r#"NEW "unterminated"#),
+        Err(NotesAppError::ParseError(_))
+    ));
+    assert!(matches!(
+        Command::from_str("save"),
+        Err(NotesAppError::ParseError(_))
+    ));
+    assert!(matches!(
+        Command::from_str(""),
+        Err(NotesAppError::ParseError(_))
+    ));
+}
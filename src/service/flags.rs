@@ -0,0 +1,222 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::{Command, command::absolute};
+use crate::notes::NotesFormat;
+use std::{path::PathBuf, str::FromStr};
+use thiserror::Error;
+
+pub const USAGE: &str = "\
+Usage: notes-service [OPTIONS]
+
+Options:
+  --data-dir <dir>          keep notes in <dir>/notes.json instead of the config
+  --import <file>           import notes from <file> on start
+  --format <format>         format of the imported file: indicator-stickynotes (default), json
+  --new <text>              create a new note with <text>
+  --style <style>           style (name or UUID) of the note created with --new
+  --no-windows              don't open sticky windows on start
+  --headless                run without windows and without display at all
+  --log-level <level>       error, warn, info, debug or trace
+  --config-profile <name>   use separate config named by <name>
+  -h, --help                print this help
+
+If notes-service is already running --new and --import are forwarded to it,
+other options are applied only when the service starts.";
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ServiceFlagsError {
+    #[error("unknown argument {0}")]
+    UnknownArgument(String),
+    #[error("{0} requires a value")]
+    MissingValue(String),
+    #[error("invalid value {1} of {0}")]
+    InvalidValue(String, String),
+    #[error("{0} requires {1}")]
+    Dependent(String, String),
+}
+
+/// Command line arguments of notes-service
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ServiceFlags {
+    pub data_dir: Option<PathBuf>,
+    pub import: Option<(String, NotesFormat)>, // (file, format)
+    pub no_windows: bool,
+    pub headless: bool,
+    pub log_level: Option<tracing::Level>,
+    pub config_profile: Option<String>,
+    pub new_note: Option<(String, String)>, // (content, style)
+    pub help: bool,
+    // the command forwarded to the running instance
+    pub(super) command: Option<Command>,
+}
+
+impl ServiceFlags {
+    /// Parses command line arguments without the program name
+    ///
+    /// # Errors
+    ///
+    /// Returns error on unknown arguments, missing or invalid values
+    pub fn try_parse<I>(args: I) -> Result<Self, ServiceFlagsError>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut flags = Self::default();
+        let mut format = None;
+        let mut style = None;
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| ServiceFlagsError::MissingValue(arg.clone()))
+            };
+            match arg.as_str() {
+                "--data-dir" => flags.data_dir = Some(PathBuf::from(value()?)),
+                "--import" => flags.import = Some((absolute(value()?), NotesFormat::default())),
+                "--format" => {
+                    let v = value()?;
+                    format = Some(
+                        NotesFormat::from_str(&v)
+                            .map_err(|_| ServiceFlagsError::InvalidValue(arg, v))?,
+                    );
+                }
+                "--new" => flags.new_note = Some((value()?, String::new())),
+                "--style" => style = Some(value()?),
+                "--no-windows" => flags.no_windows = true,
+                "--headless" => flags.headless = true,
+                "--log-level" => {
+                    let v = value()?;
+                    flags.log_level = Some(
+                        tracing::Level::from_str(&v)
+                            .map_err(|_| ServiceFlagsError::InvalidValue(arg, v))?,
+                    );
+                }
+                "--config-profile" => flags.config_profile = Some(value()?),
+                "-h" | "--help" => flags.help = true,
+                _ => return Err(ServiceFlagsError::UnknownArgument(arg)),
+            }
+        }
+        if let Some(format) = format {
+            let (_, import_format) = flags.import.as_mut().ok_or_else(|| {
+                ServiceFlagsError::Dependent("--format".to_string(), "--import".to_string())
+            })?;
+            *import_format = format;
+        }
+        if let Some(style) = style {
+            let (_, new_style) = flags.new_note.as_mut().ok_or_else(|| {
+                ServiceFlagsError::Dependent("--style".to_string(), "--new".to_string())
+            })?;
+            *new_style = style;
+        }
+        flags.command = if let Some((content, style)) = &flags.new_note {
+            Some(Command::NewNote(content.clone(), style.clone()))
+        } else {
+            flags
+                .import
+                .as_ref()
+                .map(|file_args| Command::ImportNotes(Some(file_args.clone())))
+        };
+        Ok(flags)
+    }
+}
+
+/// The running instance of notes-service gets `--new` (or `--import` if there is no `--new`)
+/// as an action of D-Bus activation, arguments are a part of the action
+#[cfg(feature = "gui")]
+impl cosmic::app::CosmicFlags for ServiceFlags {
+    type SubCommand = Command;
+    type Args = Vec<String>;
+
+    fn action(&self) -> Option<&Command> {
+        self.command.as_ref()
+    }
+
+    fn args(&self) -> Vec<&str> {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+fn parse(args: &[&str]) -> Result<ServiceFlags, ServiceFlagsError> {
+    ServiceFlags::try_parse(args.iter().map(ToString::to_string))
+}
+
+#[test]
+fn parse_service_flags() {
+    assert_eq!(parse(&[]), Ok(ServiceFlags::default()));
+    assert_eq!(
+        parse(&[
+            "--data-dir",
+            "/tmp/notes",
+            "--import",
+            "/tmp/notes.json",
+            "--format",
+            "json",
+            "--no-windows",
+            "--headless",
+            "--log-level",
+            "debug",
+            "--config-profile",
+            "work",
+        ]),
+        Ok(ServiceFlags {
+            data_dir: Some(PathBuf::from("/tmp/notes")),
+            import: Some(("/tmp/notes.json".to_string(), NotesFormat::Json)),
+            no_windows: true,
+            headless: true,
+            log_level: Some(tracing::Level::DEBUG),
+            config_profile: Some("work".to_string()),
+            command: Some(Command::ImportNotes(Some((
+                "/tmp/notes.json".to_string(),
+                NotesFormat::Json
+            )))),
+            ..Default::default()
+        })
+    );
+    let flags = parse(&["--style", "Green", "--new", "buy milk"]).expect("valid arguments");
+    assert_eq!(
+        flags.new_note,
+        Some(("buy milk".to_string(), "Green".to_string()))
+    );
+    assert_eq!(
+        flags.command.as_ref(),
+        Some(&Command::NewNote(
+            "buy milk".to_string(),
+            "Green".to_string()
+        ))
+    );
+    let flags = parse(&["--import", "stickynotes"]).expect("valid arguments");
+    let import_file = std::path::absolute("stickynotes")
+        .expect("valid path")
+        .display()
+        .to_string();
+    assert_eq!(
+        flags.command.as_ref(),
+        Some(&Command::ImportNotes(Some((
+            import_file,
+            NotesFormat::IndicatorStickyNotes
+        ))))
+    );
+
+    assert_eq!(
+        parse(&["--new"]),
+        Err(ServiceFlagsError::MissingValue("--new".to_string()))
+    );
+    assert_eq!(
+        parse(&["--format", "json"]),
+        Err(ServiceFlagsError::Dependent(
+            "--format".to_string(),
+            "--import".to_string()
+        ))
+    );
+    assert_eq!(
+        parse(&["--log-level", "loud"]),
+        Err(ServiceFlagsError::InvalidValue(
+            "--log-level".to_string(),
+            "loud".to_string()
+        ))
+    );
+    assert_eq!(
+        parse(&["--windows"]),
+        Err(ServiceFlagsError::UnknownArgument("--windows".to_string()))
+    );
+}
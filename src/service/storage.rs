@@ -0,0 +1,164 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::ServiceFlags;
+use crate::{
+    config::{Config, config_id},
+    notes::{
+        Attachment, NotesCollection, collect_garbage, config_attachments_dir, config_history_file,
+        file_attachments_dir, file_history_file,
+    },
+};
+use cosmic_config::CosmicConfigEntry;
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+/// The name of notes file in the directory given by `--data-dir`
+const DATA_FILE_NAME: &str = "notes.json";
+
+/// The place where notes-service keeps notes: either the config or the data file
+#[derive(Debug, Clone)]
+pub struct NotesStorage {
+    // The name of the config selected with --config-profile
+    config_id: &'static str,
+    // Notes are kept in this file instead of config if --data-dir is set
+    data_file: Option<PathBuf>,
+    // Files attached to notes are kept here, next to the notes
+    attachments_dir: PathBuf,
+    // Previous versions of the content are kept here, next to the notes
+    history_file: PathBuf,
+}
+
+impl NotesStorage {
+    #[must_use]
+    pub fn new(flags: &ServiceFlags) -> Self {
+        let config_id = config_id(flags.config_profile.as_deref());
+        let data_file = flags.data_dir.as_ref().map(|dir| dir.join(DATA_FILE_NAME));
+        let (attachments_dir, history_file) = match &data_file {
+            Some(data_file) => (
+                file_attachments_dir(data_file),
+                file_history_file(data_file),
+            ),
+            None => (
+                config_attachments_dir(&config_id),
+                config_history_file(&config_id),
+            ),
+        };
+        Self {
+            // the config ID lives as long as the service does
+            config_id: Box::leak(config_id.into_boxed_str()),
+            data_file,
+            attachments_dir,
+            history_file,
+        }
+    }
+
+    pub fn config_id(&self) -> &'static str {
+        self.config_id
+    }
+
+    pub fn attachments_dir(&self) -> &Path {
+        &self.attachments_dir
+    }
+
+    /// Returns the data file or the config notes are kept in, the running service is talked to
+    /// only by clients using the same storage
+    pub fn location(&self) -> String {
+        self.data_file.as_ref().map_or_else(
+            || self.config_id.to_string(),
+            |data_file| data_file.display().to_string(),
+        )
+    }
+
+    pub fn load_config(&self) -> Config {
+        Config::load(self.config_id)
+    }
+
+    /// Loads notes from the data file or from `config_notes` if there is no data file,
+    /// missing or broken notes are replaced with default collection
+    pub fn load_notes_or_default(&self, config_notes: &str) -> NotesCollection {
+        self.try_load_notes(config_notes)
+            .map_err(|e| tracing::error!("failed loading notes: {e}"))
+            .unwrap_or_default()
+    }
+
+    /// Loads notes from the data file or from `config_notes` if there is no data file,
+    /// missing notes are the default collection. Notes lose their history if it is broken.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the data file is not readable or notes are broken
+    pub fn try_load_notes(
+        &self,
+        config_notes: &str,
+    ) -> Result<NotesCollection, Box<dyn std::error::Error>> {
+        let (json, source) = match &self.data_file {
+            Some(data_file) => match std::fs::read_to_string(data_file) {
+                Ok(json) => (json, data_file.display().to_string()),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    return Ok(NotesCollection::default());
+                }
+                Err(e) => return Err(format!("{}: {e}", data_file.display()).into()),
+            },
+            None => (
+                config_notes.to_string(),
+                format!("{}/v{}/notes", self.config_id, Config::VERSION),
+            ),
+        };
+        if json.is_empty() {
+            return Ok(NotesCollection::default());
+        }
+        let mut notes = NotesCollection::try_read(&json).map_err(|e| format!("{source}: {e}"))?;
+        match std::fs::read_to_string(&self.history_file) {
+            Ok(history) => {
+                if let Err(e) = notes.try_read_history(&history) {
+                    tracing::error!("failed reading {}: {e}", self.history_file.display());
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => tracing::error!("failed reading {}: {e}", self.history_file.display()),
+        }
+        Ok(notes)
+    }
+
+    pub fn save_notes(
+        &self,
+        notes: &mut NotesCollection,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let json = notes.try_write()?;
+        let history = notes.try_write_history()?;
+        if let Some(data_file) = &self.data_file {
+            if let Some(data_dir) = data_file.parent() {
+                std::fs::create_dir_all(data_dir)?;
+            }
+            std::fs::write(data_file, json)?;
+        } else {
+            Config::store_notes(self.config_id, json)?;
+        }
+        if let Some(history_dir) = self.history_file.parent() {
+            std::fs::create_dir_all(history_dir)?;
+        }
+        std::fs::write(&self.history_file, history)?;
+        notes.commit_changes();
+        Ok(())
+    }
+
+    /// Removes attached files which live notes don't refer to, it is done once deleted notes
+    /// are dropped for good and can't be restored anymore
+    pub fn collect_garbage(&self, notes: &NotesCollection) {
+        let files: HashSet<&str> = notes
+            .iter_notes()
+            .flat_map(|(_, note)| note.get_attachments())
+            .map(Attachment::get_file)
+            .collect();
+        match collect_garbage(&self.attachments_dir, &files) {
+            Ok(0) => {}
+            Ok(removed) => tracing::debug!("removed {removed} unused attachments"),
+            Err(e) => tracing::error!(
+                "failed removing unused attachments from {}: {e}",
+                self.attachments_dir.display()
+            ),
+        }
+    }
+}